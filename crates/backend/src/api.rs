@@ -0,0 +1,691 @@
+use std::fmt;
+use std::str::FromStr;
+
+use actix_web::http::{StatusCode, header};
+use actix_web::{HttpRequest, HttpResponse, ResponseError, delete, get, post, web};
+use diesel_async::{AsyncPgConnection, pooled_connection::bb8::Pool};
+use log::error;
+use rand::Rng;
+use serde::Deserialize;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::digests::TransactionDigest;
+
+use crate::dto;
+use crate::handlers::{
+    assess_cap_risk, fetch_address_overview, fetch_cap_audit, fetch_cap_ownership,
+    fetch_dependency_risk, fetch_package_diff, fetch_package_graph, fetch_transaction_activity,
+};
+use crate::live::{self, LiveEvent, LiveFeed};
+use crate::models;
+use crate::pagination::{self, Page, PageQuery, TransferCursor};
+use crate::query;
+use phantom_cap_db::events::{self, EventFilter, PackageFilter};
+use phantom_cap_db::webhooks;
+
+type DbPool = Pool<AsyncPgConnection>;
+
+type ApiResult<T> = Result<web::Json<T>, ApiError>;
+
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    Unauthorized(String),
+    Internal,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::BadRequest(message)
+            | ApiError::NotFound(message)
+            | ApiError::Unauthorized(message) => write!(f, "{}", message),
+            // internal details are only logged.
+            ApiError::Internal => write!(f, "Internal server error"),
+        }
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let error = match self {
+            ApiError::BadRequest(_) => "bad_request",
+            ApiError::NotFound(_) => "not_found",
+            ApiError::Unauthorized(_) => "unauthorized",
+            ApiError::Internal => "internal",
+        };
+
+        let mut response = HttpResponse::build(self.status_code());
+        if let ApiError::Unauthorized(_) = self {
+            response.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+        }
+
+        response.json(dto::ErrorBody {
+            error,
+            message: self.to_string(),
+        })
+    }
+}
+
+impl From<anyhow::Error> for ApiError {
+    fn from(err: anyhow::Error) -> Self {
+        match err.downcast_ref::<diesel::result::Error>() {
+            Some(diesel::result::Error::NotFound) => ApiError::NotFound(err.to_string()),
+            _ => {
+                error!("API query failed: {:#}", err);
+                ApiError::Internal
+            }
+        }
+    }
+}
+
+fn parse_object_id(id: &str) -> Result<String, ApiError> {
+    ObjectID::from_hex_literal(id)
+        .map(|object_id| object_id.to_hex_literal())
+        .map_err(|e| ApiError::BadRequest(format!("Invalid object id: {}", e)))
+}
+
+fn parse_address(address: &str) -> Result<String, ApiError> {
+    SuiAddress::from_hex_literal(address)
+        .map(|address| address.to_hex_literal())
+        .map_err(|e| ApiError::BadRequest(format!("Invalid address: {}", e)))
+}
+
+fn parse_tx_digest(digest: &str) -> Result<String, ApiError> {
+    TransactionDigest::from_str(digest)
+        .map(|digest| digest.to_string())
+        .map_err(|e| ApiError::BadRequest(format!("Invalid transaction digest: {}", e)))
+}
+
+fn pool_error<E: fmt::Display>(err: E) -> ApiError {
+    error!("Failed to get db connection: {}", err);
+    ApiError::Internal
+}
+
+/// Rejects malformed query strings with the API error body.
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default().error_handler(|err, _| ApiError::BadRequest(err.to_string()).into())
+}
+
+/// Rejects malformed JSON bodies with the API error body.
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default().error_handler(|err, _| ApiError::BadRequest(err.to_string()).into())
+}
+
+#[get("/counts")]
+pub async fn get_counts(pool: web::Data<DbPool>) -> ApiResult<dto::Counts> {
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    Ok(web::Json(dto::Counts {
+        upgrade_caps: query::get_upgrade_caps_count(&mut conn).await?,
+        packages: query::get_packages_count(&mut conn).await?,
+        transfers: query::get_transfers_count(&mut conn).await?,
+    }))
+}
+
+#[derive(Deserialize)]
+struct SearchQuery {
+    id: String,
+}
+
+#[get("/search")]
+pub async fn search(
+    pool: web::Data<DbPool>,
+    query: web::Query<SearchQuery>,
+) -> ApiResult<dto::SearchResult> {
+    let object_id = parse_object_id(&query.id)?;
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    match query::get_cap_by_id(&mut conn, &object_id).await {
+        Ok(cap) => {
+            return Ok(web::Json(dto::SearchResult {
+                kind: dto::SearchKind::Cap,
+                id: cap.object_id,
+            }));
+        }
+        Err(e) if query::is_not_found(&e) => {}
+        Err(e) => return Err(e.into()),
+    }
+
+    let package = query::get_package_by_id(&mut conn, &object_id).await?;
+    Ok(web::Json(dto::SearchResult {
+        kind: dto::SearchKind::Package,
+        id: package.package_id,
+    }))
+}
+
+#[get("/caps/{id}")]
+pub async fn get_cap(pool: web::Data<DbPool>, id: web::Path<String>) -> ApiResult<dto::Cap> {
+    let cap_id = parse_object_id(&id)?;
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    let cap = query::get_cap_by_id(&mut conn, &cap_id).await?;
+    let state = query::get_cap_state(&mut conn, &cap_id).await?;
+    let created_by = match query::get_cap_first_transfer(&mut conn, &cap_id).await {
+        Ok(transfer) => Some(transfer.new_owner_address),
+        Err(e) if query::is_not_found(&e) => None,
+        Err(e) => return Err(e.into()),
+    };
+    let ownership = fetch_cap_ownership(&mut conn, &state).await;
+    let risk = assess_cap_risk(&state, &ownership.class);
+    let upgrade_audit = fetch_cap_audit(&mut conn, &state).await?;
+
+    Ok(web::Json(dto::Cap {
+        destroyed: dto::CapDestruction::from_state(&state),
+        package_id: Some(state.package_id),
+        version: Some(state.version),
+        policy: state.policy,
+        owner: ownership.owner_address,
+        owner_class: ownership.class,
+        holder: ownership.holder.map(dto::CapHolder::from),
+        created_by,
+        created_seq_checkpoint: cap.created_seq_checkpoint,
+        created_tx_digest: cap.created_tx_digest.clone(),
+        created_at: cap.created_at,
+        last_activity_checkpoint: state.last_activity_checkpoint,
+        risk,
+        upgrade_audit,
+        object_id: cap.object_id,
+    }))
+}
+
+#[get("/caps/{id}/versions")]
+pub async fn get_cap_versions(
+    pool: web::Data<DbPool>,
+    id: web::Path<String>,
+    page: web::Query<PageQuery>,
+) -> ApiResult<dto::Page<dto::CapVersion>> {
+    let cap_id = parse_object_id(&id)?;
+    let cursor = page
+        .cursor
+        .as_deref()
+        .map(pagination::parse_version_cursor)
+        .transpose()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let limit = page.limit();
+
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    // unknown caps have no history, answer with not found instead of an empty list.
+    query::get_cap_by_id(&mut conn, &cap_id).await?;
+    let versions = query::get_cap_versions_history(&mut conn, &cap_id, cursor, limit + 1).await?;
+    let versions = Page::from_rows(versions, limit, |v| v.version.to_string());
+
+    Ok(web::Json(dto::Page::from(versions)))
+}
+
+#[get("/caps/{id}/transfers")]
+pub async fn get_cap_transfers(
+    pool: web::Data<DbPool>,
+    id: web::Path<String>,
+    page: web::Query<PageQuery>,
+) -> ApiResult<dto::Page<dto::CapTransfer>> {
+    let cap_id = parse_object_id(&id)?;
+    let cursor = page
+        .cursor
+        .as_deref()
+        .map(str::parse::<TransferCursor>)
+        .transpose()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let limit = page.limit();
+
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    query::get_cap_by_id(&mut conn, &cap_id).await?;
+    let transfers =
+        query::get_cap_transfers_history(&mut conn, &cap_id, cursor.as_ref(), limit + 1).await?;
+    let transfers = Page::from_rows(transfers, limit, |t| TransferCursor::from(t).to_string());
+
+    Ok(web::Json(dto::Page::from(transfers)))
+}
+
+#[get("/caps/{id}/policies")]
+pub async fn get_cap_policies(
+    pool: web::Data<DbPool>,
+    id: web::Path<String>,
+) -> ApiResult<Vec<dto::CapPolicyChange>> {
+    let cap_id = parse_object_id(&id)?;
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    query::get_cap_by_id(&mut conn, &cap_id).await?;
+    let policies = query::get_cap_policy_history(&mut conn, &cap_id).await?;
+
+    Ok(web::Json(
+        policies
+            .into_iter()
+            .map(dto::CapPolicyChange::from)
+            .collect(),
+    ))
+}
+
+#[get("/packages/{id}")]
+pub async fn get_package(
+    pool: web::Data<DbPool>,
+    id: web::Path<String>,
+) -> ApiResult<dto::Package> {
+    let package_id = parse_object_id(&id)?;
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    let p = query::get_package_by_id(&mut conn, &package_id).await?;
+    let state = query::get_cap_state(&mut conn, &p.object_id).await?;
+    let lineage = query::get_package_lineage(&mut conn, &state.object_id).await?;
+    let latest = lineage.last().unwrap_or(&p).clone();
+    let modules = query::get_package_modules(&mut conn, &p.package_id).await?;
+    let type_origins = query::get_package_type_origins(&mut conn, &p.package_id).await?;
+    let linkage = query::get_package_linkage(&mut conn, &p.package_id).await?;
+    let ownership = fetch_cap_ownership(&mut conn, &state).await;
+    let risk = assess_cap_risk(&state, &ownership.class);
+    let (_, risk_roll_up) = fetch_dependency_risk(&mut conn, &p.package_id).await?;
+
+    Ok(web::Json(dto::Package {
+        package_id: p.package_id,
+        upgrade_cap_id: p.object_id,
+        version: p.version,
+        publisher: p.publisher,
+        seq_checkpoint: p.seq_checkpoint,
+        tx_digest: p.tx_digest,
+        timestamp: p.timestamp,
+        latest_package_id: latest.package_id,
+        latest_version: latest.version,
+        lineage: lineage.into_iter().map(dto::CapVersion::from).collect(),
+        modules: dto::PackageModule::from_rows(modules, &type_origins),
+        dependencies: linkage
+            .into_iter()
+            .map(dto::PackageDependency::from)
+            .collect(),
+        cap_owner_class: ownership.class,
+        risk,
+        risk_roll_up,
+    }))
+}
+
+#[get("/packages/{id}/graph")]
+pub async fn get_package_graph(
+    pool: web::Data<DbPool>,
+    id: web::Path<String>,
+) -> ApiResult<dto::PackageGraph> {
+    let package_id = parse_object_id(&id)?;
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    let graph = fetch_package_graph(&mut conn, &package_id).await?;
+
+    Ok(web::Json(dto::PackageGraph {
+        risk_roll_up: graph.risk_roll_up,
+        dependencies: graph
+            .dependencies
+            .into_iter()
+            .map(dto::GraphPackage::from)
+            .collect(),
+        dependents: graph
+            .dependents
+            .into_iter()
+            .map(dto::GraphPackage::from)
+            .collect(),
+    }))
+}
+
+#[get("/packages/{id}/diff/{other}")]
+pub async fn get_package_diff(
+    pool: web::Data<DbPool>,
+    path: web::Path<(String, String)>,
+) -> ApiResult<dto::PackageDiff> {
+    let (id, other) = path.into_inner();
+    let from = parse_object_id(&id)?;
+    let to = parse_object_id(&other)?;
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    let modules = fetch_package_diff(&mut conn, &from, &to)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Package bytecode is not indexed".to_string()))?;
+
+    Ok(web::Json(dto::PackageDiff { from, to, modules }))
+}
+
+#[get("/addresses/{address}")]
+pub async fn get_address(
+    pool: web::Data<DbPool>,
+    address: web::Path<String>,
+) -> ApiResult<dto::Address> {
+    let address = parse_address(&address)?;
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    let overview = fetch_address_overview(&mut conn, &address).await?;
+
+    Ok(web::Json(dto::Address {
+        address,
+        owner_class: overview.owner_class,
+        risk_summary: overview.risk_summary,
+        current_caps: overview
+            .current_caps
+            .into_iter()
+            .map(dto::AddressCap::from)
+            .collect(),
+        held_caps: overview
+            .held_caps
+            .into_iter()
+            .map(dto::AddressCap::from)
+            .collect(),
+        previous_caps: overview
+            .previous_caps
+            .into_iter()
+            .map(dto::AddressCap::from)
+            .collect(),
+        published: overview
+            .published
+            .into_iter()
+            .map(dto::PublishedPackage::from)
+            .collect(),
+    }))
+}
+
+#[get("/transactions/{digest}")]
+pub async fn get_transaction(
+    pool: web::Data<DbPool>,
+    digest: web::Path<String>,
+) -> ApiResult<dto::Transaction> {
+    let tx_digest = parse_tx_digest(&digest)?;
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    let activity = fetch_transaction_activity(&mut conn, &tx_digest).await?;
+    let (seq_checkpoint, timestamp) = activity.checkpoint().ok_or_else(|| {
+        ApiError::NotFound("Transaction has no indexed UpgradeCap activity".to_string())
+    })?;
+
+    Ok(web::Json(dto::Transaction {
+        tx_digest,
+        seq_checkpoint,
+        timestamp,
+        created_caps: activity.created.into_iter().map(|c| c.object_id).collect(),
+        versions: activity
+            .versions
+            .into_iter()
+            .map(|v| dto::TransactionVersion {
+                upgrade_cap_id: v.object_id,
+                upgrade_class: activity
+                    .upgrade_classes
+                    .get(&v.package_id)
+                    .copied()
+                    .flatten(),
+                version: v.version,
+                package_id: v.package_id,
+                publisher: v.publisher,
+            })
+            .collect(),
+        transfers: activity
+            .transfers
+            .into_iter()
+            .map(|t| dto::TransactionTransfer {
+                holder: activity
+                    .holders
+                    .iter()
+                    .find(|h| h.object_id == t.object_id)
+                    .cloned()
+                    .map(dto::CapHolder::from),
+                upgrade_cap_id: t.object_id,
+                from: t.old_owner_address,
+                to: t.new_owner_address,
+            })
+            .collect(),
+        policy_changes: activity
+            .policy_changes
+            .into_iter()
+            .map(|p| dto::TransactionPolicyChange {
+                upgrade_cap_id: p.object_id,
+                old_policy: p.old_policy,
+                new_policy: p.new_policy,
+            })
+            .collect(),
+        destroyed_caps: activity
+            .destroyed
+            .into_iter()
+            .map(|c| c.object_id)
+            .collect(),
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct EventsQuery {
+    package: Option<String>,
+    cap: Option<String>,
+    owner: Option<String>,
+}
+
+async fn events_filter(pool: &DbPool, params: &EventsQuery) -> Result<EventFilter, ApiError> {
+    let package = match params.package.as_deref() {
+        Some(id) => {
+            let package_id = parse_object_id(id)?;
+            let mut conn = pool.get().await.map_err(pool_error)?;
+            // an indexed package follows its cap, later upgrades included.
+            Some(
+                PackageFilter::load(&mut conn, package_id)
+                    .await
+                    .map_err(anyhow::Error::from)?,
+            )
+        }
+        None => None,
+    };
+
+    Ok(EventFilter {
+        cap_id: params.cap.as_deref().map(parse_object_id).transpose()?,
+        package,
+        owner: params.owner.as_deref().map(parse_address).transpose()?,
+        kinds: vec![],
+    })
+}
+
+fn render_event(live: &LiveEvent) -> Option<String> {
+    serde_json::to_string(&dto::CapEvent::from(live.event.clone())).ok()
+}
+
+/// Server-Sent Events of new versions, transfers and policy changes.
+#[get("/events")]
+pub async fn get_events(
+    pool: web::Data<DbPool>,
+    feed: web::Data<LiveFeed>,
+    params: web::Query<EventsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let filter = events_filter(&pool, &params).await?;
+
+    Ok(live::sse_response(feed.subscribe(filter), render_event))
+}
+
+/// Same events as `/events`, over a WebSocket.
+#[get("/events/ws")]
+pub async fn get_events_ws(
+    req: HttpRequest,
+    payload: web::Payload,
+    pool: web::Data<DbPool>,
+    feed: web::Data<LiveFeed>,
+    params: web::Query<EventsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let filter = events_filter(&pool, &params).await?;
+
+    live::websocket_response(&req, payload, feed.subscribe(filter), render_event)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))
+}
+
+/// Bearer token managing webhooks, which are disabled without one.
+pub struct WebhookToken(pub Option<String>);
+
+impl WebhookToken {
+    fn authorize(&self, req: &HttpRequest) -> Result<(), ApiError> {
+        let Some(expected) = &self.0 else {
+            return Err(ApiError::NotFound("Webhooks are disabled".to_string()));
+        };
+
+        let given = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+
+        match given {
+            Some(given) if constant_time_eq(given.as_bytes(), expected.as_bytes()) => Ok(()),
+            _ => Err(ApiError::Unauthorized(
+                "A valid webhook token is required".to_string(),
+            )),
+        }
+    }
+}
+
+/// Compares tokens without leaking how much of them matched through timing.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn parse_webhook_id(id: &str) -> Result<i64, ApiError> {
+    id.parse()
+        .map_err(|_| ApiError::BadRequest(format!("Invalid webhook id: {}", id)))
+}
+
+#[derive(Deserialize)]
+pub struct NewWebhookBody {
+    url: String,
+    package: Option<String>,
+    cap: Option<String>,
+    owner: Option<String>,
+    #[serde(default)]
+    kinds: Vec<String>,
+}
+
+/// Registers a webhook, the response holds the secret its deliveries are signed with.
+#[post("/webhooks")]
+pub async fn create_webhook(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    token: web::Data<WebhookToken>,
+    body: web::Json<NewWebhookBody>,
+) -> Result<HttpResponse, ApiError> {
+    token.authorize(&req)?;
+
+    let url = url::Url::parse(&body.url)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid url: {}", e)))?;
+    webhooks::check_url(&url).map_err(ApiError::BadRequest)?;
+
+    if let Some(kind) = body
+        .kinds
+        .iter()
+        .find(|k| !events::KINDS.contains(&k.as_str()))
+    {
+        return Err(ApiError::BadRequest(format!(
+            "Unknown event kind '{}', expected one of {}",
+            kind,
+            events::KINDS.join(", ")
+        )));
+    }
+
+    let mut kinds = body.kinds.clone();
+    kinds.sort();
+    kinds.dedup();
+
+    let secret = hex::encode(rand::thread_rng().r#gen::<[u8; 32]>());
+    let webhook = models::NewWebhookSubscription {
+        url: url.to_string(),
+        secret: secret.clone(),
+        package_id: body.package.as_deref().map(parse_object_id).transpose()?,
+        cap_id: body.cap.as_deref().map(parse_object_id).transpose()?,
+        owner_address: body.owner.as_deref().map(parse_address).transpose()?,
+        event_kinds: kinds,
+    };
+
+    let mut conn = pool.get().await.map_err(pool_error)?;
+    let webhook = query::create_webhook(&mut conn, &webhook).await?;
+
+    Ok(HttpResponse::Created().json(dto::CreatedWebhook {
+        webhook: dto::Webhook::from(webhook),
+        secret,
+    }))
+}
+
+#[get("/webhooks")]
+pub async fn get_webhooks(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    token: web::Data<WebhookToken>,
+) -> ApiResult<Vec<dto::Webhook>> {
+    token.authorize(&req)?;
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    let webhooks = query::get_webhooks(&mut conn).await?;
+
+    Ok(web::Json(
+        webhooks.into_iter().map(dto::Webhook::from).collect(),
+    ))
+}
+
+#[get("/webhooks/{id}")]
+pub async fn get_webhook(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    token: web::Data<WebhookToken>,
+    id: web::Path<String>,
+) -> ApiResult<dto::Webhook> {
+    token.authorize(&req)?;
+    let webhook_id = parse_webhook_id(&id)?;
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    let webhook = query::get_webhook(&mut conn, webhook_id).await?;
+
+    Ok(web::Json(dto::Webhook::from(webhook)))
+}
+
+/// Deletes a webhook, pending deliveries and the delivery log included.
+#[delete("/webhooks/{id}")]
+pub async fn delete_webhook(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    token: web::Data<WebhookToken>,
+    id: web::Path<String>,
+) -> Result<HttpResponse, ApiError> {
+    token.authorize(&req)?;
+    let webhook_id = parse_webhook_id(&id)?;
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    if !query::delete_webhook(&mut conn, webhook_id).await? {
+        return Err(ApiError::NotFound("Webhook not found".to_string()));
+    }
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Delivery log of a webhook, latest first.
+#[get("/webhooks/{id}/deliveries")]
+pub async fn get_webhook_deliveries(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    token: web::Data<WebhookToken>,
+    id: web::Path<String>,
+    page: web::Query<PageQuery>,
+) -> ApiResult<dto::Page<dto::WebhookDelivery>> {
+    token.authorize(&req)?;
+    let webhook_id = parse_webhook_id(&id)?;
+    let cursor = page
+        .cursor
+        .as_deref()
+        .map(pagination::parse_delivery_cursor)
+        .transpose()
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    let limit = page.limit();
+
+    let mut conn = pool.get().await.map_err(pool_error)?;
+
+    query::get_webhook(&mut conn, webhook_id).await?;
+    let deliveries =
+        query::get_webhook_deliveries(&mut conn, webhook_id, cursor, limit + 1).await?;
+    let deliveries = Page::from_rows(deliveries, limit, |d| d.id.to_string());
+
+    Ok(web::Json(dto::Page::from(deliveries)))
+}
+
+pub async fn not_found() -> Result<HttpResponse, ApiError> {
+    Err(ApiError::NotFound("Route not found".to_string()))
+}
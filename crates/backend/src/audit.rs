@@ -0,0 +1,178 @@
+use serde::Serialize;
+
+use crate::models::{UpgradeClassEnum, UpgradeCompatibilityPolicyEnum};
+
+/// Upgrades of a cap checked against its declared policy.
+#[derive(Debug, Clone, Serialize)]
+pub struct UpgradeAudit {
+    /// Widest change among the audited upgrades.
+    pub effective_class: Option<UpgradeClassEnum>,
+    pub audited: usize,
+    pub unaudited: usize,
+    /// Marked Compatible although every upgrade only changed dependencies.
+    pub dep_only_under_compatible: bool,
+    pub findings: Vec<String>,
+}
+
+/// Widest upgrade the policy allows, `None` when no upgrade is allowed.
+fn allowed_class(policy: &UpgradeCompatibilityPolicyEnum) -> Option<UpgradeClassEnum> {
+    match policy {
+        UpgradeCompatibilityPolicyEnum::Compatible => Some(UpgradeClassEnum::Compatible),
+        UpgradeCompatibilityPolicyEnum::Additive => Some(UpgradeClassEnum::Additive),
+        UpgradeCompatibilityPolicyEnum::DepOnly => Some(UpgradeClassEnum::DepOnly),
+        UpgradeCompatibilityPolicyEnum::Immutable => None,
+    }
+}
+
+/// Audits the upgrades of a cap, one entry per upgrade, `None` when it wasn't audited.
+pub fn audit(
+    policy: &UpgradeCompatibilityPolicyEnum,
+    upgrades: &[Option<UpgradeClassEnum>],
+) -> UpgradeAudit {
+    let classes = upgrades.iter().flatten().copied().collect::<Vec<_>>();
+    let effective_class = classes.iter().max().copied();
+    let unaudited = upgrades.len() - classes.len();
+    let mut findings = vec![];
+
+    if upgrades.is_empty() {
+        findings.push("Never upgraded since publish".to_string());
+    }
+
+    // the policy can only be restricted, wider upgrades predate the restriction.
+    let allowed = allowed_class(policy);
+    let beyond_policy = classes
+        .iter()
+        .filter(|class| allowed.is_none_or(|allowed| **class > allowed))
+        .count();
+    if beyond_policy > 0 {
+        findings.push(format!(
+            "{} upgrade(s) went beyond the current {} policy, before it was restricted",
+            beyond_policy, policy
+        ));
+    }
+
+    let dep_only_under_compatible = *policy == UpgradeCompatibilityPolicyEnum::Compatible
+        && effective_class == Some(UpgradeClassEnum::DepOnly);
+
+    match effective_class {
+        _ if dep_only_under_compatible => findings.push(
+            "Marked Compatible but every upgrade only changed dependencies, \
+             the policy could be restricted to DepOnly"
+                .to_string(),
+        ),
+        Some(UpgradeClassEnum::Additive)
+            if *policy == UpgradeCompatibilityPolicyEnum::Compatible =>
+        {
+            findings.push(
+                "Upgrades only added code, the policy could be restricted to Additive".to_string(),
+            )
+        }
+        Some(class) if beyond_policy == 0 => findings.push(format!(
+            "Upgrades stayed within the {} policy, widest change was {}",
+            policy, class
+        )),
+        _ => {}
+    }
+
+    if unaudited > 0 {
+        findings.push(format!(
+            "{} upgrade(s) not audited, the previous version isn't indexed",
+            unaudited
+        ));
+    }
+
+    UpgradeAudit {
+        effective_class,
+        audited: classes.len(),
+        unaudited,
+        dep_only_under_compatible,
+        findings,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WITHIN: &str = "within";
+    const BEYOND: &str = "beyond";
+    const RESTRICT_DEP_ONLY: &str = "restrict to DepOnly";
+    const RESTRICT_ADDITIVE: &str = "restrict to Additive";
+
+    /// Finding of a single audited upgrade, rows follow `UpgradeCompatibilityPolicyEnum::ALL`
+    /// and columns `UpgradeClassEnum::ALL`.
+    const CELLS: [[&str; 3]; 4] = [
+        [RESTRICT_DEP_ONLY, RESTRICT_ADDITIVE, WITHIN],
+        [WITHIN, WITHIN, BEYOND],
+        [WITHIN, BEYOND, BEYOND],
+        [BEYOND, BEYOND, BEYOND],
+    ];
+
+    #[test]
+    fn audit_each_policy_and_class() {
+        for (policy, row) in UpgradeCompatibilityPolicyEnum::ALL.iter().zip(CELLS) {
+            for (class, cell) in UpgradeClassEnum::ALL.into_iter().zip(row) {
+                let expected = match cell {
+                    WITHIN => format!(
+                        "Upgrades stayed within the {} policy, widest change was {}",
+                        policy, class
+                    ),
+                    BEYOND => format!(
+                        "1 upgrade(s) went beyond the current {} policy, before it was restricted",
+                        policy
+                    ),
+                    RESTRICT_DEP_ONLY => "Marked Compatible but every upgrade only changed \
+                                          dependencies, the policy could be restricted to DepOnly"
+                        .to_string(),
+                    _ => "Upgrades only added code, the policy could be restricted to Additive"
+                        .to_string(),
+                };
+
+                let audit = audit(policy, &[Some(class)]);
+
+                assert_eq!(audit.findings, [expected], "{} {}", policy, class);
+                assert_eq!(audit.effective_class, Some(class));
+                assert_eq!(audit.dep_only_under_compatible, cell == RESTRICT_DEP_ONLY);
+                assert_eq!((audit.audited, audit.unaudited), (1, 0));
+            }
+        }
+    }
+
+    #[test]
+    fn audit_widest_upgrade_and_unaudited_ones() {
+        let audit = audit(
+            &UpgradeCompatibilityPolicyEnum::Additive,
+            &[
+                Some(UpgradeClassEnum::DepOnly),
+                None,
+                Some(UpgradeClassEnum::Compatible),
+                Some(UpgradeClassEnum::Additive),
+            ],
+        );
+
+        assert_eq!(audit.effective_class, Some(UpgradeClassEnum::Compatible));
+        assert_eq!((audit.audited, audit.unaudited), (3, 1));
+        assert_eq!(
+            audit.findings,
+            [
+                "1 upgrade(s) went beyond the current Additive policy, before it was restricted",
+                "1 upgrade(s) not audited, the previous version isn't indexed",
+            ]
+        );
+    }
+
+    #[test]
+    fn audit_without_upgrades() {
+        let never = audit(&UpgradeCompatibilityPolicyEnum::Compatible, &[]);
+        assert_eq!(never.effective_class, None);
+        assert!(!never.dep_only_under_compatible);
+        assert_eq!(never.findings, ["Never upgraded since publish"]);
+
+        let unaudited = audit(&UpgradeCompatibilityPolicyEnum::Compatible, &[None, None]);
+        assert_eq!(unaudited.effective_class, None);
+        assert_eq!(
+            unaudited.findings,
+            ["2 upgrade(s) not audited, the previous version isn't indexed"]
+        );
+    }
+}
@@ -0,0 +1,479 @@
+use chrono::{DateTime, Utc};
+use package_diff::ModuleDiff;
+use serde::Serialize;
+
+use crate::audit::UpgradeAudit;
+use crate::handlers::{AddressCapNode, GraphNode};
+use crate::models::{
+    self, UpgradeClassEnum, UpgradeCompatibilityPolicyEnum, WebhookDeliveryStatusEnum,
+};
+use crate::owner::OwnerClass;
+use crate::pagination;
+use crate::risk::{Risk, RiskRollUp, RiskSummary};
+use phantom_cap_db::events;
+
+#[derive(Serialize)]
+pub struct ErrorBody {
+    pub error: &'static str,
+    pub message: String,
+}
+
+#[derive(Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+impl<T, R: Into<T>> From<pagination::Page<R>> for Page<T> {
+    fn from(page: pagination::Page<R>) -> Self {
+        Page {
+            items: page.items.into_iter().map(Into::into).collect(),
+            next_cursor: page.next_cursor,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Counts {
+    pub upgrade_caps: i64,
+    pub packages: i64,
+    pub transfers: i64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchKind {
+    Cap,
+    Package,
+}
+
+#[derive(Serialize)]
+pub struct SearchResult {
+    pub kind: SearchKind,
+    pub id: String,
+}
+
+#[derive(Serialize)]
+pub struct Cap {
+    pub object_id: String,
+    pub package_id: Option<String>,
+    pub version: Option<i64>,
+    pub policy: UpgradeCompatibilityPolicyEnum,
+    pub owner: String,
+    pub owner_class: OwnerClass,
+    pub holder: Option<CapHolder>,
+    pub created_by: Option<String>,
+    pub created_seq_checkpoint: i64,
+    pub created_tx_digest: String,
+    pub created_at: DateTime<Utc>,
+    pub destroyed: Option<CapDestruction>,
+    pub last_activity_checkpoint: i64,
+    pub risk: Risk,
+    pub upgrade_audit: UpgradeAudit,
+}
+
+#[derive(Serialize)]
+pub struct CapHolder {
+    pub holder_id: String,
+    pub holder_type: String,
+    pub chain: Vec<String>,
+    /// Owner of the outermost object when the cap was wrapped.
+    pub root_owner: Option<String>,
+}
+
+impl From<models::UpgradeCapHolder> for CapHolder {
+    fn from(holder: models::UpgradeCapHolder) -> Self {
+        CapHolder {
+            holder_id: holder.holder_id,
+            holder_type: holder.holder_type,
+            chain: holder.chain,
+            root_owner: holder.root_owner,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct CapDestruction {
+    pub seq_checkpoint: i64,
+    pub tx_digest: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl CapDestruction {
+    pub fn from_state(state: &models::UpgradeCapState) -> Option<Self> {
+        Some(CapDestruction {
+            seq_checkpoint: state.destroyed_seq_checkpoint?,
+            tx_digest: state.destroyed_tx_digest.clone()?,
+            timestamp: state.destroyed_at?,
+        })
+    }
+}
+
+#[derive(Serialize)]
+pub struct Package {
+    pub package_id: String,
+    pub upgrade_cap_id: String,
+    pub version: i64,
+    pub publisher: String,
+    pub seq_checkpoint: i64,
+    pub tx_digest: String,
+    pub timestamp: DateTime<Utc>,
+    pub latest_package_id: String,
+    pub latest_version: i64,
+    pub lineage: Vec<CapVersion>,
+    pub modules: Vec<PackageModule>,
+    pub dependencies: Vec<PackageDependency>,
+    pub cap_owner_class: OwnerClass,
+    pub risk: Risk,
+    /// Risk of the package and its transitive dependencies.
+    pub risk_roll_up: RiskRollUp,
+}
+
+#[derive(Serialize)]
+pub struct PackageModule {
+    pub name: String,
+    pub types: Vec<PackageType>,
+}
+
+#[derive(Serialize)]
+pub struct PackageType {
+    pub name: String,
+    pub origin_package_id: String,
+}
+
+impl PackageModule {
+    pub fn from_rows(
+        modules: Vec<models::PackageModuleName>,
+        type_origins: &[models::PackageTypeOrigin],
+    ) -> Vec<Self> {
+        modules
+            .into_iter()
+            .map(|m| PackageModule {
+                types: type_origins
+                    .iter()
+                    .filter(|t| t.module_name == m.module_name)
+                    .map(|t| PackageType {
+                        name: t.datatype_name.clone(),
+                        origin_package_id: t.origin_package_id.clone(),
+                    })
+                    .collect(),
+                name: m.module_name,
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize)]
+pub struct PackageDependency {
+    pub dependency_id: String,
+    pub linked_id: String,
+    pub linked_version: i64,
+}
+
+impl From<models::PackageLinkage> for PackageDependency {
+    fn from(l: models::PackageLinkage) -> Self {
+        PackageDependency {
+            dependency_id: l.dependency_id,
+            linked_id: l.upgraded_id,
+            linked_version: l.upgraded_version,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct PackageGraph {
+    pub risk_roll_up: RiskRollUp,
+    pub dependencies: Vec<GraphPackage>,
+    pub dependents: Vec<GraphPackage>,
+}
+
+#[derive(Serialize)]
+pub struct GraphPackage {
+    pub package_id: String,
+    pub depth: i32,
+    pub upgrade_cap_id: Option<String>,
+    pub risk: Option<Risk>,
+}
+
+impl From<GraphNode> for GraphPackage {
+    fn from(node: GraphNode) -> Self {
+        GraphPackage {
+            package_id: node.package_id,
+            depth: node.depth,
+            upgrade_cap_id: node.cap_id,
+            risk: node.risk,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Address {
+    pub address: String,
+    pub owner_class: OwnerClass,
+    pub risk_summary: RiskSummary,
+    pub current_caps: Vec<AddressCap>,
+    /// Caps held by objects the address owned when they were wrapped.
+    pub held_caps: Vec<AddressCap>,
+    pub previous_caps: Vec<AddressCap>,
+    pub published: Vec<PublishedPackage>,
+}
+
+#[derive(Serialize)]
+pub struct AddressCap {
+    pub upgrade_cap_id: String,
+    pub package_id: Option<String>,
+    /// When the address got control of a current cap, or lost control of a previous one.
+    pub timestamp: DateTime<Utc>,
+    pub risk: Risk,
+}
+
+impl From<AddressCapNode> for AddressCap {
+    fn from(cap: AddressCapNode) -> Self {
+        AddressCap {
+            upgrade_cap_id: cap.cap_id,
+            package_id: cap.package_id,
+            timestamp: cap.timestamp,
+            risk: cap.risk,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct PublishedPackage {
+    pub package_id: String,
+    pub upgrade_cap_id: String,
+    pub version: i64,
+    pub seq_checkpoint: i64,
+    pub tx_digest: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl From<models::UpgradeCapVersion> for PublishedPackage {
+    fn from(v: models::UpgradeCapVersion) -> Self {
+        PublishedPackage {
+            package_id: v.package_id,
+            upgrade_cap_id: v.object_id,
+            version: v.version,
+            seq_checkpoint: v.seq_checkpoint,
+            tx_digest: v.tx_digest,
+            timestamp: v.timestamp,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct PackageDiff {
+    pub from: String,
+    pub to: String,
+    pub modules: Vec<ModuleDiff>,
+}
+
+#[derive(Serialize)]
+pub struct CapVersion {
+    pub version: i64,
+    pub package_id: String,
+    pub publisher: String,
+    pub seq_checkpoint: i64,
+    pub tx_digest: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl From<models::UpgradeCapVersion> for CapVersion {
+    fn from(v: models::UpgradeCapVersion) -> Self {
+        CapVersion {
+            version: v.version,
+            package_id: v.package_id,
+            publisher: v.publisher,
+            seq_checkpoint: v.seq_checkpoint,
+            tx_digest: v.tx_digest,
+            timestamp: v.timestamp,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct CapTransfer {
+    pub from: String,
+    pub to: String,
+    pub seq_checkpoint: i64,
+    pub tx_digest: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl From<models::UpgradeCapTransfer> for CapTransfer {
+    fn from(t: models::UpgradeCapTransfer) -> Self {
+        CapTransfer {
+            from: t.old_owner_address,
+            to: t.new_owner_address,
+            seq_checkpoint: t.seq_checkpoint,
+            tx_digest: t.tx_digest,
+            timestamp: t.timestamp,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct CapPolicyChange {
+    pub old_policy: UpgradeCompatibilityPolicyEnum,
+    pub new_policy: UpgradeCompatibilityPolicyEnum,
+    pub seq_checkpoint: i64,
+    pub tx_digest: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl From<models::UpgradeCapPolicyChange> for CapPolicyChange {
+    fn from(p: models::UpgradeCapPolicyChange) -> Self {
+        CapPolicyChange {
+            old_policy: p.old_policy,
+            new_policy: p.new_policy,
+            seq_checkpoint: p.seq_checkpoint,
+            tx_digest: p.tx_digest,
+            timestamp: p.timestamp,
+        }
+    }
+}
+
+/// Event of the live feeds, `kind` is also the name of its Server-Sent Event.
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CapEvent {
+    Version {
+        upgrade_cap_id: String,
+        #[serde(flatten)]
+        version: CapVersion,
+    },
+    Transfer {
+        upgrade_cap_id: String,
+        #[serde(flatten)]
+        transfer: CapTransfer,
+    },
+    PolicyChange {
+        upgrade_cap_id: String,
+        #[serde(flatten)]
+        change: CapPolicyChange,
+    },
+}
+
+impl From<events::CapEvent> for CapEvent {
+    fn from(event: events::CapEvent) -> Self {
+        match event {
+            events::CapEvent::Version(v) => CapEvent::Version {
+                upgrade_cap_id: v.object_id.clone(),
+                version: v.into(),
+            },
+            events::CapEvent::Transfer(t) => CapEvent::Transfer {
+                upgrade_cap_id: t.object_id.clone(),
+                transfer: t.into(),
+            },
+            events::CapEvent::PolicyChange(p) => CapEvent::PolicyChange {
+                upgrade_cap_id: p.object_id.clone(),
+                change: p.into(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Transaction {
+    pub tx_digest: String,
+    pub seq_checkpoint: i64,
+    pub timestamp: DateTime<Utc>,
+    pub created_caps: Vec<String>,
+    pub versions: Vec<TransactionVersion>,
+    pub transfers: Vec<TransactionTransfer>,
+    pub policy_changes: Vec<TransactionPolicyChange>,
+    pub destroyed_caps: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct TransactionVersion {
+    pub upgrade_cap_id: String,
+    pub package_id: String,
+    pub version: i64,
+    pub publisher: String,
+    /// Audited class of an upgrade, `None` for publishes and unaudited upgrades.
+    pub upgrade_class: Option<UpgradeClassEnum>,
+}
+
+#[derive(Serialize)]
+pub struct TransactionTransfer {
+    pub upgrade_cap_id: String,
+    pub from: String,
+    pub to: String,
+    /// Object the cap was put into, if any.
+    pub holder: Option<CapHolder>,
+}
+
+#[derive(Serialize)]
+pub struct TransactionPolicyChange {
+    pub upgrade_cap_id: String,
+    pub old_policy: UpgradeCompatibilityPolicyEnum,
+    pub new_policy: UpgradeCompatibilityPolicyEnum,
+}
+
+#[derive(Serialize)]
+pub struct Webhook {
+    pub id: i64,
+    pub url: String,
+    pub package: Option<String>,
+    pub cap: Option<String>,
+    pub owner: Option<String>,
+    /// Every kind when empty.
+    pub kinds: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<models::WebhookSubscription> for Webhook {
+    fn from(s: models::WebhookSubscription) -> Self {
+        Webhook {
+            id: s.id,
+            url: s.url,
+            package: s.package_id,
+            cap: s.cap_id,
+            owner: s.owner_address,
+            kinds: s.event_kinds,
+            created_at: s.created_at,
+        }
+    }
+}
+
+/// Webhook as created, the only time its secret is shown.
+#[derive(Serialize)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+#[derive(Serialize)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub kind: String,
+    pub status: WebhookDeliveryStatusEnum,
+    pub attempts: i32,
+    /// Only set while the delivery is pending.
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub payload: serde_json::Value,
+}
+
+impl From<models::WebhookDelivery> for WebhookDelivery {
+    fn from(d: models::WebhookDelivery) -> Self {
+        WebhookDelivery {
+            id: d.id,
+            kind: d.event_kind,
+            status: d.status,
+            attempts: d.attempts,
+            next_attempt_at: (d.status == WebhookDeliveryStatusEnum::Pending)
+                .then_some(d.next_attempt_at),
+            last_attempt_at: d.last_attempt_at,
+            last_status_code: d.last_status_code,
+            last_error: d.last_error,
+            created_at: d.created_at,
+            payload: serde_json::from_str(&d.payload).unwrap_or(serde_json::Value::Null),
+        }
+    }
+}
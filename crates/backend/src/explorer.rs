@@ -0,0 +1,229 @@
+use actix_web::web::{Html, Redirect};
+use actix_web::{Either, error, get, routes, web};
+use askama::Template;
+use serde::Deserialize;
+
+use std::str::FromStr;
+use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::digests::TransactionDigest;
+
+use diesel_async::{AsyncPgConnection, pooled_connection::bb8::Pool};
+
+use crate::format;
+use crate::query;
+use crate::templates;
+use phantom_cap_db::Network;
+
+type DbPool = Pool<AsyncPgConnection>;
+
+/// Redirect to the PhantomCap page matching an explorer url, or a page explaining
+/// why there is none.
+pub type Translated = Either<Redirect, Html>;
+
+#[derive(Deserialize)]
+struct AccountPath {
+    address: String,
+}
+
+#[derive(Deserialize)]
+struct ObjectPath {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct TransactionPath {
+    digest: String,
+}
+
+#[derive(Deserialize)]
+struct CoinPath {
+    coin_type: String,
+}
+
+/// SuiVision and SuiScan account pages, SuiScan tabs included.
+#[routes]
+#[get("/account/{address}")]
+#[get("/account/{address}/{tab}")]
+pub async fn translate_account(
+    network: web::Data<Network>,
+    path: web::Path<AccountPath>,
+) -> actix_web::Result<Redirect> {
+    let address = SuiAddress::from_hex_literal(&path.address)
+        .map_err(error::ErrorBadRequest)?
+        .to_hex_literal();
+
+    Ok(Redirect::to(format::phantom_address_url(
+        *network.get_ref(),
+        &address,
+    )))
+}
+
+/// SuiScan object tabs, e.g. `/object/{id}/fields`.
+#[get("/object/{id}/{tab}")]
+pub async fn translate_object_tab(
+    network: web::Data<Network>,
+    path: web::Path<ObjectPath>,
+) -> actix_web::Result<Redirect> {
+    let object_id = ObjectID::from_hex_literal(&path.id)
+        .map_err(error::ErrorBadRequest)?
+        .to_hex_literal();
+
+    Ok(Redirect::to(format::phantom_cap_url(
+        *network.get_ref(),
+        &object_id,
+    )))
+}
+
+/// SuiVision transaction pages and SuiScan transaction tabs.
+#[routes]
+#[get("/txblock/{digest}")]
+#[get("/tx/{digest}/{tab}")]
+pub async fn translate_transaction(
+    network: web::Data<Network>,
+    path: web::Path<TransactionPath>,
+) -> actix_web::Result<Redirect> {
+    let digest = TransactionDigest::from_str(&path.digest)
+        .map_err(error::ErrorBadRequest)?
+        .to_string();
+
+    Ok(Redirect::to(format::phantom_tx_url(
+        *network.get_ref(),
+        &digest,
+    )))
+}
+
+/// Checkpoints group unrelated transactions, there is no single page to send them to.
+#[get("/checkpoint/{checkpoint}")]
+pub async fn translate_checkpoint(
+    network: web::Data<Network>,
+    checkpoint: web::Path<i64>,
+) -> actix_web::Result<Translated> {
+    translate(templates::Translation {
+        kind: "Checkpoint".to_string(),
+        id: checkpoint.to_string(),
+        short_id: checkpoint.to_string(),
+        explorer_url: format::sui_checkpoint_url(*network.get_ref(), &checkpoint),
+        message: "Checkpoints aren't tied to an UpgradeCap, open one of their transactions instead"
+            .to_string(),
+        matches: vec![],
+    })
+}
+
+/// SuiVision and SuiScan coin pages, resolved to the package defining the coin.
+#[routes]
+#[get("/coin/{coin_type}")]
+#[get("/coin/{coin_type}/{tab}")]
+pub async fn translate_coin(
+    pool: web::Data<DbPool>,
+    network: web::Data<Network>,
+    path: web::Path<CoinPath>,
+) -> actix_web::Result<Translated> {
+    let network = *network.get_ref();
+    let coin_type = &path.coin_type;
+
+    // e.g. `0x2` for `0x2::sui::SUI`.
+    let package_id = coin_type
+        .split("::")
+        .next()
+        .and_then(|address| ObjectID::from_hex_literal(address).ok())
+        .ok_or_else(|| error::ErrorBadRequest("Invalid coin type"))?
+        .to_hex_literal();
+
+    let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
+    match query::get_package_by_id(&mut conn, &package_id).await {
+        Ok(package) => {
+            return Ok(Either::Left(Redirect::to(format::phantom_package_url(
+                network,
+                &package.package_id,
+            ))));
+        }
+        Err(e) if query::is_not_found(&e) => {}
+        Err(e) => return Err(error::ErrorInternalServerError(e)),
+    }
+
+    translate(templates::Translation {
+        kind: "Coin".to_string(),
+        id: coin_type.clone(),
+        short_id: format::short_move_type(coin_type),
+        explorer_url: format::sui_coin_url(network, coin_type),
+        message: "The package defining this coin isn't upgraded through an indexed UpgradeCap"
+            .to_string(),
+        matches: vec![],
+    })
+}
+
+/// Object that isn't an UpgradeCap: a package, an object holding caps, or neither.
+pub(crate) async fn translate_object(
+    conn: &mut AsyncPgConnection,
+    network: Network,
+    object_id: &str,
+) -> actix_web::Result<Translated> {
+    match query::get_package_by_id(conn, object_id).await {
+        Ok(package) => {
+            return Ok(Either::Left(Redirect::to(format::phantom_package_url(
+                network,
+                &package.package_id,
+            ))));
+        }
+        Err(e) if query::is_not_found(&e) => {}
+        Err(e) => return Err(error::ErrorInternalServerError(e)),
+    }
+
+    let matches = query::get_held_caps(conn, object_id)
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .into_iter()
+        .map(|cap_id| {
+            translation_match(
+                "UpgradeCap",
+                format::phantom_cap_url(network, &cap_id),
+                cap_id,
+                "Held",
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let message = if matches.is_empty() {
+        "This object has no UpgradeCap involvement: it isn't an UpgradeCap, a package \
+         or an object holding an UpgradeCap"
+    } else {
+        "This object holds several UpgradeCaps"
+    };
+
+    translate(templates::Translation {
+        kind: "Object".to_string(),
+        id: object_id.to_string(),
+        short_id: format::short_sui_object_id(object_id),
+        explorer_url: format::sui_object_url(network, object_id),
+        message: message.to_string(),
+        matches,
+    })
+}
+
+/// Redirects to the only match, explains the translation otherwise.
+fn translate(translation: templates::Translation) -> actix_web::Result<Translated> {
+    if let [only] = translation.matches.as_slice() {
+        return Ok(Either::Left(Redirect::to(only.url.clone())));
+    }
+
+    Ok(Either::Right(Html::new(
+        translation
+            .render()
+            .map_err(error::ErrorInternalServerError)?,
+    )))
+}
+
+fn translation_match(
+    kind: &str,
+    url: String,
+    id: String,
+    events: &str,
+) -> templates::TranslationMatch {
+    templates::TranslationMatch {
+        kind: kind.to_string(),
+        id: format::short_sui_object_id(&id),
+        id_full: id,
+        url,
+        events: events.to_string(),
+    }
+}
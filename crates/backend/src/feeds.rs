@@ -0,0 +1,282 @@
+use actix_web::{HttpRequest, HttpResponse, error, get, web};
+use askama::Template;
+use chrono::{DateTime, SecondsFormat, Utc};
+use diesel_async::{AsyncPgConnection, pooled_connection::bb8::Pool};
+use sui_types::base_types::{ObjectID, SuiAddress};
+
+use crate::format;
+use crate::models;
+use crate::query;
+use crate::templates;
+use phantom_cap_db::Network;
+
+type DbPool = Pool<AsyncPgConnection>;
+
+/// Entries of a feed, readers only need the recent ones.
+const FEED_LIMIT: i64 = 50;
+/// Authority of the `tag:` URIs identifying feeds and entries, which don't depend on
+/// the host a feed was fetched from.
+const TAG_AUTHORITY: &str = "phacap.xyz,2025";
+
+/// Entry with the time it is sorted on.
+struct Entry {
+    timestamp: DateTime<Utc>,
+    entry: templates::FeedEntry,
+}
+
+/// Versions published by every UpgradeCap.
+#[get("/feeds/upgrades.atom")]
+pub async fn upgrades_feed(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    network: web::Data<Network>,
+) -> actix_web::Result<HttpResponse> {
+    let network = *network.get_ref();
+    let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
+
+    let versions = query::get_latest_versions(&mut conn, FEED_LIMIT)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    let origin = origin(&req);
+
+    render_feed(
+        &origin,
+        network,
+        "upgrades",
+        "PhantomCap - Latest upgrades".to_string(),
+        format::phantom_upgrades_feed_url(network),
+        format::phantom_root(network) + "/",
+        versions
+            .iter()
+            .map(|v| version_entry(&origin, network, v))
+            .collect(),
+    )
+}
+
+/// Versions of the package's lineage and transfers of its UpgradeCap.
+#[get("/feeds/package/{id}.atom")]
+pub async fn package_feed(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    network: web::Data<Network>,
+    id: web::Path<String>,
+) -> actix_web::Result<HttpResponse> {
+    let network = *network.get_ref();
+    let package_id = ObjectID::from_hex_literal(&id)
+        .map_err(error::ErrorBadRequest)?
+        .to_hex_literal();
+    let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
+
+    let package = query::get_package_by_id(&mut conn, &package_id)
+        .await
+        .map_err(error::ErrorNotFound)?;
+    let origin = origin(&req);
+    let entries = cap_entries(&mut conn, &origin, network, &package.object_id).await?;
+
+    render_feed(
+        &origin,
+        network,
+        &format!("package/{}", package_id),
+        format!(
+            "PhantomCap - Package {}",
+            format::short_sui_object_id(&package_id)
+        ),
+        format::phantom_package_feed_url(network, &package_id),
+        format::phantom_package_url(network, &package_id),
+        entries,
+    )
+}
+
+/// Versions published and transfers of an UpgradeCap.
+#[get("/feeds/object/{id}.atom")]
+pub async fn cap_feed(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    network: web::Data<Network>,
+    id: web::Path<String>,
+) -> actix_web::Result<HttpResponse> {
+    let network = *network.get_ref();
+    let cap_id = ObjectID::from_hex_literal(&id)
+        .map_err(error::ErrorBadRequest)?
+        .to_hex_literal();
+    let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
+
+    query::get_cap_by_id(&mut conn, &cap_id)
+        .await
+        .map_err(error::ErrorNotFound)?;
+    let origin = origin(&req);
+    let entries = cap_entries(&mut conn, &origin, network, &cap_id).await?;
+
+    render_feed(
+        &origin,
+        network,
+        &format!("object/{}", cap_id),
+        format!(
+            "PhantomCap - UpgradeCap {}",
+            format::short_sui_object_id(&cap_id)
+        ),
+        format::phantom_cap_feed_url(network, &cap_id),
+        format::phantom_cap_url(network, &cap_id),
+        entries,
+    )
+}
+
+/// Versions the address published and UpgradeCaps it sent or received.
+#[get("/feeds/address/{address}.atom")]
+pub async fn address_feed(
+    req: HttpRequest,
+    pool: web::Data<DbPool>,
+    network: web::Data<Network>,
+    address: web::Path<String>,
+) -> actix_web::Result<HttpResponse> {
+    let network = *network.get_ref();
+    let address = SuiAddress::from_hex_literal(&address)
+        .map_err(error::ErrorBadRequest)?
+        .to_hex_literal();
+    let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
+
+    let versions = query::get_address_published_packages(&mut conn, &address, FEED_LIMIT)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    let transfers = query::get_address_transfers(&mut conn, &address, FEED_LIMIT)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    let origin = origin(&req);
+
+    let entries = versions
+        .iter()
+        .map(|v| version_entry(&origin, network, v))
+        .chain(
+            transfers
+                .iter()
+                .map(|t| transfer_entry(&origin, network, t)),
+        )
+        .collect();
+
+    render_feed(
+        &origin,
+        network,
+        &format!("address/{}", address),
+        format!(
+            "PhantomCap - Address {}",
+            format::short_sui_object_id(&address)
+        ),
+        format::phantom_address_feed_url(network, &address),
+        format::phantom_address_url(network, &address),
+        entries,
+    )
+}
+
+async fn cap_entries(
+    conn: &mut AsyncPgConnection,
+    origin: &str,
+    network: Network,
+    cap_id: &str,
+) -> actix_web::Result<Vec<Entry>> {
+    let versions = query::get_cap_versions_history(conn, cap_id, None, FEED_LIMIT)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    let transfers = query::get_cap_transfers_history(conn, cap_id, None, FEED_LIMIT)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+
+    Ok(versions
+        .iter()
+        .map(|v| version_entry(origin, network, v))
+        .chain(transfers.iter().map(|t| transfer_entry(origin, network, t)))
+        .collect())
+}
+
+/// Scheme and host the request was made to, feeds need absolute urls.
+fn origin(req: &HttpRequest) -> String {
+    let info = req.connection_info();
+    format!("{}://{}", info.scheme(), info.host())
+}
+
+/// `tag:` URI of a feed or entry, e.g. `version/<cap>/<digest>` on mainnet.
+fn tag(network: Network, specific: &str) -> String {
+    format!("tag:{}:{}/{}", TAG_AUTHORITY, network, specific)
+}
+
+fn atom_time(timestamp: &DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn version_entry(origin: &str, network: Network, v: &models::UpgradeCapVersion) -> Entry {
+    let package = format::short_sui_object_id(&v.package_id);
+    let title = match v.version {
+        1 => format!("Package {} published", package),
+        version => format!("Package {} upgraded to version {}", package, version),
+    };
+
+    Entry {
+        timestamp: v.timestamp,
+        entry: templates::FeedEntry {
+            // at most one version of a cap per transaction.
+            id: tag(network, &format!("version/{}/{}", v.object_id, v.tx_digest)),
+            title,
+            url: origin.to_string() + &format::phantom_package_url(network, &v.package_id),
+            updated: atom_time(&v.timestamp),
+            summary: format!(
+                "Package {} version {} was published by {} with UpgradeCap {} in transaction {}",
+                v.package_id, v.version, v.publisher, v.object_id, v.tx_digest
+            ),
+        },
+    }
+}
+
+fn transfer_entry(origin: &str, network: Network, t: &models::UpgradeCapTransfer) -> Entry {
+    Entry {
+        timestamp: t.timestamp,
+        entry: templates::FeedEntry {
+            id: tag(
+                network,
+                &format!("transfer/{}/{}", t.object_id, t.tx_digest),
+            ),
+            title: format!(
+                "UpgradeCap {} transferred to {}",
+                format::short_sui_object_id(&t.object_id),
+                format::short_sui_object_id(&t.new_owner_address)
+            ),
+            url: origin.to_string() + &format::phantom_tx_url(network, &t.tx_digest),
+            updated: atom_time(&t.timestamp),
+            summary: format!(
+                "UpgradeCap {} was transferred from {} to {} in transaction {}",
+                t.object_id, t.old_owner_address, t.new_owner_address, t.tx_digest
+            ),
+        },
+    }
+}
+
+/// Feed of the latest entries. An empty feed is dated from the epoch so that its
+/// `updated` only changes with new entries.
+fn render_feed(
+    origin: &str,
+    network: Network,
+    specific: &str,
+    title: String,
+    self_url: String,
+    page_url: String,
+    mut entries: Vec<Entry>,
+) -> actix_web::Result<HttpResponse> {
+    entries.sort_by_key(|e| std::cmp::Reverse(e.timestamp));
+    entries.truncate(FEED_LIMIT as usize);
+
+    let updated = entries
+        .first()
+        .map(|e| e.timestamp)
+        .unwrap_or(DateTime::UNIX_EPOCH);
+
+    let feed = templates::Feed {
+        id: tag(network, &format!("feeds/{}", specific)),
+        title,
+        self_url: origin.to_string() + &self_url,
+        page_url: origin.to_string() + &page_url,
+        updated: atom_time(&updated),
+        entries: entries.into_iter().map(|e| e.entry).collect(),
+    };
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/atom+xml; charset=utf-8")
+        .body(feed.render().map_err(error::ErrorInternalServerError)?))
+}
@@ -1,10 +1,7 @@
 use chrono::{DateTime, Utc};
 
-const SUI_TX_EXPLORER_URL: &str = "https://suivision.xyz/txblock/";
-const SUI_CHECKPOINT_EXPLORER_URL: &str = "https://suivision.xyz/checkpoint/";
-const SUI_PACKAGE_EXPLORER_URL: &str = "https://suivision.xyz/package/";
-const SUI_ADDRESS_EXPLORER_URL: &str = "https://suivision.xyz/account/";
-const SUI_OBJECT_EXPLORER_URL: &str = "https://suivision.xyz/object/";
+use crate::models::{UpgradeClassEnum, UpgradeCompatibilityPolicyEnum};
+use phantom_cap_db::Network;
 
 pub fn short_sui_object_id(id: &str) -> String {
     if id.len() > 14 {
@@ -14,32 +11,137 @@ pub fn short_sui_object_id(id: &str) -> String {
     }
 }
 
-pub fn sui_tx_url(tx_digest: &str) -> String {
-    format!("{}{}", SUI_TX_EXPLORER_URL, tx_digest)
+/// SuiVision host of a network.
+fn sui_explorer_url(network: Network) -> &'static str {
+    match network {
+        Network::Mainnet => "https://suivision.xyz",
+        Network::Testnet => "https://testnet.suivision.xyz",
+        Network::Devnet => "https://devnet.suivision.xyz",
+    }
+}
+
+pub fn sui_tx_url(network: Network, tx_digest: &str) -> String {
+    format!("{}/txblock/{}", sui_explorer_url(network), tx_digest)
+}
+
+pub fn sui_checkpoint_url(network: Network, checkpoint: &i64) -> String {
+    format!("{}/checkpoint/{}", sui_explorer_url(network), checkpoint)
+}
+
+pub fn sui_package_url(network: Network, package_id: &str) -> String {
+    format!("{}/package/{}", sui_explorer_url(network), package_id)
+}
+
+pub fn sui_address_url(network: Network, address: &str) -> String {
+    format!("{}/account/{}", sui_explorer_url(network), address)
+}
+
+pub fn sui_object_url(network: Network, object_id: &str) -> String {
+    format!("{}/object/{}", sui_explorer_url(network), object_id)
+}
+
+pub fn sui_coin_url(network: Network, coin_type: &str) -> String {
+    format!("{}/coin/{}", sui_explorer_url(network), coin_type)
 }
 
-pub fn sui_checkpoint_url(checkpoint: &i64) -> String {
-    format!("{}{}", SUI_CHECKPOINT_EXPLORER_URL, checkpoint)
+/// Shortens every full-length address of a Move type, e.g. `0x2a...b1c3d4::gov::Governance`.
+pub fn short_move_type(move_type: &str) -> String {
+    let mut short = String::with_capacity(move_type.len());
+    let mut rest = move_type;
+
+    while let Some(start) = rest.find("0x") {
+        short.push_str(&rest[..start]);
+        let address_len = rest[start + 2..]
+            .find(|c: char| !c.is_ascii_hexdigit())
+            .unwrap_or(rest.len() - start - 2)
+            + 2;
+        short.push_str(&short_sui_object_id(&rest[start..start + address_len]));
+        rest = &rest[start + address_len..];
+    }
+    short.push_str(rest);
+
+    short
 }
 
-pub fn sui_package_url(package_id: &str) -> String {
-    format!("{}{}", SUI_PACKAGE_EXPLORER_URL, package_id)
+/// Policy badge label, as listed in the README.
+pub fn policy_badge(policy: &UpgradeCompatibilityPolicyEnum) -> String {
+    match policy {
+        UpgradeCompatibilityPolicyEnum::Compatible => "Upgradable",
+        UpgradeCompatibilityPolicyEnum::Additive => "AdditiveOnly",
+        UpgradeCompatibilityPolicyEnum::DepOnly => "DepsOnly",
+        UpgradeCompatibilityPolicyEnum::Immutable => "Immutable",
+    }
+    .to_string()
 }
 
-pub fn sui_address_url(address: &str) -> String {
-    format!("{}{}", SUI_ADDRESS_EXPLORER_URL, address)
+/// Badge class of an audited upgrade, from the most restrictive.
+pub fn upgrade_class_style(class: UpgradeClassEnum) -> &'static str {
+    match class {
+        UpgradeClassEnum::DepOnly => "badge-best",
+        UpgradeClassEnum::Additive => "badge-low",
+        UpgradeClassEnum::Compatible => "badge-medium",
+    }
 }
 
-pub fn sui_object_url(object_id: &str) -> String {
-    format!("{}{}", SUI_OBJECT_EXPLORER_URL, object_id)
+/// Path prefix of a network's pages, mainnet is served from the root.
+pub fn phantom_root(network: Network) -> String {
+    match network {
+        Network::Mainnet => String::new(),
+        other => format!("/{}", other),
+    }
 }
 
-pub fn phantom_cap_url(cap_id: &str) -> String {
-    format!("/object/{}", cap_id)
+pub fn phantom_search_url(network: Network) -> String {
+    format!("{}/search", phantom_root(network))
 }
 
-pub fn phantom_package_url(package_id: &str) -> String {
-    format!("/package/{}", package_id)
+pub fn phantom_cap_url(network: Network, cap_id: &str) -> String {
+    format!("{}/object/{}", phantom_root(network), cap_id)
+}
+
+pub fn phantom_package_url(network: Network, package_id: &str) -> String {
+    format!("{}/package/{}", phantom_root(network), package_id)
+}
+
+pub fn phantom_address_url(network: Network, address: &str) -> String {
+    format!("{}/address/{}", phantom_root(network), address)
+}
+
+pub fn phantom_tx_url(network: Network, tx_digest: &str) -> String {
+    format!("{}/tx/{}", phantom_root(network), tx_digest)
+}
+
+pub fn phantom_live_upgrades_url(network: Network) -> String {
+    format!("{}/live/upgrades", phantom_root(network))
+}
+
+pub fn phantom_upgrades_feed_url(network: Network) -> String {
+    format!("{}/feeds/upgrades.atom", phantom_root(network))
+}
+
+pub fn phantom_package_feed_url(network: Network, package_id: &str) -> String {
+    format!(
+        "{}/feeds/package/{}.atom",
+        phantom_root(network),
+        package_id
+    )
+}
+
+pub fn phantom_cap_feed_url(network: Network, cap_id: &str) -> String {
+    format!("{}/feeds/object/{}.atom", phantom_root(network), cap_id)
+}
+
+pub fn phantom_address_feed_url(network: Network, address: &str) -> String {
+    format!("{}/feeds/address/{}.atom", phantom_root(network), address)
+}
+
+/// Address page url of an owner, empty for non-address owners like `shared` or `wrapped`.
+pub fn phantom_owner_url(network: Network, owner: &str) -> String {
+    if owner.starts_with("0x") {
+        phantom_address_url(network, owner)
+    } else {
+        String::new()
+    }
 }
 
 pub fn format_time_ago(timestamp: &DateTime<Utc>, current: &DateTime<Utc>) -> String {
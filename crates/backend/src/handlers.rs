@@ -1,38 +1,115 @@
 use actix_web::web::Html;
-use actix_web::{error, get, web};
+use actix_web::{Either, HttpResponse, error, get, web};
 use askama::Template;
 use serde::Deserialize;
 
 use anyhow;
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
 use sui_types::base_types::{ObjectID, SuiAddress};
+use sui_types::digests::TransactionDigest;
 
 use diesel_async::{AsyncPgConnection, pooled_connection::bb8::Pool};
 
+use crate::audit;
+use crate::explorer;
 use crate::format;
+use crate::live::{self, LiveFeed};
+use crate::models;
+use crate::owner;
+use crate::pagination::{self, Page, PageQuery, TransferCursor};
 use crate::query;
+use crate::risk;
 use crate::templates;
+use phantom_cap_db::Network;
+use phantom_cap_db::events::{CapEvent, EventFilter};
 
 type DbPool = Pool<AsyncPgConnection>;
 
+/// Packages listed on each side of the dependency graph.
+const GRAPH_LIMIT: i64 = 50;
+/// Dependencies assessed for the risk roll-up.
+const DEPENDENCY_TREE_LIMIT: i64 = 500;
+/// Caps and packages listed on each section of the address page.
+const ADDRESS_LIMIT: i64 = 50;
+/// Upgrades listed on the home page, live ones push the oldest out.
+const LATEST_UPGRADES_LIMIT: i64 = 10;
+
 #[get("/")]
-pub async fn home(pool: web::Data<DbPool>) -> actix_web::Result<Html> {
+pub async fn home(pool: web::Data<DbPool>, network: web::Data<Network>) -> actix_web::Result<Html> {
+    let network = *network.get_ref();
     let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
 
     let upgrade_caps_count = query::get_upgrade_caps_count(&mut conn).await.unwrap_or(0);
     let packages_count = query::get_packages_count(&mut conn).await.unwrap_or(0);
     let transfers_count = query::get_transfers_count(&mut conn).await.unwrap_or(0);
 
+    let now = chrono::Utc::now();
+    let upgrades = query::get_latest_versions(&mut conn, LATEST_UPGRADES_LIMIT)
+        .await
+        .unwrap_or_default()
+        .iter()
+        .map(|v| latest_upgrade_view(network, v, &now))
+        .collect();
+
     Ok(Html::new(
         templates::HomePage {
+            search_url: format::phantom_search_url(network),
             upgrade_caps_count,
             packages_count,
             transfers_count,
+            upgrades,
+            upgrades_limit: LATEST_UPGRADES_LIMIT,
+            live_upgrades_url: format::phantom_live_upgrades_url(network),
+            feed_url: format::phantom_upgrades_feed_url(network),
         }
         .render()
         .unwrap(),
     ))
 }
 
+/// Rows of the home page's latest upgrades, as Server-Sent Events.
+#[get("/live/upgrades")]
+pub async fn live_upgrades(network: web::Data<Network>, feed: web::Data<LiveFeed>) -> HttpResponse {
+    let network = *network.get_ref();
+    let filter = EventFilter {
+        kinds: vec!["version".to_string()],
+        ..Default::default()
+    };
+
+    live::sse_response(feed.subscribe(filter), move |live| {
+        let CapEvent::Version(version) = &live.event else {
+            return None;
+        };
+
+        templates::LatestUpgrades {
+            upgrades: vec![latest_upgrade_view(network, version, &chrono::Utc::now())],
+        }
+        .render()
+        .ok()
+    })
+}
+
+fn latest_upgrade_view(
+    network: Network,
+    v: &models::UpgradeCapVersion,
+    now: &chrono::DateTime<chrono::Utc>,
+) -> templates::LatestUpgrade {
+    templates::LatestUpgrade {
+        package_id: format::short_sui_object_id(&v.package_id),
+        package_id_full: v.package_id.clone(),
+        package_url: format::phantom_package_url(network, &v.package_id),
+        version: v.version,
+        cap_id: format::short_sui_object_id(&v.object_id),
+        cap_url: format::phantom_cap_url(network, &v.object_id),
+        publisher: format::short_sui_object_id(&v.publisher),
+        publisher_full: v.publisher.clone(),
+        publisher_url: format::phantom_address_url(network, &v.publisher),
+        tx_page_url: format::phantom_tx_url(network, &v.tx_digest),
+        time_ago: format::format_time_ago(&v.timestamp, now),
+    }
+}
+
 #[derive(Deserialize)]
 struct SearchQuery {
     id: String,
@@ -41,8 +118,10 @@ struct SearchQuery {
 #[get("/search")]
 pub async fn search_cap(
     pool: web::Data<DbPool>,
+    network: web::Data<Network>,
     query: web::Query<SearchQuery>,
 ) -> actix_web::Result<Html> {
+    let network = *network.get_ref();
     let object_id = ObjectID::from_hex_literal(&query.id.clone())
         .map_err(error::ErrorBadRequest)?
         .to_hex_literal();
@@ -51,17 +130,23 @@ pub async fn search_cap(
 
     if let Ok(cap_result) = query::get_cap_by_id(&mut conn, &object_id).await {
         return Ok(Html::new(
-            templates::SearchResult::Cap(cap_result.object_id)
-                .render()
-                .unwrap(),
+            templates::SearchResult::Cap {
+                url: format::phantom_cap_url(network, &cap_result.object_id),
+                id: cap_result.object_id,
+            }
+            .render()
+            .unwrap(),
         ));
     }
 
     if let Ok(package_result) = query::get_package_by_id(&mut conn, &object_id).await {
         return Ok(Html::new(
-            templates::SearchResult::Package(package_result.package_id)
-                .render()
-                .unwrap(),
+            templates::SearchResult::Package {
+                url: format::phantom_package_url(network, &package_result.package_id),
+                id: package_result.package_id,
+            }
+            .render()
+            .unwrap(),
         ));
     }
 
@@ -71,52 +156,88 @@ pub async fn search_cap(
 #[get("/object/{id}")]
 pub async fn show_cap_info(
     pool: web::Data<DbPool>,
+    network: web::Data<Network>,
     id: web::Path<String>,
-) -> actix_web::Result<Html> {
-    let object_id = ObjectID::from_hex_literal(&id).map_err(error::ErrorBadRequest)?;
+) -> actix_web::Result<explorer::Translated> {
+    let network = *network.get_ref();
+    let object_id = ObjectID::from_hex_literal(&id)
+        .map_err(error::ErrorBadRequest)?
+        .to_hex_literal();
     let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
-    let cap = fetch_cap_details(&mut conn, &object_id.to_hex_literal()).await?;
-    Ok(Html::new(
+
+    // explorers link every object to `/object/`, packages and cap holders included.
+    match query::get_cap_by_id(&mut conn, &object_id).await {
+        Ok(_) => {}
+        Err(e) if query::is_not_found(&e) => {
+            return explorer::translate_object(&mut conn, network, &object_id).await;
+        }
+        Err(e) => return Err(error::ErrorInternalServerError(e)),
+    }
+
+    let cap = fetch_cap_details(&mut conn, network, &object_id).await?;
+    Ok(Either::Right(Html::new(
         cap.render().map_err(error::ErrorInternalServerError)?,
-    ))
+    )))
 }
 
 #[get("/object/{id}/transfers")]
 pub async fn show_cap_transfers(
     pool: web::Data<DbPool>,
+    network: web::Data<Network>,
     id: web::Path<String>,
+    page: web::Query<PageQuery>,
 ) -> actix_web::Result<Html> {
+    let network = *network.get_ref();
     let object_id = ObjectID::from_hex_literal(&id).map_err(error::ErrorBadRequest)?;
+    let cursor = page
+        .cursor
+        .as_deref()
+        .map(str::parse::<TransferCursor>)
+        .transpose()
+        .map_err(error::ErrorBadRequest)?;
+    let limit = page.limit();
+
     let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
-    let transfers = query::get_cap_transfers_history(&mut conn, &object_id.to_hex_literal())
-        .await
-        .unwrap_or(vec![]);
+    let transfers = query::get_cap_transfers_history(
+        &mut conn,
+        &object_id.to_hex_literal(),
+        cursor.as_ref(),
+        limit + 1,
+    )
+    .await
+    .unwrap_or(vec![]);
+    let transfers = Page::from_rows(transfers, limit, |t| TransferCursor::from(t).to_string());
 
     let now = chrono::Utc::now();
     let transfer_views = transfers
+        .items
         .iter()
         .map(|t| {
             let time_ago = format::format_time_ago(&t.timestamp, &now);
             templates::CapTransfer {
                 tx_digest: format::short_sui_object_id(&t.tx_digest),
                 tx_digest_full: t.tx_digest.clone(),
-                tx_url: format::sui_tx_url(&t.tx_digest),
+                tx_url: format::sui_tx_url(network, &t.tx_digest),
+                tx_page_url: format::phantom_tx_url(network, &t.tx_digest),
                 seq_checkpoint: t.seq_checkpoint,
-                seq_checkpoint_url: format::sui_checkpoint_url(&t.seq_checkpoint),
+                seq_checkpoint_url: format::sui_checkpoint_url(network, &t.seq_checkpoint),
                 time_ago,
                 from: format::short_sui_object_id(&t.old_owner_address),
                 from_full: t.old_owner_address.clone(),
-                from_url: format::sui_address_url(&t.old_owner_address),
+                from_url: format::phantom_owner_url(network, &t.old_owner_address),
                 to: format::short_sui_object_id(&t.new_owner_address),
                 to_full: t.new_owner_address.clone(),
-                to_url: format::sui_address_url(&t.new_owner_address),
+                to_url: format::phantom_owner_url(network, &t.new_owner_address),
             }
         })
         .collect();
 
     Ok(Html::new(
         templates::CapTransfers {
+            cap_url: format::phantom_cap_url(network, &object_id.to_hex_literal()),
             transfers: transfer_views,
+            next_cursor: transfers.next_cursor,
+            limit,
         }
         .render()
         .map_err(error::ErrorInternalServerError)?,
@@ -126,34 +247,102 @@ pub async fn show_cap_transfers(
 #[get("/object/{id}/versions")]
 pub async fn show_cap_versions(
     pool: web::Data<DbPool>,
+    network: web::Data<Network>,
     id: web::Path<String>,
+    page: web::Query<PageQuery>,
 ) -> actix_web::Result<Html> {
+    let network = *network.get_ref();
     let object_id = ObjectID::from_hex_literal(&id).map_err(error::ErrorBadRequest)?;
+    let cursor = page
+        .cursor
+        .as_deref()
+        .map(pagination::parse_version_cursor)
+        .transpose()
+        .map_err(error::ErrorBadRequest)?;
+    let limit = page.limit();
+
     let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
-    let versions = query::get_cap_versions_history(&mut conn, &object_id.to_hex_literal())
+    let versions =
+        query::get_cap_versions_history(&mut conn, &object_id.to_hex_literal(), cursor, limit + 1)
+            .await
+            .unwrap_or(vec![]);
+    let versions = Page::from_rows(versions, limit, |v| v.version.to_string());
+    let classes = query::get_cap_upgrade_classes(&mut conn, &object_id.to_hex_literal())
         .await
-        .unwrap_or(vec![]);
+        .unwrap_or(vec![])
+        .into_iter()
+        .map(|c| (c.package_id, c.upgrade_class))
+        .collect::<HashMap<_, _>>();
 
     let now = chrono::Utc::now();
     let version_views = versions
+        .items
         .iter()
-        .map(|v| templates::CapVersion {
-            version: v.version,
-            package_id: format::short_sui_object_id(&v.package_id),
-            package_id_full: v.package_id.clone(),
-            package_url: format::sui_package_url(&v.package_id),
-            tx_digest: format::short_sui_object_id(&v.tx_digest),
-            tx_digest_full: v.tx_digest.clone(),
-            tx_url: format::sui_tx_url(&v.tx_digest),
-            seq_checkpoint: v.seq_checkpoint,
-            seq_checkpoint_url: format::sui_checkpoint_url(&v.seq_checkpoint),
-            time_ago: format::format_time_ago(&v.timestamp, &now),
+        .map(|v| {
+            let (upgrade_class, upgrade_class_badge) =
+                upgrade_class_badge(v, classes.get(&v.package_id).copied().flatten());
+            templates::CapVersion {
+                version: v.version,
+                upgrade_class,
+                upgrade_class_badge,
+                package_id: format::short_sui_object_id(&v.package_id),
+                package_id_full: v.package_id.clone(),
+                package_url: format::sui_package_url(network, &v.package_id),
+                tx_digest: format::short_sui_object_id(&v.tx_digest),
+                tx_digest_full: v.tx_digest.clone(),
+                tx_url: format::sui_tx_url(network, &v.tx_digest),
+                tx_page_url: format::phantom_tx_url(network, &v.tx_digest),
+                seq_checkpoint: v.seq_checkpoint,
+                seq_checkpoint_url: format::sui_checkpoint_url(network, &v.seq_checkpoint),
+                time_ago: format::format_time_ago(&v.timestamp, &now),
+            }
         })
         .collect();
 
     Ok(Html::new(
         templates::CapVersions {
+            cap_url: format::phantom_cap_url(network, &object_id.to_hex_literal()),
             versions: version_views,
+            next_cursor: versions.next_cursor,
+            limit,
+        }
+        .render()
+        .map_err(error::ErrorInternalServerError)?,
+    ))
+}
+
+#[get("/object/{id}/policies")]
+pub async fn show_cap_policies(
+    pool: web::Data<DbPool>,
+    network: web::Data<Network>,
+    id: web::Path<String>,
+) -> actix_web::Result<Html> {
+    let network = *network.get_ref();
+    let object_id = ObjectID::from_hex_literal(&id).map_err(error::ErrorBadRequest)?;
+    let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
+    let policies = query::get_cap_policy_history(&mut conn, &object_id.to_hex_literal())
+        .await
+        .unwrap_or(vec![]);
+
+    let now = chrono::Utc::now();
+    let policy_views = policies
+        .iter()
+        .map(|p| templates::CapPolicyChange {
+            old_policy: p.old_policy.to_string(),
+            new_policy: p.new_policy.to_string(),
+            tx_digest: format::short_sui_object_id(&p.tx_digest),
+            tx_digest_full: p.tx_digest.clone(),
+            tx_url: format::sui_tx_url(network, &p.tx_digest),
+            tx_page_url: format::phantom_tx_url(network, &p.tx_digest),
+            seq_checkpoint: p.seq_checkpoint,
+            seq_checkpoint_url: format::sui_checkpoint_url(network, &p.seq_checkpoint),
+            time_ago: format::format_time_ago(&p.timestamp, &now),
+        })
+        .collect();
+
+    Ok(Html::new(
+        templates::CapPolicies {
+            policies: policy_views,
         }
         .render()
         .map_err(error::ErrorInternalServerError)?,
@@ -163,34 +352,385 @@ pub async fn show_cap_versions(
 #[get("/package/{id}")]
 pub async fn show_package_info(
     pool: web::Data<DbPool>,
+    network: web::Data<Network>,
     id: web::Path<String>,
 ) -> actix_web::Result<Html> {
+    let network = *network.get_ref();
     let object_id = ObjectID::from_hex_literal(&id).map_err(error::ErrorBadRequest)?;
     let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
 
-    let package = query::get_package_by_id(&mut conn, &object_id.to_hex_literal())
-        .await
-        .map(|p| templates::Package {
-            id: p.package_id.clone(),
-            short_id: format::short_sui_object_id(&p.package_id),
-            // id_url: sui_package_url(&package.package_id),
-            upgrade_cap_id: format::short_sui_object_id(&p.object_id),
-            upgrade_cap_id_full: p.object_id.clone(),
-            upgrade_cap_id_url: format::phantom_cap_url(&p.object_id),
-            version: p.version,
-            published_by: format::short_sui_object_id(&p.publisher),
-            published_by_full: p.publisher.clone(),
-            published_by_url: format::sui_address_url(&p.publisher),
-            tx_digest_url: format::sui_tx_url(&p.tx_digest),
-            time_ago: format::format_time_ago(&p.timestamp, &chrono::Utc::now()),
-        })
+    let p = query::get_package_by_id(&mut conn, &object_id.to_hex_literal())
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+
+    let state = query::get_cap_state(&mut conn, &p.object_id)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    let ownership = fetch_cap_ownership(&mut conn, &state).await;
+
+    // every package published with the cap, from the original one.
+    let lineage = query::get_package_lineage(&mut conn, &state.object_id)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    let latest = lineage.last().unwrap_or(&p);
+
+    let modules = query::get_package_modules(&mut conn, &p.package_id)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    let type_origins = query::get_package_type_origins(&mut conn, &p.package_id)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+    let linkage = query::get_package_linkage(&mut conn, &p.package_id)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+
+    let risk = assess_cap_risk(&state, &ownership.class);
+    let badges = cap_badges(&state, &ownership.class, risk);
+    let (_, risk_roll_up) = fetch_dependency_risk(&mut conn, &p.package_id)
+        .await
         .map_err(error::ErrorInternalServerError)?;
 
+    let now = chrono::Utc::now();
+    let lineage_views = lineage
+        .iter()
+        .enumerate()
+        .rev()
+        .map(|(i, v)| templates::PackageVersion {
+            version: v.version,
+            diff_url: i.checked_sub(1).map_or(String::new(), |prev| {
+                format!(
+                    "{}/diff/{}",
+                    format::phantom_package_url(network, &lineage[prev].package_id),
+                    v.package_id
+                )
+            }),
+            package_id: format::short_sui_object_id(&v.package_id),
+            package_id_full: v.package_id.clone(),
+            package_url: format::phantom_package_url(network, &v.package_id),
+            time_ago: format::format_time_ago(&v.timestamp, &now),
+            current: v.package_id == p.package_id,
+        })
+        .collect();
+
+    let package = templates::Package {
+        id: p.package_id.clone(),
+        url: format::phantom_package_url(network, &p.package_id),
+        short_id: format::short_sui_object_id(&p.package_id),
+        // id_url: sui_package_url(&package.package_id),
+        upgrade_cap_id: format::short_sui_object_id(&p.object_id),
+        upgrade_cap_id_full: p.object_id.clone(),
+        upgrade_cap_id_url: format::phantom_cap_url(network, &p.object_id),
+        version: p.version,
+        is_latest: latest.package_id == p.package_id,
+        latest_id: format::short_sui_object_id(&latest.package_id),
+        latest_url: format::phantom_package_url(network, &latest.package_id),
+        latest_version: latest.version,
+        lineage: lineage_views,
+        modules: package_module_views(network, &modules, &type_origins, &p.package_id),
+        dependencies: linkage
+            .iter()
+            .map(|l| package_dependency_view(network, l))
+            .collect(),
+        owner_type: ownership.class.to_string(),
+        badges,
+        risk_roll_up: risk_roll_up.level.to_string(),
+        risk_roll_up_class: risk_roll_up.level.badge_class().to_string(),
+        risk_roll_up_reasons: risk_roll_up.reasons,
+        published_by: format::short_sui_object_id(&p.publisher),
+        published_by_full: p.publisher.clone(),
+        published_by_url: format::phantom_address_url(network, &p.publisher),
+        tx_digest_url: format::sui_tx_url(network, &p.tx_digest),
+        time_ago: format::format_time_ago(&p.timestamp, &now),
+        feed_url: format::phantom_package_feed_url(network, &p.package_id),
+    };
+
     Ok(Html::new(
         package.render().map_err(error::ErrorInternalServerError)?,
     ))
 }
 
+#[get("/address/{address}")]
+pub async fn show_address_info(
+    pool: web::Data<DbPool>,
+    network: web::Data<Network>,
+    address: web::Path<String>,
+) -> actix_web::Result<Html> {
+    let network = *network.get_ref();
+    let address = SuiAddress::from_hex_literal(&address)
+        .map_err(error::ErrorBadRequest)?
+        .to_hex_literal();
+    let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
+
+    let overview = fetch_address_overview(&mut conn, &address)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+
+    let now = chrono::Utc::now();
+    let address_page = templates::Address {
+        short_address: format::short_sui_object_id(&address),
+        explorer_url: format::sui_address_url(network, &address),
+        owner_type: overview.owner_class.to_string(),
+        owner_members: overview.owner_class.members().join(", "),
+        risk: overview.risk_summary.level.to_string(),
+        risk_class: overview.risk_summary.level.badge_class().to_string(),
+        best_count: overview.risk_summary.best,
+        low_count: overview.risk_summary.low,
+        medium_count: overview.risk_summary.medium,
+        high_count: overview.risk_summary.high,
+        current_caps: overview
+            .current_caps
+            .iter()
+            .map(|c| address_cap_view(network, c, &now))
+            .collect(),
+        held_caps: overview
+            .held_caps
+            .iter()
+            .map(|c| address_cap_view(network, c, &now))
+            .collect(),
+        previous_caps: overview
+            .previous_caps
+            .iter()
+            .map(|c| address_cap_view(network, c, &now))
+            .collect(),
+        published: overview
+            .published
+            .iter()
+            .map(|v| templates::PublishedPackage {
+                package_id: format::short_sui_object_id(&v.package_id),
+                package_id_full: v.package_id.clone(),
+                package_url: format::phantom_package_url(network, &v.package_id),
+                version: v.version,
+                cap_id: format::short_sui_object_id(&v.object_id),
+                cap_url: format::phantom_cap_url(network, &v.object_id),
+                tx_url: format::sui_tx_url(network, &v.tx_digest),
+                time_ago: format::format_time_ago(&v.timestamp, &now),
+            })
+            .collect(),
+        limit: ADDRESS_LIMIT,
+        feed_url: format::phantom_address_feed_url(network, &address),
+        address,
+    };
+
+    Ok(Html::new(
+        address_page
+            .render()
+            .map_err(error::ErrorInternalServerError)?,
+    ))
+}
+
+#[get("/package/{id}/graph")]
+pub async fn show_package_graph(
+    pool: web::Data<DbPool>,
+    network: web::Data<Network>,
+    id: web::Path<String>,
+) -> actix_web::Result<Html> {
+    let network = *network.get_ref();
+    let object_id = ObjectID::from_hex_literal(&id).map_err(error::ErrorBadRequest)?;
+    let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
+
+    let graph = fetch_package_graph(&mut conn, &object_id.to_hex_literal())
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+
+    Ok(Html::new(
+        templates::PackageGraph {
+            risk: graph.risk_roll_up.level.to_string(),
+            risk_class: graph.risk_roll_up.level.badge_class().to_string(),
+            risk_reasons: graph.risk_roll_up.reasons,
+            weakest_link: graph
+                .risk_roll_up
+                .weakest_link
+                .as_deref()
+                .map_or(String::new(), |id| format::phantom_package_url(network, id)),
+            dependencies_count: graph.dependencies.len(),
+            dependencies: graph
+                .dependencies
+                .iter()
+                .take(GRAPH_LIMIT as usize)
+                .map(|n| graph_node_view(network, n))
+                .collect(),
+            dependents: graph
+                .dependents
+                .iter()
+                .map(|n| graph_node_view(network, n))
+                .collect(),
+            limit: GRAPH_LIMIT,
+        }
+        .render()
+        .map_err(error::ErrorInternalServerError)?,
+    ))
+}
+
+#[get("/package/{id}/diff/{other}")]
+pub async fn show_package_diff(
+    pool: web::Data<DbPool>,
+    network: web::Data<Network>,
+    path: web::Path<(String, String)>,
+) -> actix_web::Result<Html> {
+    let network = *network.get_ref();
+    let (id, other) = path.into_inner();
+    let from = ObjectID::from_hex_literal(&id)
+        .map_err(error::ErrorBadRequest)?
+        .to_hex_literal();
+    let to = ObjectID::from_hex_literal(&other)
+        .map_err(error::ErrorBadRequest)?
+        .to_hex_literal();
+    let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
+
+    let modules = fetch_package_diff(&mut conn, &from, &to)
+        .await
+        .map_err(error::ErrorInternalServerError)?
+        .ok_or_else(|| error::ErrorNotFound("Package bytecode is not indexed"))?;
+
+    let diff_page = templates::PackageDiff {
+        from: format::short_sui_object_id(&from),
+        from_url: format::phantom_package_url(network, &from),
+        to: format::short_sui_object_id(&to),
+        to_url: format::phantom_package_url(network, &to),
+        modules: modules.into_iter().map(module_diff_view).collect(),
+        from_full: from,
+        to_full: to,
+    };
+
+    Ok(Html::new(
+        diff_page
+            .render()
+            .map_err(error::ErrorInternalServerError)?,
+    ))
+}
+
+#[get("/tx/{digest}")]
+pub async fn show_transaction(
+    pool: web::Data<DbPool>,
+    network: web::Data<Network>,
+    digest: web::Path<String>,
+) -> actix_web::Result<Html> {
+    let network = *network.get_ref();
+    let digest = TransactionDigest::from_str(&digest)
+        .map_err(error::ErrorBadRequest)?
+        .to_string();
+    let mut conn = pool.get().await.map_err(error::ErrorInternalServerError)?;
+
+    let activity = fetch_transaction_activity(&mut conn, &digest)
+        .await
+        .map_err(error::ErrorInternalServerError)?;
+
+    let now = chrono::Utc::now();
+    let checkpoint = activity.checkpoint();
+    let cap_link = |cap_id: &str| {
+        (
+            format::short_sui_object_id(cap_id),
+            format::phantom_cap_url(network, cap_id),
+        )
+    };
+
+    let transaction = templates::Transaction {
+        short_digest: format::short_sui_object_id(&digest),
+        explorer_url: format::sui_tx_url(network, &digest),
+        involved: !activity.is_empty(),
+        seq_checkpoint: checkpoint.map_or(0, |(c, _)| c),
+        seq_checkpoint_url: checkpoint.map_or(String::new(), |(c, _)| {
+            format::sui_checkpoint_url(network, &c)
+        }),
+        time_ago: checkpoint.map_or(String::new(), |(_, t)| format::format_time_ago(&t, &now)),
+        created: activity
+            .created
+            .iter()
+            .map(|c| {
+                let (cap_id, cap_url) = cap_link(&c.object_id);
+                templates::TransactionCap {
+                    cap_id,
+                    cap_id_full: c.object_id.clone(),
+                    cap_url,
+                }
+            })
+            .collect(),
+        versions: activity
+            .versions
+            .iter()
+            .map(|v| {
+                let (cap_id, cap_url) = cap_link(&v.object_id);
+                let (upgrade_class, upgrade_class_badge) = upgrade_class_badge(
+                    v,
+                    activity
+                        .upgrade_classes
+                        .get(&v.package_id)
+                        .copied()
+                        .flatten(),
+                );
+                templates::TransactionVersion {
+                    cap_id,
+                    cap_url,
+                    version: v.version,
+                    upgrade_class,
+                    upgrade_class_badge,
+                    package_id: format::short_sui_object_id(&v.package_id),
+                    package_id_full: v.package_id.clone(),
+                    package_url: format::phantom_package_url(network, &v.package_id),
+                    publisher: format::short_sui_object_id(&v.publisher),
+                    publisher_full: v.publisher.clone(),
+                    publisher_url: format::phantom_address_url(network, &v.publisher),
+                }
+            })
+            .collect(),
+        transfers: activity
+            .transfers
+            .iter()
+            .map(|t| {
+                let (cap_id, cap_url) = cap_link(&t.object_id);
+                templates::TransactionTransfer {
+                    cap_id,
+                    cap_url,
+                    from: format::short_sui_object_id(&t.old_owner_address),
+                    from_full: t.old_owner_address.clone(),
+                    from_url: format::phantom_owner_url(network, &t.old_owner_address),
+                    to: format::short_sui_object_id(&t.new_owner_address),
+                    to_full: t.new_owner_address.clone(),
+                    to_url: format::phantom_owner_url(network, &t.new_owner_address),
+                    holder: activity
+                        .holders
+                        .iter()
+                        .find(|h| h.object_id == t.object_id)
+                        .cloned()
+                        .map(|h| cap_holder_view(network, h)),
+                }
+            })
+            .collect(),
+        policy_changes: activity
+            .policy_changes
+            .iter()
+            .map(|p| {
+                let (cap_id, cap_url) = cap_link(&p.object_id);
+                templates::TransactionPolicyChange {
+                    cap_id,
+                    cap_url,
+                    old_policy: format::policy_badge(&p.old_policy),
+                    new_policy: format::policy_badge(&p.new_policy),
+                }
+            })
+            .collect(),
+        destroyed: activity
+            .destroyed
+            .iter()
+            .map(|c| {
+                let (cap_id, cap_url) = cap_link(&c.object_id);
+                templates::TransactionCap {
+                    cap_id,
+                    cap_id_full: c.object_id.clone(),
+                    cap_url,
+                }
+            })
+            .collect(),
+        digest,
+    };
+
+    Ok(Html::new(
+        transaction
+            .render()
+            .map_err(error::ErrorInternalServerError)?,
+    ))
+}
+
 pub async fn not_found() -> actix_web::Result<Html> {
     Ok(Html::new(
         templates::NotFound
@@ -201,47 +741,648 @@ pub async fn not_found() -> actix_web::Result<Html> {
 
 async fn fetch_cap_details(
     conn: &mut AsyncPgConnection,
+    network: Network,
     cap_id: &str,
 ) -> anyhow::Result<templates::Cap, actix_web::Error> {
     let cap = query::get_cap_by_id(conn, cap_id)
         .await
         .map_err(error::ErrorInternalServerError)?;
 
-    let latest_version = query::get_cap_latest_version(conn, cap_id)
+    let state = query::get_cap_state(conn, cap_id)
         .await
-        .map_or(("Unknown".to_string(), 0), |c| (c.package_id, c.version));
+        .map_err(error::ErrorInternalServerError)?;
 
-    let owner_address = query::get_cap_latest_transfer(conn, cap_id)
+    let ownership = fetch_cap_ownership(conn, &state).await;
+    let risk = assess_cap_risk(&state, &ownership.class);
+    let badges = cap_badges(&state, &ownership.class, risk);
+    let audit = fetch_cap_audit(conn, &state)
         .await
-        .map_or(SuiAddress::ZERO.to_string(), |t| t.new_owner_address);
+        .map_err(error::ErrorInternalServerError)?;
+    let owner_address = ownership.owner_address;
+    let holder = ownership.holder.map(|h| cap_holder_view(network, h));
 
     let created_by = query::get_cap_first_transfer(conn, cap_id)
         .await
         .map_or(SuiAddress::ZERO.to_string(), |t| t.new_owner_address);
 
-    let created_by_url = format::sui_address_url(&created_by);
+    let created_by_url = format::phantom_address_url(network, &created_by);
 
-    let policy_str = cap.policy.to_string();
+    let policy_str = state.policy.to_string();
     let now = chrono::Utc::now();
     let time_ago = format::format_time_ago(&cap.created_at, &now);
-    let package_id = latest_version.0;
-    let version_str = latest_version.1.to_string();
+    let destroyed_time_ago = state
+        .destroyed_at
+        .map_or(String::new(), |t| format::format_time_ago(&t, &now));
+    let destroyed_tx_url = state
+        .destroyed_tx_digest
+        .as_ref()
+        .map_or(String::new(), |d| format::sui_tx_url(network, d));
+    let package_id = state.package_id;
+    let version_str = state.version.to_string();
 
     Ok(templates::Cap {
         id: cap.object_id.clone(),
+        url: format::phantom_cap_url(network, &cap.object_id),
         short_id: format::short_sui_object_id(&cap.object_id),
         package: format::short_sui_object_id(&package_id),
         package_full: package_id.clone(),
-        package_url: format::phantom_package_url(&package_id),
+        package_url: format::phantom_package_url(network, &package_id),
         version: version_str,
         policy: policy_str,
         owner: format::short_sui_object_id(&owner_address),
         owner_full: owner_address.clone(),
-        owner_url: format::sui_address_url(&owner_address),
+        owner_url: format::phantom_owner_url(network, &owner_address),
+        holder,
+        owner_type: ownership.class.to_string(),
+        owner_members: ownership.class.members().join(", "),
+        badges,
+        audit: upgrade_audit_view(audit),
         created_by: format::short_sui_object_id(&created_by),
         created_by_full: created_by.clone(),
         created_by_url,
-        tx_digest_url: format::sui_tx_url(&cap.created_tx_digest),
+        tx_digest_url: format::sui_tx_url(network, &cap.created_tx_digest),
         time_ago,
+        destroyed: state.destroyed,
+        destroyed_tx_url,
+        destroyed_time_ago,
+        feed_url: format::phantom_cap_feed_url(network, &cap.object_id),
     })
 }
+
+/// Current owner of a cap, the object holding it and the owner type.
+pub(crate) struct CapOwnership {
+    pub owner_address: String,
+    pub holder: Option<models::UpgradeCapHolder>,
+    pub class: owner::OwnerClass,
+}
+
+pub(crate) async fn fetch_cap_ownership(
+    conn: &mut AsyncPgConnection,
+    state: &models::UpgradeCapState,
+) -> CapOwnership {
+    // the cap is held by an object, if the latest transfer put it there.
+    let holder = query::get_cap_holder(conn, &state.object_id, &state.owner_tx_digest)
+        .await
+        .ok();
+    // a held cap is controlled by whoever holds the outermost object.
+    let controller = holder
+        .as_ref()
+        .and_then(|holder| holder.root_owner.as_deref())
+        .unwrap_or(&state.owner_address);
+    let signer = query::get_cap_owner(conn, controller).await.ok();
+
+    cap_ownership(state, holder, signer)
+}
+
+/// Ownership of a cap given the holder of its latest transfer and the signer of its owner,
+/// or of the root owner of the holder.
+pub(crate) fn cap_ownership(
+    state: &models::UpgradeCapState,
+    holder: Option<models::UpgradeCapHolder>,
+    signer: Option<models::CapOwner>,
+) -> CapOwnership {
+    let destroyed = state.destroyed;
+
+    // destroyed caps are immutable and have no owner.
+    let (owner_address, holder, signer) = if destroyed {
+        (SuiAddress::ZERO.to_string(), None, None)
+    } else {
+        (state.owner_address.clone(), holder, signer)
+    };
+
+    let class =
+        owner::OwnerClass::classify(&owner_address, holder.as_ref(), signer.as_ref(), destroyed);
+
+    CapOwnership {
+        owner_address,
+        holder,
+        class,
+    }
+}
+
+/// Risk of a cap following the README risk matrix.
+pub(crate) fn assess_cap_risk(
+    state: &models::UpgradeCapState,
+    class: &owner::OwnerClass,
+) -> risk::Risk {
+    risk::assess(
+        &state.policy,
+        class,
+        state.destroyed,
+        state.last_upgrade_at.as_ref(),
+        &chrono::Utc::now(),
+    )
+}
+
+/// Audit of every upgrade of a cap against its policy.
+pub(crate) async fn fetch_cap_audit(
+    conn: &mut AsyncPgConnection,
+    state: &models::UpgradeCapState,
+) -> anyhow::Result<audit::UpgradeAudit> {
+    let lineage = query::get_package_lineage(conn, &state.object_id).await?;
+    let classes = query::get_cap_upgrade_classes(conn, &state.object_id)
+        .await?
+        .into_iter()
+        .map(|c| (c.package_id, c.upgrade_class))
+        .collect::<HashMap<_, _>>();
+
+    // the first version is the publish itself.
+    let upgrades = lineage
+        .iter()
+        .filter(|v| v.version > 1)
+        .map(|v| classes.get(&v.package_id).copied().flatten())
+        .collect::<Vec<_>>();
+
+    Ok(audit::audit(&state.policy, &upgrades))
+}
+
+fn upgrade_audit_view(audit: audit::UpgradeAudit) -> templates::UpgradeAudit {
+    templates::UpgradeAudit {
+        effective_class: audit
+            .effective_class
+            .map_or("Unaudited".to_string(), |c| c.to_string()),
+        effective_class_badge: audit
+            .effective_class
+            .map_or("badge-neutral", format::upgrade_class_style)
+            .to_string(),
+        findings: audit.findings,
+        dep_only_under_compatible: audit.dep_only_under_compatible,
+    }
+}
+
+fn upgrade_class_badge(
+    version: &models::UpgradeCapVersion,
+    class: Option<models::UpgradeClassEnum>,
+) -> (String, String) {
+    match class {
+        _ if version.version == 1 => ("Publish".to_string(), "badge-neutral".to_string()),
+        Some(class) => (
+            class.to_string(),
+            format::upgrade_class_style(class).to_string(),
+        ),
+        None => ("Unaudited".to_string(), "badge-neutral".to_string()),
+    }
+}
+
+fn cap_badges(
+    state: &models::UpgradeCapState,
+    class: &owner::OwnerClass,
+    risk: risk::Risk,
+) -> templates::Badges {
+    templates::Badges {
+        risk: risk.level.to_string(),
+        risk_class: risk.level.badge_class().to_string(),
+        risk_reasons: risk.reasons,
+        policy: format::policy_badge(&state.policy),
+        owner_type: class.to_string(),
+    }
+}
+
+fn package_module_views(
+    network: Network,
+    modules: &[models::PackageModuleName],
+    type_origins: &[models::PackageTypeOrigin],
+    package_id: &str,
+) -> Vec<templates::PackageModule> {
+    modules
+        .iter()
+        .map(|m| templates::PackageModule {
+            name: m.module_name.clone(),
+            types: type_origins
+                .iter()
+                .filter(|t| t.module_name == m.module_name)
+                .map(|t| templates::PackageType {
+                    name: t.datatype_name.clone(),
+                    origin: format::short_sui_object_id(&t.origin_package_id),
+                    origin_url: format::phantom_package_url(network, &t.origin_package_id),
+                    introduced_here: t.origin_package_id == package_id,
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn package_dependency_view(
+    network: Network,
+    linkage: &models::PackageLinkage,
+) -> templates::PackageDependency {
+    templates::PackageDependency {
+        dependency_id: format::short_sui_object_id(&linkage.dependency_id),
+        dependency_id_full: linkage.dependency_id.clone(),
+        linked_id: format::short_sui_object_id(&linkage.upgraded_id),
+        linked_id_full: linkage.upgraded_id.clone(),
+        linked_url: format::sui_package_url(network, &linkage.upgraded_id),
+        linked_version: linkage.upgraded_version,
+    }
+}
+
+/// Package of the dependency graph and the risk of its cap, if the cap is indexed.
+pub(crate) struct GraphNode {
+    pub package_id: String,
+    pub depth: i32,
+    pub cap_id: Option<String>,
+    pub risk: Option<risk::Risk>,
+}
+
+pub(crate) struct PackageGraph {
+    pub dependencies: Vec<GraphNode>,
+    pub dependents: Vec<GraphNode>,
+    pub risk_roll_up: risk::RiskRollUp,
+}
+
+/// Transitive dependencies and dependents of a package with their cap risk.
+pub(crate) async fn fetch_package_graph(
+    conn: &mut AsyncPgConnection,
+    package_id: &str,
+) -> anyhow::Result<PackageGraph> {
+    let (dependencies, risk_roll_up) = fetch_dependency_risk(conn, package_id).await?;
+    let dependents = query::get_transitive_dependents(conn, package_id, GRAPH_LIMIT).await?;
+    let dependents = fetch_graph_nodes(conn, dependents).await?;
+
+    Ok(PackageGraph {
+        dependencies,
+        dependents,
+        risk_roll_up,
+    })
+}
+
+/// Transitive dependencies of a package with their cap risk and the risk of
+/// the package rolled up over them, a dependency inherits the risk of whoever
+/// can upgrade it. Framework packages are left out, they aren't upgraded
+/// through an UpgradeCap.
+pub(crate) async fn fetch_dependency_risk(
+    conn: &mut AsyncPgConnection,
+    package_id: &str,
+) -> anyhow::Result<(Vec<GraphNode>, risk::RiskRollUp)> {
+    let dependencies = query::get_transitive_dependencies(
+        conn,
+        package_id,
+        &risk::FRAMEWORK_PACKAGES,
+        DEPENDENCY_TREE_LIMIT,
+    )
+    .await?;
+
+    let root = models::PackageGraphNode {
+        package_id: package_id.to_string(),
+        depth: 0,
+    };
+    let mut nodes =
+        fetch_graph_nodes(conn, std::iter::once(root).chain(dependencies).collect()).await?;
+    let root = nodes.remove(0);
+
+    let assessed = nodes
+        .iter()
+        .map(|n| (n.package_id.as_str(), n.risk.as_ref()))
+        .collect::<Vec<_>>();
+    let mut risk_roll_up = risk::roll_up(root.risk.as_ref(), &assessed);
+    if nodes.len() as i64 == DEPENDENCY_TREE_LIMIT {
+        risk_roll_up.reasons.push(format!(
+            "Only the closest {} dependencies were assessed",
+            DEPENDENCY_TREE_LIMIT
+        ));
+    }
+
+    Ok((nodes, risk_roll_up))
+}
+
+/// Risk of the cap of every node, loaded in a single query.
+async fn fetch_graph_nodes(
+    conn: &mut AsyncPgConnection,
+    nodes: Vec<models::PackageGraphNode>,
+) -> anyhow::Result<Vec<GraphNode>> {
+    let package_ids = nodes
+        .iter()
+        .map(|node| node.package_id.clone())
+        .collect::<Vec<_>>();
+
+    // framework packages and packages published before indexing have no cap.
+    let mut caps = query::get_packages_cap_ownership(conn, &package_ids)
+        .await?
+        .into_iter()
+        .map(|(package_id, state, holder, signer)| {
+            let ownership = cap_ownership(&state, holder, signer);
+            let risk = assess_cap_risk(&state, &ownership.class);
+            (package_id, (state.object_id, risk))
+        })
+        .collect::<HashMap<_, _>>();
+
+    Ok(nodes
+        .into_iter()
+        .map(|node| {
+            let cap = caps.remove(&node.package_id);
+            GraphNode {
+                cap_id: cap.as_ref().map(|(cap_id, _)| cap_id.clone()),
+                risk: cap.map(|(_, risk)| risk),
+                package_id: node.package_id,
+                depth: node.depth,
+            }
+        })
+        .collect())
+}
+
+fn graph_node_view(network: Network, node: &GraphNode) -> templates::GraphPackage {
+    let (risk, risk_class) = node.risk.as_ref().map_or(
+        ("No UpgradeCap".to_string(), "badge-neutral".to_string()),
+        |r| (r.level.to_string(), r.level.badge_class().to_string()),
+    );
+
+    templates::GraphPackage {
+        package_id: format::short_sui_object_id(&node.package_id),
+        package_id_full: node.package_id.clone(),
+        package_url: format::phantom_package_url(network, &node.package_id),
+        direct: node.depth == 1,
+        depth: node.depth,
+        risk,
+        risk_class,
+        risk_reasons: node
+            .risk
+            .as_ref()
+            .map_or(String::new(), |r| r.reasons.join("\n")),
+        cap_url: node
+            .cap_id
+            .as_deref()
+            .map_or(String::new(), |id| format::phantom_cap_url(network, id)),
+    }
+}
+
+/// Every UpgradeCap event of a transaction.
+pub(crate) struct TransactionActivity {
+    pub created: Vec<models::UpgradeCap>,
+    pub versions: Vec<models::UpgradeCapVersion>,
+    pub upgrade_classes: HashMap<String, Option<models::UpgradeClassEnum>>,
+    pub transfers: Vec<models::UpgradeCapTransfer>,
+    pub holders: Vec<models::UpgradeCapHolder>,
+    pub policy_changes: Vec<models::UpgradeCapPolicyChange>,
+    pub destroyed: Vec<models::UpgradeCapState>,
+}
+
+impl TransactionActivity {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty()
+            && self.versions.is_empty()
+            && self.transfers.is_empty()
+            && self.policy_changes.is_empty()
+            && self.destroyed.is_empty()
+    }
+
+    /// Checkpoint and time of the transaction, as recorded by any of its events.
+    pub fn checkpoint(&self) -> Option<(i64, chrono::DateTime<chrono::Utc>)> {
+        self.versions
+            .iter()
+            .map(|v| (v.seq_checkpoint, v.timestamp))
+            .chain(
+                self.transfers
+                    .iter()
+                    .map(|t| (t.seq_checkpoint, t.timestamp)),
+            )
+            .chain(
+                self.policy_changes
+                    .iter()
+                    .map(|p| (p.seq_checkpoint, p.timestamp)),
+            )
+            .chain(
+                self.created
+                    .iter()
+                    .map(|c| (c.created_seq_checkpoint, c.created_at)),
+            )
+            .chain(
+                self.destroyed
+                    .iter()
+                    .filter_map(|c| Some((c.destroyed_seq_checkpoint?, c.destroyed_at?))),
+            )
+            .next()
+    }
+}
+
+pub(crate) async fn fetch_transaction_activity(
+    conn: &mut AsyncPgConnection,
+    tx_digest: &str,
+) -> anyhow::Result<TransactionActivity> {
+    Ok(TransactionActivity {
+        created: query::get_transaction_created_caps(conn, tx_digest).await?,
+        versions: query::get_transaction_versions(conn, tx_digest).await?,
+        upgrade_classes: query::get_transaction_upgrade_classes(conn, tx_digest)
+            .await?
+            .into_iter()
+            .map(|c| (c.package_id, c.upgrade_class))
+            .collect(),
+        transfers: query::get_transaction_transfers(conn, tx_digest).await?,
+        holders: query::get_transaction_holders(conn, tx_digest).await?,
+        policy_changes: query::get_transaction_policy_changes(conn, tx_digest).await?,
+        destroyed: query::get_transaction_destroyed_caps(conn, tx_digest).await?,
+    })
+}
+
+/// Cap an address controls or controlled, with its latest package and current risk.
+pub(crate) struct AddressCapNode {
+    pub cap_id: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub package_id: Option<String>,
+    pub risk: risk::Risk,
+}
+
+pub(crate) struct AddressOverview {
+    pub owner_class: owner::OwnerClass,
+    pub current_caps: Vec<AddressCapNode>,
+    pub held_caps: Vec<AddressCapNode>,
+    pub previous_caps: Vec<AddressCapNode>,
+    pub published: Vec<models::UpgradeCapVersion>,
+    pub risk_summary: risk::RiskSummary,
+}
+
+/// Caps an address controls and controlled, the packages it published and
+/// the worst risk over everything it can upgrade.
+pub(crate) async fn fetch_address_overview(
+    conn: &mut AsyncPgConnection,
+    address: &str,
+) -> anyhow::Result<AddressOverview> {
+    let signer = query::get_cap_owner(conn, address).await.ok();
+    let owner_class = owner::OwnerClass::classify(address, None, signer.as_ref(), false);
+
+    let current_caps = query::get_address_caps(
+        conn,
+        address,
+        query::AddressCapRelation::Owned,
+        ADDRESS_LIMIT,
+    )
+    .await?;
+    let held_caps = query::get_address_caps(
+        conn,
+        address,
+        query::AddressCapRelation::Held,
+        ADDRESS_LIMIT,
+    )
+    .await?;
+    let previous_caps = query::get_address_caps(
+        conn,
+        address,
+        query::AddressCapRelation::Previous,
+        ADDRESS_LIMIT,
+    )
+    .await?;
+
+    let cap_ids = current_caps
+        .iter()
+        .chain(&held_caps)
+        .chain(&previous_caps)
+        .map(|cap| cap.object_id.clone())
+        .collect::<Vec<_>>();
+    let mut states = query::get_caps_ownership(conn, &cap_ids)
+        .await?
+        .into_iter()
+        .map(|(state, holder, signer)| {
+            let ownership = cap_ownership(&state, holder, signer);
+            let risk = assess_cap_risk(&state, &ownership.class);
+            (state.object_id.clone(), (state, risk))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let current_caps = address_cap_nodes(current_caps, &mut states);
+    let held_caps = address_cap_nodes(held_caps, &mut states);
+    let previous_caps = address_cap_nodes(previous_caps, &mut states);
+    let published = query::get_address_published_packages(conn, address, ADDRESS_LIMIT).await?;
+
+    let risk_summary = risk::summarize(current_caps.iter().map(|c| &c.risk));
+
+    Ok(AddressOverview {
+        owner_class,
+        current_caps,
+        held_caps,
+        previous_caps,
+        published,
+        risk_summary,
+    })
+}
+
+/// Caps of an address with the risk of their current state, every cap has one relation only.
+fn address_cap_nodes(
+    caps: Vec<models::AddressCap>,
+    states: &mut HashMap<String, (models::UpgradeCapState, risk::Risk)>,
+) -> Vec<AddressCapNode> {
+    caps.into_iter()
+        .filter_map(|cap| {
+            let (state, risk) = states.remove(&cap.object_id)?;
+            Some(AddressCapNode {
+                cap_id: state.object_id,
+                timestamp: cap.timestamp,
+                package_id: Some(state.package_id),
+                risk,
+            })
+        })
+        .collect()
+}
+
+fn address_cap_view(
+    network: Network,
+    cap: &AddressCapNode,
+    now: &chrono::DateTime<chrono::Utc>,
+) -> templates::AddressCap {
+    templates::AddressCap {
+        cap_id: format::short_sui_object_id(&cap.cap_id),
+        cap_id_full: cap.cap_id.clone(),
+        cap_url: format::phantom_cap_url(network, &cap.cap_id),
+        package_id: cap
+            .package_id
+            .as_deref()
+            .map_or(String::new(), format::short_sui_object_id),
+        package_id_full: cap.package_id.clone().unwrap_or_default(),
+        package_url: cap
+            .package_id
+            .as_deref()
+            .map_or(String::new(), |id| format::phantom_package_url(network, id)),
+        risk: cap.risk.level.to_string(),
+        risk_class: cap.risk.level.badge_class().to_string(),
+        risk_reasons: cap.risk.reasons.join("\n"),
+        time_ago: format::format_time_ago(&cap.timestamp, now),
+    }
+}
+
+/// Diff between two packages, `None` when the bytecode of either one isn't indexed.
+pub(crate) async fn fetch_package_diff(
+    conn: &mut AsyncPgConnection,
+    from: &str,
+    to: &str,
+) -> anyhow::Result<Option<Vec<package_diff::ModuleDiff>>> {
+    let old = package_bytecode(query::get_package_bytecode(conn, from).await?);
+    let new = package_bytecode(query::get_package_bytecode(conn, to).await?);
+
+    if old.is_empty() || new.is_empty() {
+        return Ok(None);
+    }
+
+    package_diff::diff_packages(&old, &new).map(Some)
+}
+
+fn package_bytecode(modules: Vec<models::PackageModuleBytecode>) -> BTreeMap<String, Vec<u8>> {
+    modules
+        .into_iter()
+        .filter_map(|m| Some((m.module_name, m.bytecode?)))
+        .collect()
+}
+
+fn module_diff_view(module: package_diff::ModuleDiff) -> templates::ModuleDiff {
+    let item_view = |item: package_diff::ItemChange| templates::ItemChange {
+        kind: item.kind.to_string(),
+        kind_class: item.kind.badge_class().to_string(),
+        old: item
+            .old
+            .as_deref()
+            .map_or(String::new(), format::short_move_type),
+        new: item
+            .new
+            .as_deref()
+            .map_or(String::new(), format::short_move_type),
+        exposed: item.exposed,
+        name: item.name,
+    };
+
+    templates::ModuleDiff {
+        kind: module.kind.to_string(),
+        kind_class: module.kind.badge_class().to_string(),
+        exposed_changes: module.exposed_changes(),
+        functions: module.functions.into_iter().map(item_view).collect(),
+        structs: module.structs.into_iter().map(item_view).collect(),
+        enums: module.enums.into_iter().map(item_view).collect(),
+        name: module.name,
+    }
+}
+
+fn cap_holder_view(network: Network, holder: models::UpgradeCapHolder) -> templates::CapHolder {
+    let outermost_id = holder.chain.last().cloned().unwrap_or_default();
+
+    let (controller, controller_full, controller_url) = match holder.root_owner.as_deref() {
+        Some(root) if root.starts_with("0x") => (
+            format::short_sui_object_id(root),
+            root.to_string(),
+            format::phantom_address_url(network, root),
+        ),
+        Some(models::SHARED_OWNER) => (
+            format!(
+                "shared object {}",
+                format::short_sui_object_id(&outermost_id)
+            ),
+            outermost_id.clone(),
+            format::sui_object_url(network, &outermost_id),
+        ),
+        Some(root) => (root.to_string(), root.to_string(), String::new()),
+        None => (
+            format!(
+                "unknown object {}",
+                format::short_sui_object_id(&outermost_id)
+            ),
+            outermost_id.clone(),
+            format::sui_object_url(network, &outermost_id),
+        ),
+    };
+
+    templates::CapHolder {
+        holder_type: format::short_move_type(&holder.holder_type),
+        holder_type_full: holder.holder_type.clone(),
+        holder_id: format::short_sui_object_id(&holder.holder_id),
+        holder_id_full: holder.holder_id.clone(),
+        holder_url: format::sui_object_url(network, &holder.holder_id),
+        controller,
+        controller_full,
+        controller_url,
+    }
+}
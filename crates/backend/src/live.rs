@@ -0,0 +1,236 @@
+use std::pin::pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::http::header;
+use actix_web::web::Bytes;
+use actix_web::{HttpRequest, HttpResponse, web};
+use actix_ws::{CloseCode, Message, MessageStream, Session};
+use anyhow::anyhow;
+use diesel_async::{AsyncPgConnection, pooled_connection::bb8::Pool};
+use futures_util::{Stream, StreamExt, future, stream};
+use log::{error, info, warn};
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::Instant;
+use tokio_postgres::{AsyncMessage, NoTls};
+use url::Url;
+
+use phantom_cap_db::Network;
+use phantom_cap_db::events::{self, CapEvent, EventFilter};
+
+type DbPool = Pool<AsyncPgConnection>;
+
+/// Events buffered for each subscriber, slower ones skip the oldest.
+const FEED_CAPACITY: usize = 256;
+/// Idle feeds are kept open by proxies with a message at this interval.
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+/// Delay before listening again once the connection is lost.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// Frames sent by WebSocket clients are only read to answer pings and closes.
+const MAX_CLIENT_FRAME: usize = 64 * 1024;
+
+/// Event with the addresses it concerns, resolved once for every subscriber.
+pub struct LiveEvent {
+    pub event: CapEvent,
+    /// Publisher of a version, both sides of a transfer, owner of a cap changing policy.
+    pub owners: Vec<String>,
+}
+
+/// Events committed by the indexer of a network, fanned out to its live feeds.
+#[derive(Clone)]
+pub struct LiveFeed {
+    sender: broadcast::Sender<Arc<LiveEvent>>,
+}
+
+impl LiveFeed {
+    /// Feed listening to the channel of `network` for as long as the server runs.
+    pub fn listen(database_url: Url, network: Network, pool: DbPool) -> LiveFeed {
+        let (sender, _) = broadcast::channel(FEED_CAPACITY);
+        let feed = LiveFeed { sender };
+
+        let listener = feed.clone();
+        actix_web::rt::spawn(async move {
+            let channel = events::channel(network);
+            loop {
+                if let Err(e) = listener.forward(&database_url, &channel, &pool).await {
+                    error!("Live feed of {} stopped: {:#}", network, e);
+                }
+                tokio::time::sleep(RECONNECT_DELAY).await;
+            }
+        });
+
+        feed
+    }
+
+    /// Forwards the notifications of `channel` until the connection is lost.
+    async fn forward(
+        &self,
+        database_url: &Url,
+        channel: &str,
+        pool: &DbPool,
+    ) -> anyhow::Result<()> {
+        let (client, mut connection) =
+            tokio_postgres::connect(database_url.as_str(), NoTls).await?;
+
+        // the connection only makes progress while polled, notifications included.
+        let (messages_sender, mut messages) = mpsc::unbounded_channel();
+        actix_web::rt::spawn(async move {
+            let mut connection_messages = stream::poll_fn(|cx| connection.poll_message(cx));
+            while let Some(message) = connection_messages.next().await {
+                if messages_sender.send(message).is_err() {
+                    break;
+                }
+            }
+        });
+
+        client.batch_execute(&format!("LISTEN {}", channel)).await?;
+        info!("Listening to {}", channel);
+
+        while let Some(message) = messages.recv().await {
+            let AsyncMessage::Notification(notification) = message? else {
+                continue;
+            };
+
+            match serde_json::from_str::<CapEvent>(notification.payload()) {
+                Ok(event) => {
+                    let event = resolve_owners(pool, event).await;
+                    // no subscriber is not an error.
+                    let _ = self.sender.send(Arc::new(event));
+                }
+                Err(e) => warn!("Invalid event on {}: {}", channel, e),
+            }
+        }
+
+        Err(anyhow!("Connection closed"))
+    }
+
+    /// Events matching `filter`, and `None` when there was none for a while.
+    pub fn subscribe(
+        &self,
+        filter: EventFilter,
+    ) -> impl Stream<Item = Option<Arc<LiveEvent>>> + 'static {
+        let receiver = self.sender.subscribe();
+
+        stream::unfold((receiver, filter), |(mut receiver, filter)| async move {
+            let keep_alive = Instant::now() + KEEP_ALIVE;
+            loop {
+                match tokio::time::timeout_at(keep_alive, receiver.recv()).await {
+                    Err(_) => return Some((None, (receiver, filter))),
+                    Ok(Ok(event)) if filter.matches(&event.event, &event.owners) => {
+                        return Some((Some(event), (receiver, filter)));
+                    }
+                    Ok(Ok(_)) => {}
+                    Ok(Err(broadcast::error::RecvError::Lagged(skipped))) => {
+                        warn!("Live feed subscriber skipped {} events", skipped);
+                    }
+                    Ok(Err(broadcast::error::RecvError::Closed)) => return None,
+                }
+            }
+        })
+    }
+}
+
+async fn resolve_owners(pool: &DbPool, event: CapEvent) -> LiveEvent {
+    let owners = match pool.get().await {
+        Ok(mut conn) => events::owners(&mut conn, &event).await.unwrap_or_default(),
+        Err(_) => vec![],
+    };
+
+    LiveEvent { event, owners }
+}
+
+/// Server-Sent Events response of a feed, `render` gives the data of an event.
+pub fn sse_response(
+    feed: impl Stream<Item = Option<Arc<LiveEvent>>> + 'static,
+    render: impl Fn(&LiveEvent) -> Option<String> + 'static,
+) -> HttpResponse {
+    let messages = feed.filter_map(move |event| {
+        let message = match event {
+            Some(event) => render(&event).map(|data| sse_message(event.event.kind(), &data)),
+            None => Some(Bytes::from_static(b": keep-alive\n\n")),
+        };
+        future::ready(message.map(Ok::<_, actix_web::Error>))
+    });
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        // reverse proxies would otherwise hold events back.
+        .insert_header(("X-Accel-Buffering", "no"))
+        .streaming(messages)
+}
+
+fn sse_message(event: &str, data: &str) -> Bytes {
+    let mut message = format!("event: {}\n", event);
+    for line in data.lines() {
+        message.push_str("data: ");
+        message.push_str(line);
+        message.push('\n');
+    }
+    message.push('\n');
+    Bytes::from(message)
+}
+
+/// WebSocket response of a feed, `render` gives the text message of an event.
+pub fn websocket_response(
+    req: &HttpRequest,
+    payload: web::Payload,
+    feed: impl Stream<Item = Option<Arc<LiveEvent>>> + 'static,
+    render: impl Fn(&LiveEvent) -> Option<String> + 'static,
+) -> Result<HttpResponse, actix_web::Error> {
+    let (response, session, messages) = actix_ws::handle(req, payload)?;
+
+    actix_web::rt::spawn(websocket_session(
+        session,
+        messages.max_frame_size(MAX_CLIENT_FRAME),
+        feed,
+        render,
+    ));
+
+    Ok(response)
+}
+
+/// Sends the events of the feed until either side closes the connection.
+async fn websocket_session(
+    mut session: Session,
+    mut messages: MessageStream,
+    feed: impl Stream<Item = Option<Arc<LiveEvent>>> + 'static,
+    render: impl Fn(&LiveEvent) -> Option<String> + 'static,
+) {
+    let mut feed = pin!(feed);
+
+    let reason = loop {
+        tokio::select! {
+            event = feed.next() => {
+                let sent = match event {
+                    Some(Some(event)) => match render(&event) {
+                        Some(text) => session.text(text).await,
+                        None => Ok(()),
+                    },
+                    Some(None) => session.ping(b"").await,
+                    None => break None,
+                };
+                // the client is gone, the feed ends with it.
+                if sent.is_err() {
+                    return;
+                }
+            }
+            message = messages.recv() => match message {
+                Some(Ok(Message::Ping(data))) => {
+                    if session.pong(&data).await.is_err() {
+                        return;
+                    }
+                }
+                Some(Ok(Message::Close(reason))) => break reason,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    warn!("Invalid WebSocket frame: {}", e);
+                    break Some(CloseCode::Protocol.into());
+                }
+                None => return,
+            },
+        }
+    };
+
+    let _ = session.close(reason).await;
+}
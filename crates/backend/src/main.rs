@@ -5,13 +5,24 @@ use diesel_async::{
     pooled_connection::{AsyncDieselConnectionManager, bb8::Pool},
 };
 
+use live::LiveFeed;
+use log::info;
+use phantom_cap_db::Network;
 use url::Url;
 
+mod api;
+mod audit;
+mod dto;
+mod explorer;
+mod feeds;
 mod format;
 mod handlers;
+mod live;
 mod models;
+mod owner;
+mod pagination;
 mod query;
-mod schema;
+mod risk;
 mod templates;
 
 #[actix_web::main]
@@ -30,22 +41,57 @@ async fn main() -> std::io::Result<()> {
         .parse::<u16>()
         .unwrap();
 
-    let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new(database_url);
-    let pool = Pool::builder().build(manager).await.unwrap();
+    let networks = Network::parse_list(
+        &std::env::var("NETWORKS").unwrap_or_else(|_| Network::Mainnet.to_string()),
+    )
+    .expect("Invalid NETWORKS");
+
+    // webhooks can only be managed once a token is set.
+    let webhook_token = std::env::var("WEBHOOK_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty());
+    if webhook_token.is_none() {
+        info!("WEBHOOK_TOKEN is not set, webhooks are disabled");
+    }
+    let webhook_token = web::Data::new(api::WebhookToken(webhook_token));
+
+    let mut pools = Vec::with_capacity(networks.len());
+    for network in networks {
+        let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new(
+            network.database_url(&database_url),
+        );
+        let pool = Pool::builder().build(manager).await.unwrap();
+        let feed = LiveFeed::listen(database_url.clone(), network, pool.clone());
+        pools.push((network, pool, feed));
+    }
+
+    // the root serves mainnet, or the first network when mainnet isn't indexed.
+    let (default_network, default_pool, default_feed) = pools
+        .iter()
+        .find(|(network, _, _)| *network == Network::Mainnet)
+        .or(pools.first())
+        .cloned()
+        .expect("NETWORKS must not be empty");
 
     HttpServer::new(move || {
         // {Real IP} {PATH} {STATUS CODE} {TOOK MS}
         let logger = Logger::new("ip=%{r}a path=%U status=%s took_ms=%D");
 
-        App::new()
-            .wrap(logger)
-            .app_data(web::Data::new(pool.clone()))
-            .service(handlers::home)
-            .service(handlers::search_cap)
-            .service(handlers::show_cap_info)
-            .service(handlers::show_cap_transfers)
-            .service(handlers::show_cap_versions)
-            .service(handlers::show_package_info)
+        let mut app = App::new().wrap(logger).app_data(webhook_token.clone());
+        for (network, pool, feed) in &pools {
+            app = app.service(
+                web::scope(&format!("/{}", network))
+                    .app_data(web::Data::new(pool.clone()))
+                    .app_data(web::Data::new(*network))
+                    .app_data(web::Data::new(feed.clone()))
+                    .configure(routes),
+            );
+        }
+
+        app.app_data(web::Data::new(default_pool.clone()))
+            .app_data(web::Data::new(default_network))
+            .app_data(web::Data::new(default_feed.clone()))
+            .configure(routes)
             .service(fs::Files::new("/static", "static").show_files_listing())
             .default_service(web::route().to(handlers::not_found))
     })
@@ -53,3 +99,53 @@ async fn main() -> std::io::Result<()> {
     .run()
     .await
 }
+
+/// Pages and API of a network, served under its name and, for the default network, from the root.
+fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(handlers::home)
+        .service(handlers::live_upgrades)
+        .service(handlers::search_cap)
+        .service(handlers::show_cap_info)
+        .service(handlers::show_cap_transfers)
+        .service(handlers::show_cap_versions)
+        .service(handlers::show_cap_policies)
+        .service(handlers::show_package_info)
+        .service(handlers::show_package_graph)
+        .service(handlers::show_package_diff)
+        .service(handlers::show_address_info)
+        .service(handlers::show_transaction)
+        .service(feeds::upgrades_feed)
+        .service(feeds::package_feed)
+        .service(feeds::cap_feed)
+        .service(feeds::address_feed)
+        // pages of other explorers, e.g. `/txblock/{digest}`, after the routes they overlap.
+        .service(explorer::translate_account)
+        .service(explorer::translate_object_tab)
+        .service(explorer::translate_transaction)
+        .service(explorer::translate_checkpoint)
+        .service(explorer::translate_coin)
+        .service(
+            web::scope("/api/v1")
+                .app_data(api::query_config())
+                .app_data(api::json_config())
+                .service(api::get_counts)
+                .service(api::search)
+                .service(api::get_cap)
+                .service(api::get_cap_versions)
+                .service(api::get_cap_transfers)
+                .service(api::get_cap_policies)
+                .service(api::get_package)
+                .service(api::get_package_graph)
+                .service(api::get_package_diff)
+                .service(api::get_address)
+                .service(api::get_transaction)
+                .service(api::get_events)
+                .service(api::get_events_ws)
+                .service(api::create_webhook)
+                .service(api::get_webhooks)
+                .service(api::get_webhook)
+                .service(api::delete_webhook)
+                .service(api::get_webhook_deliveries)
+                .default_service(web::route().to(api::not_found)),
+        );
+}
@@ -0,0 +1,168 @@
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::models::{
+    CapOwner, IMMUTABLE_OWNER, OwnerTypeEnum, SHARED_OWNER, UpgradeCapHolder, WRAPPED_OWNER,
+};
+
+/// Who controls an UpgradeCap, as far as the indexer could observe.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OwnerClass {
+    SingleKey,
+    MultiSig {
+        threshold: i32,
+        members: Vec<String>,
+        weights: Vec<i16>,
+    },
+    ZkLogin,
+    Passkey,
+    SharedObject,
+    /// Held by another object, `controller` being whoever holds its outermost object
+    /// when the chain could be resolved.
+    Wrapped {
+        controller: Option<Box<OwnerClass>>,
+    },
+    /// Destroyed or frozen cap.
+    NoOwner,
+    /// Address that never signed a transaction with its cap.
+    Unknown,
+}
+
+impl OwnerClass {
+    /// `signer` is the signer of the root owner of the holder, if any, else of the owner.
+    pub fn classify(
+        owner_address: &str,
+        holder: Option<&UpgradeCapHolder>,
+        signer: Option<&CapOwner>,
+        destroyed: bool,
+    ) -> Self {
+        if destroyed || owner_address == IMMUTABLE_OWNER {
+            return OwnerClass::NoOwner;
+        }
+
+        if holder.is_some() || owner_address == WRAPPED_OWNER {
+            // whoever holds the outermost object controls the cap, a frozen one is left
+            // to the Move logic of the wrapper.
+            let controller = holder
+                .and_then(|holder| holder.root_owner.as_deref())
+                .filter(|root| *root != IMMUTABLE_OWNER && *root != WRAPPED_OWNER)
+                .map(|root| Box::new(Self::classify(root, None, signer, false)));
+            return OwnerClass::Wrapped { controller };
+        }
+
+        if owner_address == SHARED_OWNER {
+            return OwnerClass::SharedObject;
+        }
+
+        let Some(signer) = signer else {
+            return OwnerClass::Unknown;
+        };
+
+        match signer.owner_type {
+            OwnerTypeEnum::SingleKey => OwnerClass::SingleKey,
+            OwnerTypeEnum::MultiSig => OwnerClass::MultiSig {
+                threshold: signer.multisig_threshold.unwrap_or_default(),
+                members: signer.multisig_members.clone().unwrap_or_default(),
+                weights: signer.multisig_weights.clone().unwrap_or_default(),
+            },
+            OwnerTypeEnum::ZkLogin => OwnerClass::ZkLogin,
+            OwnerTypeEnum::Passkey => OwnerClass::Passkey,
+        }
+    }
+
+    /// Members of a multisig owner, empty for other owners.
+    pub fn members(&self) -> &[String] {
+        match self {
+            OwnerClass::MultiSig { members, .. } => members,
+            _ => &[],
+        }
+    }
+}
+
+impl fmt::Display for OwnerClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OwnerClass::SingleKey => write!(f, "{}", OwnerTypeEnum::SingleKey),
+            OwnerClass::MultiSig {
+                threshold, weights, ..
+            } => {
+                let total: i32 = weights.iter().map(|w| *w as i32).sum();
+                write!(f, "{} {}/{}", OwnerTypeEnum::MultiSig, threshold, total)
+            }
+            OwnerClass::ZkLogin => write!(f, "{}", OwnerTypeEnum::ZkLogin),
+            OwnerClass::Passkey => write!(f, "{}", OwnerTypeEnum::Passkey),
+            OwnerClass::SharedObject => write!(f, "Shared Object"),
+            OwnerClass::Wrapped { .. } => write!(f, "Wrapped"),
+            OwnerClass::NoOwner => write!(f, "No Owner"),
+            OwnerClass::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holder(root_owner: Option<&str>) -> UpgradeCapHolder {
+        UpgradeCapHolder {
+            object_id: "0x1".to_string(),
+            holder_id: "0x2".to_string(),
+            holder_type: "0x3::vault::Vault".to_string(),
+            chain: vec!["0x2".to_string()],
+            root_owner: root_owner.map(str::to_string),
+            seq_checkpoint: 10,
+            tx_digest: "wrap".to_string(),
+            timestamp: chrono::DateTime::from_timestamp_millis(1_700_000_000_000).unwrap(),
+        }
+    }
+
+    fn signer(owner_type: OwnerTypeEnum) -> CapOwner {
+        CapOwner {
+            address: "0xa".to_string(),
+            owner_type,
+            multisig_threshold: None,
+            multisig_members: None,
+            multisig_weights: None,
+            seq_checkpoint: 10,
+            tx_digest: "wrap".to_string(),
+            timestamp: chrono::DateTime::from_timestamp_millis(1_700_000_000_000).unwrap(),
+        }
+    }
+
+    fn wrapped(controller: Option<OwnerClass>) -> OwnerClass {
+        OwnerClass::Wrapped {
+            controller: controller.map(Box::new),
+        }
+    }
+
+    #[test]
+    fn wrapped_caps_are_controlled_by_the_root_owner() {
+        let single_key = signer(OwnerTypeEnum::SingleKey);
+        let classify = |root: Option<&str>, signer: Option<&CapOwner>| {
+            OwnerClass::classify(WRAPPED_OWNER, Some(&holder(root)), signer, false)
+        };
+
+        assert_eq!(
+            classify(Some("0xa"), Some(&single_key)),
+            wrapped(Some(OwnerClass::SingleKey))
+        );
+        assert_eq!(
+            classify(Some("0xa"), None),
+            wrapped(Some(OwnerClass::Unknown))
+        );
+        assert_eq!(
+            classify(Some(SHARED_OWNER), None),
+            wrapped(Some(OwnerClass::SharedObject))
+        );
+
+        // frozen or unresolved wrappers are left to their Move logic.
+        assert_eq!(classify(Some(IMMUTABLE_OWNER), None), wrapped(None));
+        assert_eq!(classify(None, None), wrapped(None));
+        assert_eq!(
+            OwnerClass::classify(WRAPPED_OWNER, None, None, false),
+            wrapped(None)
+        );
+    }
+}
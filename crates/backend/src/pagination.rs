@@ -0,0 +1,166 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Deserialize;
+use sui_types::digests::TransactionDigest;
+
+use crate::models;
+
+const DEFAULT_PAGE_LIMIT: i64 = 20;
+const MAX_PAGE_LIMIT: i64 = 100;
+
+/// `?cursor=&limit=` parameters of paginated histories.
+#[derive(Deserialize)]
+pub struct PageQuery {
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+impl PageQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit
+            .unwrap_or(DEFAULT_PAGE_LIMIT)
+            .clamp(1, MAX_PAGE_LIMIT)
+    }
+}
+
+/// Rows of a page and the cursor of the next one, if any.
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Builds a page from `limit + 1` rows, the extra row only tells that more rows exist.
+    pub fn from_rows(mut rows: Vec<T>, limit: i64, cursor: impl Fn(&T) -> String) -> Self {
+        let has_more = rows.len() as i64 > limit;
+        rows.truncate(limit as usize);
+
+        let next_cursor = if has_more {
+            rows.last().map(cursor)
+        } else {
+            None
+        };
+
+        Page {
+            items: rows,
+            next_cursor,
+        }
+    }
+}
+
+/// Keyset cursor of the transfers history, the last seen `(seq_checkpoint, tx_digest)`.
+pub struct TransferCursor {
+    pub seq_checkpoint: i64,
+    pub tx_digest: String,
+}
+
+impl From<&models::UpgradeCapTransfer> for TransferCursor {
+    fn from(transfer: &models::UpgradeCapTransfer) -> Self {
+        TransferCursor {
+            seq_checkpoint: transfer.seq_checkpoint,
+            tx_digest: transfer.tx_digest.clone(),
+        }
+    }
+}
+
+impl fmt::Display for TransferCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.seq_checkpoint, self.tx_digest)
+    }
+}
+
+impl FromStr for TransferCursor {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || anyhow::anyhow!("Invalid transfers cursor");
+        let (seq_checkpoint, tx_digest) = s.split_once(':').ok_or_else(invalid)?;
+        let seq_checkpoint = seq_checkpoint
+            .parse::<u64>()
+            .ok()
+            .and_then(|seq| i64::try_from(seq).ok())
+            .ok_or_else(invalid)?;
+        let tx_digest = TransactionDigest::from_str(tx_digest).map_err(|_| invalid())?;
+
+        Ok(TransferCursor {
+            seq_checkpoint,
+            tx_digest: tx_digest.to_string(),
+        })
+    }
+}
+
+/// Keyset cursor of the versions history, the last seen version.
+pub fn parse_version_cursor(cursor: &str) -> anyhow::Result<i64> {
+    cursor
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid versions cursor"))
+}
+
+/// Keyset cursor of a webhook's delivery log, the last seen delivery id.
+pub fn parse_delivery_cursor(cursor: &str) -> anyhow::Result<i64> {
+    cursor
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid deliveries cursor"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str = "11111111111111111111111111111111";
+
+    #[test]
+    fn transfer_cursor_round_trips() {
+        let cursor = TransferCursor {
+            seq_checkpoint: 42,
+            tx_digest: DIGEST.to_string(),
+        };
+        let parsed: TransferCursor = cursor.to_string().parse().unwrap();
+
+        assert_eq!(parsed.seq_checkpoint, 42);
+        assert_eq!(parsed.tx_digest, DIGEST);
+    }
+
+    #[test]
+    fn malformed_transfer_cursors_are_rejected() {
+        for cursor in [
+            "",
+            ":",
+            "42",
+            &format!(":{}", DIGEST),
+            "42:",
+            "42:not-a-digest",
+            &format!("-1:{}", DIGEST),
+            &format!("abc:{}", DIGEST),
+            &format!("99999999999999999999:{}", DIGEST),
+            &format!("42:{}:1", DIGEST),
+        ] {
+            assert!(cursor.parse::<TransferCursor>().is_err(), "{:?}", cursor);
+        }
+    }
+
+    #[test]
+    fn version_cursors() {
+        assert_eq!(parse_version_cursor("7").unwrap(), 7);
+        assert!(parse_version_cursor("").is_err());
+        assert!(parse_version_cursor("v7").is_err());
+    }
+
+    #[test]
+    fn page_limit_is_clamped() {
+        let limit = |limit| {
+            PageQuery {
+                cursor: None,
+                limit,
+            }
+            .limit()
+        };
+
+        assert_eq!(limit(None), DEFAULT_PAGE_LIMIT);
+        assert_eq!(limit(Some(0)), 1);
+        assert_eq!(limit(Some(-5)), 1);
+        assert_eq!(limit(Some(MAX_PAGE_LIMIT)), MAX_PAGE_LIMIT);
+        assert_eq!(limit(Some(MAX_PAGE_LIMIT + 1)), MAX_PAGE_LIMIT);
+    }
+}
@@ -1,12 +1,40 @@
 use anyhow;
-use diesel::{ExpressionMethods, QueryDsl};
+use anyhow::Context;
+use diesel::sql_types::{Array, BigInt, Integer, Text};
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, JoinOnDsl, NullableExpressionMethods, QueryDsl,
+    SelectableHelper,
+};
 use diesel_async::{AsyncPgConnection, RunQueryDsl};
 
 use crate::models;
+use crate::pagination::TransferCursor;
+use phantom_cap_db::query;
 
-use crate::schema::upgrade_cap_transfers::dsl as upgrade_cap_transfers_dsl;
-use crate::schema::upgrade_cap_versions::dsl as upgrade_cap_versions_dsl;
-use crate::schema::upgrade_caps::dsl as upgrade_caps_dsl;
+/// Dependency chains are walked at most this deep.
+const MAX_GRAPH_DEPTH: i32 = 16;
+
+use phantom_cap_db::schema::cap_owners::dsl as cap_owners_dsl;
+use phantom_cap_db::schema::package_linkage::dsl as package_linkage_dsl;
+use phantom_cap_db::schema::package_modules::dsl as package_modules_dsl;
+use phantom_cap_db::schema::package_type_origins::dsl as package_type_origins_dsl;
+use phantom_cap_db::schema::packages::dsl as packages_dsl;
+use phantom_cap_db::schema::upgrade_cap_holders::dsl as upgrade_cap_holders_dsl;
+use phantom_cap_db::schema::upgrade_cap_policy_changes::dsl as upgrade_cap_policy_changes_dsl;
+use phantom_cap_db::schema::upgrade_cap_state::dsl as upgrade_cap_state_dsl;
+use phantom_cap_db::schema::upgrade_cap_transfers::dsl as upgrade_cap_transfers_dsl;
+use phantom_cap_db::schema::upgrade_cap_versions::dsl as upgrade_cap_versions_dsl;
+use phantom_cap_db::schema::upgrade_caps::dsl as upgrade_caps_dsl;
+use phantom_cap_db::schema::webhook_deliveries::dsl as webhook_deliveries_dsl;
+use phantom_cap_db::schema::webhook_subscriptions::dsl as webhook_subscriptions_dsl;
+
+/// Whether a query failed because no row matched, rather than on the database.
+pub fn is_not_found(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<diesel::result::Error>(),
+        Some(diesel::result::Error::NotFound)
+    )
+}
 
 pub async fn get_cap_by_id(
     conn: &mut AsyncPgConnection,
@@ -16,67 +44,117 @@ pub async fn get_cap_by_id(
         .filter(upgrade_caps_dsl::object_id.eq(cap_id))
         .first::<models::UpgradeCap>(conn)
         .await
-        .map_err(|_| anyhow::anyhow!("Cap not found"))
+        .context("Cap not found")
 }
 
-pub async fn get_cap_latest_version(
+pub async fn get_cap_state(
     conn: &mut AsyncPgConnection,
     cap_id: &str,
-) -> anyhow::Result<models::UpgradeCapVersion> {
-    upgrade_cap_versions_dsl::upgrade_cap_versions
-        .filter(upgrade_cap_versions_dsl::object_id.eq(cap_id))
-        .order(upgrade_cap_versions_dsl::version.desc())
-        .first::<models::UpgradeCapVersion>(conn)
-        .await
-        .map_err(|_| anyhow::anyhow!("Cap version not found"))
+) -> anyhow::Result<models::UpgradeCapState> {
+    query::get_cap_state(conn, cap_id)
+        .await?
+        .context("Cap state not found")
 }
 
-pub async fn get_cap_latest_transfer(
+pub async fn get_cap_first_transfer(
     conn: &mut AsyncPgConnection,
     cap_id: &str,
 ) -> anyhow::Result<models::UpgradeCapTransfer> {
     upgrade_cap_transfers_dsl::upgrade_cap_transfers
         .filter(upgrade_cap_transfers_dsl::object_id.eq(cap_id))
-        .order(upgrade_cap_transfers_dsl::seq_checkpoint.desc())
+        .order(upgrade_cap_transfers_dsl::seq_checkpoint.asc())
         .first::<models::UpgradeCapTransfer>(conn)
         .await
-        .map_err(|_| anyhow::anyhow!("Cap transfer not found"))
+        .context("Cap transfer not found")
 }
 
-pub async fn get_cap_first_transfer(
+pub async fn get_cap_holder(
     conn: &mut AsyncPgConnection,
     cap_id: &str,
-) -> anyhow::Result<models::UpgradeCapTransfer> {
-    upgrade_cap_transfers_dsl::upgrade_cap_transfers
-        .filter(upgrade_cap_transfers_dsl::object_id.eq(cap_id))
-        .order(upgrade_cap_transfers_dsl::seq_checkpoint.asc())
-        .first::<models::UpgradeCapTransfer>(conn)
+    tx_digest: &str,
+) -> anyhow::Result<models::UpgradeCapHolder> {
+    upgrade_cap_holders_dsl::upgrade_cap_holders
+        .filter(upgrade_cap_holders_dsl::object_id.eq(cap_id))
+        .filter(upgrade_cap_holders_dsl::tx_digest.eq(tx_digest))
+        .first::<models::UpgradeCapHolder>(conn)
         .await
-        .map_err(|_| anyhow::anyhow!("Cap transfer not found"))
+        .context("Cap holder not found")
+}
+
+pub async fn get_cap_owner(
+    conn: &mut AsyncPgConnection,
+    address: &str,
+) -> anyhow::Result<models::CapOwner> {
+    cap_owners_dsl::cap_owners
+        .filter(cap_owners_dsl::address.eq(address))
+        .first::<models::CapOwner>(conn)
+        .await
+        .context("Cap owner not found")
 }
 
 pub async fn get_cap_versions_history(
     conn: &mut AsyncPgConnection,
     cap_id: &str,
+    before_version: Option<i64>,
+    limit: i64,
 ) -> anyhow::Result<Vec<models::UpgradeCapVersion>> {
-    upgrade_cap_versions_dsl::upgrade_cap_versions
+    let mut query = upgrade_cap_versions_dsl::upgrade_cap_versions
         .filter(upgrade_cap_versions_dsl::object_id.eq(cap_id))
-        .order(upgrade_cap_versions_dsl::seq_checkpoint.desc())
+        .into_boxed();
+
+    if let Some(before_version) = before_version {
+        query = query.filter(upgrade_cap_versions_dsl::version.lt(before_version));
+    }
+
+    query
+        .order(upgrade_cap_versions_dsl::version.desc())
+        .limit(limit)
         .load::<models::UpgradeCapVersion>(conn)
         .await
-        .map_err(|_| anyhow::anyhow!("Failed to get cap versions history"))
+        .context("Failed to get cap versions history")
 }
 
 pub async fn get_cap_transfers_history(
     conn: &mut AsyncPgConnection,
     cap_id: &str,
+    before: Option<&TransferCursor>,
+    limit: i64,
 ) -> anyhow::Result<Vec<models::UpgradeCapTransfer>> {
-    upgrade_cap_transfers_dsl::upgrade_cap_transfers
+    let mut query = upgrade_cap_transfers_dsl::upgrade_cap_transfers
         .filter(upgrade_cap_transfers_dsl::object_id.eq(cap_id))
-        .order(upgrade_cap_transfers_dsl::seq_checkpoint.desc())
+        .into_boxed();
+
+    if let Some(before) = before {
+        query = query.filter(
+            upgrade_cap_transfers_dsl::seq_checkpoint
+                .lt(before.seq_checkpoint)
+                .or(upgrade_cap_transfers_dsl::seq_checkpoint
+                    .eq(before.seq_checkpoint)
+                    .and(upgrade_cap_transfers_dsl::tx_digest.lt(&before.tx_digest))),
+        );
+    }
+
+    query
+        .order((
+            upgrade_cap_transfers_dsl::seq_checkpoint.desc(),
+            upgrade_cap_transfers_dsl::tx_digest.desc(),
+        ))
+        .limit(limit)
         .load::<models::UpgradeCapTransfer>(conn)
         .await
-        .map_err(|_| anyhow::anyhow!("Failed to get cap transfers history"))
+        .context("Failed to get cap transfers history")
+}
+
+pub async fn get_cap_policy_history(
+    conn: &mut AsyncPgConnection,
+    cap_id: &str,
+) -> anyhow::Result<Vec<models::UpgradeCapPolicyChange>> {
+    upgrade_cap_policy_changes_dsl::upgrade_cap_policy_changes
+        .filter(upgrade_cap_policy_changes_dsl::object_id.eq(cap_id))
+        .order(upgrade_cap_policy_changes_dsl::seq_checkpoint.desc())
+        .load::<models::UpgradeCapPolicyChange>(conn)
+        .await
+        .context("Failed to get cap policy history")
 }
 
 pub async fn get_upgrade_caps_count(conn: &mut AsyncPgConnection) -> anyhow::Result<i64> {
@@ -84,7 +162,7 @@ pub async fn get_upgrade_caps_count(conn: &mut AsyncPgConnection) -> anyhow::Res
         .count()
         .get_result::<i64>(conn)
         .await
-        .map_err(|_| anyhow::anyhow!("Failed to get upgrade caps count"))
+        .context("Failed to get upgrade caps count")
 }
 
 pub async fn get_packages_count(conn: &mut AsyncPgConnection) -> anyhow::Result<i64> {
@@ -92,7 +170,7 @@ pub async fn get_packages_count(conn: &mut AsyncPgConnection) -> anyhow::Result<
         .count()
         .get_result::<i64>(conn)
         .await
-        .map_err(|_| anyhow::anyhow!("Failed to get packages count"))
+        .context("Failed to get packages count")
 }
 
 pub async fn get_transfers_count(conn: &mut AsyncPgConnection) -> anyhow::Result<i64> {
@@ -100,16 +178,558 @@ pub async fn get_transfers_count(conn: &mut AsyncPgConnection) -> anyhow::Result
         .count()
         .get_result::<i64>(conn)
         .await
-        .map_err(|_| anyhow::anyhow!("Failed to get transfers count"))
+        .context("Failed to get transfers count")
+}
+
+pub async fn get_latest_versions(
+    conn: &mut AsyncPgConnection,
+    limit: i64,
+) -> anyhow::Result<Vec<models::UpgradeCapVersion>> {
+    upgrade_cap_versions_dsl::upgrade_cap_versions
+        .order(upgrade_cap_versions_dsl::seq_checkpoint.desc())
+        .limit(limit)
+        .load::<models::UpgradeCapVersion>(conn)
+        .await
+        .context("Failed to get latest versions")
 }
 
 pub async fn get_package_by_id(
     conn: &mut AsyncPgConnection,
-    id: &String,
+    id: &str,
 ) -> anyhow::Result<models::UpgradeCapVersion> {
     upgrade_cap_versions_dsl::upgrade_cap_versions
-        .filter(upgrade_cap_versions_dsl::package_id.eq(&id))
+        .filter(upgrade_cap_versions_dsl::package_id.eq(id))
         .first::<models::UpgradeCapVersion>(conn)
         .await
-        .map_err(|_| anyhow::anyhow!("Package not found"))
+        .context("Package not found")
+}
+
+pub async fn get_package_lineage(
+    conn: &mut AsyncPgConnection,
+    cap_id: &str,
+) -> anyhow::Result<Vec<models::UpgradeCapVersion>> {
+    upgrade_cap_versions_dsl::upgrade_cap_versions
+        .filter(upgrade_cap_versions_dsl::object_id.eq(cap_id))
+        .order(upgrade_cap_versions_dsl::version.asc())
+        .load::<models::UpgradeCapVersion>(conn)
+        .await
+        .context("Failed to get package lineage")
+}
+
+pub async fn get_package_modules(
+    conn: &mut AsyncPgConnection,
+    package_id: &str,
+) -> anyhow::Result<Vec<models::PackageModuleName>> {
+    package_modules_dsl::package_modules
+        .filter(package_modules_dsl::package_id.eq(package_id))
+        .order(package_modules_dsl::module_name.asc())
+        .select(models::PackageModuleName::as_select())
+        .load::<models::PackageModuleName>(conn)
+        .await
+        .context("Failed to get package modules")
+}
+
+pub async fn get_package_bytecode(
+    conn: &mut AsyncPgConnection,
+    package_id: &str,
+) -> anyhow::Result<Vec<models::PackageModuleBytecode>> {
+    query::get_package_bytecode(conn, package_id)
+        .await
+        .context("Failed to get package bytecode")
+}
+
+pub async fn get_package_type_origins(
+    conn: &mut AsyncPgConnection,
+    package_id: &str,
+) -> anyhow::Result<Vec<models::PackageTypeOrigin>> {
+    package_type_origins_dsl::package_type_origins
+        .filter(package_type_origins_dsl::package_id.eq(package_id))
+        .order((
+            package_type_origins_dsl::module_name.asc(),
+            package_type_origins_dsl::datatype_name.asc(),
+        ))
+        .select(models::PackageTypeOrigin::as_select())
+        .load::<models::PackageTypeOrigin>(conn)
+        .await
+        .context("Failed to get package type origins")
+}
+
+pub async fn get_package_linkage(
+    conn: &mut AsyncPgConnection,
+    package_id: &str,
+) -> anyhow::Result<Vec<models::PackageLinkage>> {
+    package_linkage_dsl::package_linkage
+        .filter(package_linkage_dsl::package_id.eq(package_id))
+        .order(package_linkage_dsl::dependency_id.asc())
+        .select(models::PackageLinkage::as_select())
+        .load::<models::PackageLinkage>(conn)
+        .await
+        .context("Failed to get package linkage")
+}
+
+/// Audited class of every upgrade of a cap, keyed by package.
+pub async fn get_cap_upgrade_classes(
+    conn: &mut AsyncPgConnection,
+    cap_id: &str,
+) -> anyhow::Result<Vec<models::PackageUpgradeClass>> {
+    packages_dsl::packages
+        .filter(
+            packages_dsl::package_id.eq_any(
+                upgrade_cap_versions_dsl::upgrade_cap_versions
+                    .select(upgrade_cap_versions_dsl::package_id)
+                    .filter(upgrade_cap_versions_dsl::object_id.eq(cap_id)),
+            ),
+        )
+        .select(models::PackageUpgradeClass::as_select())
+        .load(conn)
+        .await
+        .context("Failed to get cap upgrade classes")
+}
+
+/// Packages the given one transitively depends on, at the versions they are linked at,
+/// leaving out `excluded` packages.
+pub async fn get_transitive_dependencies(
+    conn: &mut AsyncPgConnection,
+    package_id: &str,
+    excluded: &[&str],
+    limit: i64,
+) -> anyhow::Result<Vec<models::PackageGraphNode>> {
+    diesel::sql_query(
+        "WITH RECURSIVE dependencies (package_id, depth) AS (
+            SELECT linked_id, 1
+            FROM package_dependencies
+            WHERE package_id = $1
+            UNION
+            SELECT d.linked_id, r.depth + 1
+            FROM dependencies r
+            JOIN package_dependencies d ON d.package_id = r.package_id
+            WHERE r.depth < $2
+        )
+        SELECT package_id, MIN(depth) AS depth
+        FROM dependencies
+        WHERE package_id <> ALL($4)
+        GROUP BY package_id
+        ORDER BY depth, package_id
+        LIMIT $3",
+    )
+    .bind::<Text, _>(package_id)
+    .bind::<Integer, _>(MAX_GRAPH_DEPTH)
+    .bind::<BigInt, _>(limit)
+    .bind::<Array<Text>, _>(excluded)
+    .load::<models::PackageGraphNode>(conn)
+    .await
+    .context("Failed to get package dependencies")
+}
+
+/// State of each given cap, with its holder and the signer of its owner, or of the root
+/// owner of its holder, when indexed.
+pub async fn get_caps_ownership(
+    conn: &mut AsyncPgConnection,
+    cap_ids: &[String],
+) -> anyhow::Result<
+    Vec<(
+        models::UpgradeCapState,
+        Option<models::UpgradeCapHolder>,
+        Option<models::CapOwner>,
+    )>,
+> {
+    upgrade_cap_state_dsl::upgrade_cap_state
+        .left_join(
+            upgrade_cap_holders_dsl::upgrade_cap_holders.on(upgrade_cap_holders_dsl::object_id
+                .eq(upgrade_cap_state_dsl::object_id)
+                .and(
+                    upgrade_cap_holders_dsl::tx_digest.eq(upgrade_cap_state_dsl::owner_tx_digest),
+                )),
+        )
+        .left_join(
+            cap_owners_dsl::cap_owners.on(cap_owners_dsl::address
+                .eq(upgrade_cap_state_dsl::owner_address)
+                .or(cap_owners_dsl::address
+                    .nullable()
+                    .eq(upgrade_cap_holders_dsl::root_owner))),
+        )
+        .filter(upgrade_cap_state_dsl::object_id.eq_any(cap_ids))
+        .select((
+            models::UpgradeCapState::as_select(),
+            Option::<models::UpgradeCapHolder>::as_select(),
+            Option::<models::CapOwner>::as_select(),
+        ))
+        .load(conn)
+        .await
+        .context("Failed to get caps ownership")
+}
+
+/// Cap state of each given package, with the holder of the cap and the signer of its
+/// owner, or of the root owner of its holder, when indexed. Packages without an indexed
+/// cap are left out.
+pub async fn get_packages_cap_ownership(
+    conn: &mut AsyncPgConnection,
+    package_ids: &[String],
+) -> anyhow::Result<
+    Vec<(
+        String,
+        models::UpgradeCapState,
+        Option<models::UpgradeCapHolder>,
+        Option<models::CapOwner>,
+    )>,
+> {
+    upgrade_cap_versions_dsl::upgrade_cap_versions
+        .inner_join(
+            upgrade_cap_state_dsl::upgrade_cap_state
+                .on(upgrade_cap_state_dsl::object_id.eq(upgrade_cap_versions_dsl::object_id)),
+        )
+        .left_join(
+            upgrade_cap_holders_dsl::upgrade_cap_holders.on(upgrade_cap_holders_dsl::object_id
+                .eq(upgrade_cap_state_dsl::object_id)
+                .and(
+                    upgrade_cap_holders_dsl::tx_digest.eq(upgrade_cap_state_dsl::owner_tx_digest),
+                )),
+        )
+        .left_join(
+            cap_owners_dsl::cap_owners.on(cap_owners_dsl::address
+                .eq(upgrade_cap_state_dsl::owner_address)
+                .or(cap_owners_dsl::address
+                    .nullable()
+                    .eq(upgrade_cap_holders_dsl::root_owner))),
+        )
+        .filter(upgrade_cap_versions_dsl::package_id.eq_any(package_ids))
+        .select((
+            upgrade_cap_versions_dsl::package_id,
+            models::UpgradeCapState::as_select(),
+            Option::<models::UpgradeCapHolder>::as_select(),
+            Option::<models::CapOwner>::as_select(),
+        ))
+        .load(conn)
+        .await
+        .context("Failed to get packages cap ownership")
+}
+
+/// Packages transitively depending on any version of the given one. Only the
+/// latest version of each dependent counts, older ones may have dropped the link.
+pub async fn get_transitive_dependents(
+    conn: &mut AsyncPgConnection,
+    package_id: &str,
+    limit: i64,
+) -> anyhow::Result<Vec<models::PackageGraphNode>> {
+    diesel::sql_query(
+        "WITH RECURSIVE dependents (package_id, original_id, depth) AS (
+            SELECT p.package_id, p.original_id, 1
+            FROM package_dependencies d
+            JOIN packages p ON p.package_id = d.package_id
+            WHERE d.dependency_id = COALESCE(
+                (SELECT original_id FROM packages WHERE package_id = $1), $1
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM packages n
+                WHERE n.original_id = p.original_id AND n.version > p.version
+            )
+            UNION
+            SELECT p.package_id, p.original_id, r.depth + 1
+            FROM dependents r
+            JOIN package_dependencies d ON d.dependency_id = r.original_id
+            JOIN packages p ON p.package_id = d.package_id
+            WHERE r.depth < $2
+            AND NOT EXISTS (
+                SELECT 1 FROM packages n
+                WHERE n.original_id = p.original_id AND n.version > p.version
+            )
+        )
+        SELECT package_id, MIN(depth) AS depth
+        FROM dependents
+        GROUP BY package_id
+        ORDER BY depth, package_id
+        LIMIT $3",
+    )
+    .bind::<Text, _>(package_id)
+    .bind::<Integer, _>(MAX_GRAPH_DEPTH)
+    .bind::<BigInt, _>(limit)
+    .load::<models::PackageGraphNode>(conn)
+    .await
+    .context("Failed to get package dependents")
+}
+
+/// How an address relates to a cap it received.
+#[derive(Clone, Copy, Debug)]
+pub enum AddressCapRelation {
+    /// Owned by the address.
+    Owned,
+    /// Held by an object the address owned when the cap was wrapped, later
+    /// transfers of that object aren't indexed.
+    Held,
+    /// Transferred away or destroyed.
+    Previous,
+}
+
+impl AddressCapRelation {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AddressCapRelation::Owned => "owned",
+            AddressCapRelation::Held => "held",
+            AddressCapRelation::Previous => "previous",
+        }
+    }
+}
+
+/// Caps the address received, with the given relation to it.
+pub async fn get_address_caps(
+    conn: &mut AsyncPgConnection,
+    address: &str,
+    relation: AddressCapRelation,
+    limit: i64,
+) -> anyhow::Result<Vec<models::AddressCap>> {
+    diesel::sql_query(
+        "WITH touched AS (
+            SELECT object_id FROM upgrade_cap_transfers WHERE new_owner_address = $1
+            UNION
+            SELECT object_id FROM upgrade_cap_holders WHERE root_owner = $1
+        ),
+        caps AS (
+            SELECT s.object_id,
+                CASE
+                    WHEN s.destroyed THEN 'previous'
+                    WHEN s.owner_address = $1 THEN 'owned'
+                    WHEN h.root_owner = $1 THEN 'held'
+                    ELSE 'previous'
+                END AS relation,
+                COALESCE(s.destroyed_at, t.timestamp) AS timestamp
+            FROM upgrade_cap_state s
+            JOIN touched USING (object_id)
+            JOIN upgrade_caps c ON c.object_id = s.object_id
+            JOIN upgrade_cap_transfers t
+                ON t.object_id = s.object_id AND t.tx_digest = s.owner_tx_digest
+            LEFT JOIN upgrade_cap_holders h
+                ON h.object_id = s.object_id AND h.tx_digest = s.owner_tx_digest
+        )
+        SELECT object_id, timestamp
+        FROM caps
+        WHERE relation = $2
+        ORDER BY timestamp DESC, object_id
+        LIMIT $3",
+    )
+    .bind::<Text, _>(address)
+    .bind::<Text, _>(relation.as_str())
+    .bind::<BigInt, _>(limit)
+    .load::<models::AddressCap>(conn)
+    .await
+    .context("Failed to get address caps")
+}
+
+/// Packages published or upgraded by the address, the latest first.
+pub async fn get_address_published_packages(
+    conn: &mut AsyncPgConnection,
+    address: &str,
+    limit: i64,
+) -> anyhow::Result<Vec<models::UpgradeCapVersion>> {
+    upgrade_cap_versions_dsl::upgrade_cap_versions
+        .filter(upgrade_cap_versions_dsl::publisher.eq(address))
+        .order(upgrade_cap_versions_dsl::timestamp.desc())
+        .limit(limit)
+        .load::<models::UpgradeCapVersion>(conn)
+        .await
+        .context("Failed to get address published packages")
+}
+
+/// Transfers of caps to or from the address, the latest first.
+pub async fn get_address_transfers(
+    conn: &mut AsyncPgConnection,
+    address: &str,
+    limit: i64,
+) -> anyhow::Result<Vec<models::UpgradeCapTransfer>> {
+    upgrade_cap_transfers_dsl::upgrade_cap_transfers
+        .filter(
+            upgrade_cap_transfers_dsl::old_owner_address
+                .eq(address)
+                .or(upgrade_cap_transfers_dsl::new_owner_address.eq(address)),
+        )
+        .order(upgrade_cap_transfers_dsl::timestamp.desc())
+        .limit(limit)
+        .load::<models::UpgradeCapTransfer>(conn)
+        .await
+        .context("Failed to get address transfers")
+}
+
+pub async fn get_transaction_created_caps(
+    conn: &mut AsyncPgConnection,
+    tx_digest: &str,
+) -> anyhow::Result<Vec<models::UpgradeCap>> {
+    upgrade_caps_dsl::upgrade_caps
+        .filter(upgrade_caps_dsl::created_tx_digest.eq(tx_digest))
+        .order(upgrade_caps_dsl::object_id)
+        .load::<models::UpgradeCap>(conn)
+        .await
+        .context("Failed to get transaction created caps")
+}
+
+pub async fn get_transaction_destroyed_caps(
+    conn: &mut AsyncPgConnection,
+    tx_digest: &str,
+) -> anyhow::Result<Vec<models::UpgradeCapState>> {
+    upgrade_cap_state_dsl::upgrade_cap_state
+        .filter(upgrade_cap_state_dsl::destroyed_tx_digest.eq(tx_digest))
+        .filter(
+            upgrade_cap_state_dsl::object_id
+                .eq_any(upgrade_caps_dsl::upgrade_caps.select(upgrade_caps_dsl::object_id)),
+        )
+        .order(upgrade_cap_state_dsl::object_id)
+        .select(models::UpgradeCapState::as_select())
+        .load::<models::UpgradeCapState>(conn)
+        .await
+        .context("Failed to get transaction destroyed caps")
+}
+
+pub async fn get_transaction_versions(
+    conn: &mut AsyncPgConnection,
+    tx_digest: &str,
+) -> anyhow::Result<Vec<models::UpgradeCapVersion>> {
+    upgrade_cap_versions_dsl::upgrade_cap_versions
+        .filter(upgrade_cap_versions_dsl::tx_digest.eq(tx_digest))
+        .order(upgrade_cap_versions_dsl::object_id)
+        .load::<models::UpgradeCapVersion>(conn)
+        .await
+        .context("Failed to get transaction versions")
+}
+
+/// Audited class of every package published or upgraded in a transaction.
+pub async fn get_transaction_upgrade_classes(
+    conn: &mut AsyncPgConnection,
+    tx_digest: &str,
+) -> anyhow::Result<Vec<models::PackageUpgradeClass>> {
+    packages_dsl::packages
+        .filter(
+            packages_dsl::package_id.eq_any(
+                upgrade_cap_versions_dsl::upgrade_cap_versions
+                    .select(upgrade_cap_versions_dsl::package_id)
+                    .filter(upgrade_cap_versions_dsl::tx_digest.eq(tx_digest)),
+            ),
+        )
+        .select(models::PackageUpgradeClass::as_select())
+        .load(conn)
+        .await
+        .context("Failed to get transaction upgrade classes")
+}
+
+pub async fn get_transaction_transfers(
+    conn: &mut AsyncPgConnection,
+    tx_digest: &str,
+) -> anyhow::Result<Vec<models::UpgradeCapTransfer>> {
+    upgrade_cap_transfers_dsl::upgrade_cap_transfers
+        .filter(upgrade_cap_transfers_dsl::tx_digest.eq(tx_digest))
+        .order(upgrade_cap_transfers_dsl::object_id)
+        .load::<models::UpgradeCapTransfer>(conn)
+        .await
+        .context("Failed to get transaction transfers")
+}
+
+/// Objects the caps transferred in a transaction were put into.
+pub async fn get_transaction_holders(
+    conn: &mut AsyncPgConnection,
+    tx_digest: &str,
+) -> anyhow::Result<Vec<models::UpgradeCapHolder>> {
+    upgrade_cap_holders_dsl::upgrade_cap_holders
+        .filter(upgrade_cap_holders_dsl::tx_digest.eq(tx_digest))
+        .load::<models::UpgradeCapHolder>(conn)
+        .await
+        .context("Failed to get transaction holders")
+}
+
+pub async fn get_transaction_policy_changes(
+    conn: &mut AsyncPgConnection,
+    tx_digest: &str,
+) -> anyhow::Result<Vec<models::UpgradeCapPolicyChange>> {
+    upgrade_cap_policy_changes_dsl::upgrade_cap_policy_changes
+        .filter(upgrade_cap_policy_changes_dsl::tx_digest.eq(tx_digest))
+        .order(upgrade_cap_policy_changes_dsl::object_id)
+        .load::<models::UpgradeCapPolicyChange>(conn)
+        .await
+        .context("Failed to get transaction policy changes")
+}
+
+/// Caps currently held by an object, e.g. a multisig or governance wrapper.
+pub async fn get_held_caps(
+    conn: &mut AsyncPgConnection,
+    holder_id: &str,
+) -> anyhow::Result<Vec<String>> {
+    upgrade_cap_holders_dsl::upgrade_cap_holders
+        .inner_join(
+            upgrade_cap_state_dsl::upgrade_cap_state.on(upgrade_cap_state_dsl::object_id
+                .eq(upgrade_cap_holders_dsl::object_id)
+                .and(
+                    upgrade_cap_state_dsl::owner_tx_digest.eq(upgrade_cap_holders_dsl::tx_digest),
+                )),
+        )
+        .filter(upgrade_cap_holders_dsl::holder_id.eq(holder_id))
+        .filter(upgrade_cap_state_dsl::destroyed.eq(false))
+        .select(upgrade_cap_holders_dsl::object_id)
+        .load::<String>(conn)
+        .await
+        .context("Failed to get held caps")
+}
+
+pub async fn create_webhook(
+    conn: &mut AsyncPgConnection,
+    webhook: &models::NewWebhookSubscription,
+) -> anyhow::Result<models::WebhookSubscription> {
+    diesel::insert_into(webhook_subscriptions_dsl::webhook_subscriptions)
+        .values(webhook)
+        .returning(models::WebhookSubscription::as_returning())
+        .get_result::<models::WebhookSubscription>(conn)
+        .await
+        .context("Failed to create webhook")
+}
+
+pub async fn get_webhooks(
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<Vec<models::WebhookSubscription>> {
+    webhook_subscriptions_dsl::webhook_subscriptions
+        .select(models::WebhookSubscription::as_select())
+        .order(webhook_subscriptions_dsl::id)
+        .load::<models::WebhookSubscription>(conn)
+        .await
+        .context("Failed to get webhooks")
+}
+
+pub async fn get_webhook(
+    conn: &mut AsyncPgConnection,
+    webhook_id: i64,
+) -> anyhow::Result<models::WebhookSubscription> {
+    webhook_subscriptions_dsl::webhook_subscriptions
+        .find(webhook_id)
+        .select(models::WebhookSubscription::as_select())
+        .first::<models::WebhookSubscription>(conn)
+        .await
+        .context("Webhook not found")
+}
+
+/// Deletes a webhook and its delivery log, returns whether it existed.
+pub async fn delete_webhook(conn: &mut AsyncPgConnection, webhook_id: i64) -> anyhow::Result<bool> {
+    let deleted = diesel::delete(webhook_subscriptions_dsl::webhook_subscriptions.find(webhook_id))
+        .execute(conn)
+        .await
+        .context("Failed to delete webhook")?;
+
+    Ok(deleted > 0)
+}
+
+/// Delivery log of a webhook, latest first.
+pub async fn get_webhook_deliveries(
+    conn: &mut AsyncPgConnection,
+    webhook_id: i64,
+    before_id: Option<i64>,
+    limit: i64,
+) -> anyhow::Result<Vec<models::WebhookDelivery>> {
+    let mut query = webhook_deliveries_dsl::webhook_deliveries
+        .filter(webhook_deliveries_dsl::subscription_id.eq(webhook_id))
+        .select(models::WebhookDelivery::as_select())
+        .into_boxed();
+
+    if let Some(before_id) = before_id {
+        query = query.filter(webhook_deliveries_dsl::id.lt(before_id));
+    }
+
+    query
+        .order(webhook_deliveries_dsl::id.desc())
+        .limit(limit)
+        .load::<models::WebhookDelivery>(conn)
+        .await
+        .context("Failed to get webhook deliveries")
 }
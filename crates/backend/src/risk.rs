@@ -0,0 +1,425 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::format;
+use crate::models::UpgradeCompatibilityPolicyEnum;
+use crate::owner::OwnerClass;
+
+/// Upgrades within this many days count as recent activity.
+const RECENT_UPGRADE_DAYS: i64 = 30;
+
+/// Sui framework packages, upgraded by the validators rather than through an UpgradeCap.
+/// Written as the indexer stores package ids, without leading zeros.
+pub const FRAMEWORK_PACKAGES: [&str; 3] = ["0x1", "0x2", "0x3"];
+
+/// Risk levels of the README risk matrix, ordered from the safest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Best,
+    Low,
+    Medium,
+    High,
+}
+
+impl RiskLevel {
+    pub fn badge_class(&self) -> &'static str {
+        match self {
+            RiskLevel::Best => "badge-best",
+            RiskLevel::Low => "badge-low",
+            RiskLevel::Medium => "badge-medium",
+            RiskLevel::High => "badge-high",
+        }
+    }
+}
+
+impl fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RiskLevel::Best => write!(f, "Best"),
+            RiskLevel::Low => write!(f, "Low Risk"),
+            RiskLevel::Medium => write!(f, "Medium Risk"),
+            RiskLevel::High => write!(f, "High Risk"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Risk {
+    pub level: RiskLevel,
+    pub reasons: Vec<String>,
+}
+
+/// Describes the owner column of the risk matrix, `None` when nobody can upgrade.
+/// The flag tells whether upgrades need more than a single signer.
+fn owner_control(owner: &OwnerClass) -> Option<(String, bool)> {
+    match owner {
+        OwnerClass::SingleKey => Some(("Owned by a single key".to_string(), false)),
+        OwnerClass::ZkLogin => Some(("Owned by a single zkLogin account".to_string(), false)),
+        OwnerClass::Passkey => Some(("Owned by a single passkey".to_string(), false)),
+        OwnerClass::MultiSig {
+            threshold, members, ..
+        } => Some((
+            format!(
+                "Owned by a {}-member MultiSig with threshold {}",
+                members.len(),
+                threshold
+            ),
+            true,
+        )),
+        OwnerClass::SharedObject => Some((
+            "Held by a shared object, upgrades depend on its Move logic".to_string(),
+            true,
+        )),
+        // a wrapper held by an account gives it the same control as owning the cap.
+        OwnerClass::Wrapped {
+            controller: Some(controller),
+        } => owner_control(controller).map(|(reason, multi_party)| {
+            (
+                format!("{}, through the wrapping object", reason),
+                multi_party,
+            )
+        }),
+        OwnerClass::Wrapped { controller: None } => Some((
+            "Wrapped in another object, upgrades depend on its Move logic".to_string(),
+            true,
+        )),
+        OwnerClass::Unknown => Some((
+            "Owner type is unknown, assumed to be a single key".to_string(),
+            false,
+        )),
+        OwnerClass::NoOwner => None,
+    }
+}
+
+/// Assesses an UpgradeCap following the README risk matrix,
+/// recent upgrades raise a low risk to medium.
+pub fn assess(
+    policy: &UpgradeCompatibilityPolicyEnum,
+    owner: &OwnerClass,
+    destroyed: bool,
+    last_upgrade: Option<&DateTime<Utc>>,
+    now: &DateTime<Utc>,
+) -> Risk {
+    if destroyed {
+        return Risk {
+            level: RiskLevel::Best,
+            reasons: vec!["UpgradeCap is destroyed, the package is immutable".to_string()],
+        };
+    }
+
+    let control = owner_control(owner);
+    let (owner_reason, multi_party) = match control {
+        Some(control) if *policy != UpgradeCompatibilityPolicyEnum::Immutable => control,
+        _ => {
+            return Risk {
+                level: RiskLevel::Best,
+                reasons: vec!["UpgradeCap is frozen, the package can't be upgraded".to_string()],
+            };
+        }
+    };
+
+    let (mut level, policy_reason) = match policy {
+        UpgradeCompatibilityPolicyEnum::Compatible if multi_party => {
+            (RiskLevel::Medium, "Any compatible upgrade is allowed")
+        }
+        UpgradeCompatibilityPolicyEnum::Compatible => {
+            (RiskLevel::High, "Any compatible upgrade is allowed")
+        }
+        UpgradeCompatibilityPolicyEnum::Additive => {
+            (RiskLevel::Medium, "Only additive upgrades are allowed")
+        }
+        UpgradeCompatibilityPolicyEnum::DepOnly => {
+            (RiskLevel::Low, "Only dependency upgrades are allowed")
+        }
+        UpgradeCompatibilityPolicyEnum::Immutable => unreachable!("frozen caps are handled above"),
+    };
+
+    let mut reasons = vec![owner_reason, policy_reason.to_string()];
+
+    if let Some(last_upgrade) = last_upgrade {
+        let days = now.signed_duration_since(last_upgrade).num_days();
+        if days < RECENT_UPGRADE_DAYS {
+            reasons.push(format!(
+                "Upgraded within the last {} days",
+                RECENT_UPGRADE_DAYS
+            ));
+            level = level.max(RiskLevel::Medium);
+        }
+    }
+
+    Risk { level, reasons }
+}
+
+/// Worst-case risk over a package and its transitive dependencies.
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskRollUp {
+    pub level: RiskLevel,
+    /// Dependency carrying the worst risk, `None` when no dependency is riskier than the package.
+    pub weakest_link: Option<String>,
+    pub reasons: Vec<String>,
+}
+
+/// Rolls up the risk of a package and its dependencies, ordered from the closest.
+/// Dependencies without an indexed cap can't be assessed and are only reported.
+pub fn roll_up(own: Option<&Risk>, dependencies: &[(&str, Option<&Risk>)]) -> RiskRollUp {
+    let own_level = own.map_or(RiskLevel::Best, |r| r.level);
+
+    // the closest dependency wins among equally risky ones.
+    let weakest = dependencies
+        .iter()
+        .filter_map(|(id, risk)| Some((*id, (*risk)?)))
+        .fold(
+            None,
+            |worst: Option<(&str, &Risk)>, (id, risk)| match worst {
+                Some((_, w)) if w.level >= risk.level => worst,
+                _ => Some((id, risk)),
+            },
+        )
+        .filter(|(_, risk)| risk.level > own_level);
+
+    let mut reasons = vec![];
+    let level = match weakest {
+        Some((id, risk)) => {
+            reasons.push(format!(
+                "Depends on {} ({}): {}",
+                format::short_sui_object_id(id),
+                risk.level,
+                risk.reasons.join(", ")
+            ));
+            risk.level
+        }
+        None if own.is_some() => {
+            reasons.push("No dependency is riskier than the package itself".to_string());
+            own_level
+        }
+        None => own_level,
+    };
+
+    let unassessed = dependencies.iter().filter(|(_, r)| r.is_none()).count();
+    match unassessed {
+        0 => {}
+        1 => reasons.push("1 dependency has no indexed UpgradeCap and wasn't assessed".to_string()),
+        _ => reasons.push(format!(
+            "{} dependencies have no indexed UpgradeCap and weren't assessed",
+            unassessed
+        )),
+    }
+
+    RiskRollUp {
+        level,
+        weakest_link: weakest.map(|(id, _)| id.to_string()),
+        reasons,
+    }
+}
+
+/// Risk of every cap an address controls, the level being the worst one.
+#[derive(Debug, Clone, Serialize)]
+pub struct RiskSummary {
+    pub level: RiskLevel,
+    pub best: usize,
+    pub low: usize,
+    pub medium: usize,
+    pub high: usize,
+}
+
+pub fn summarize<'a>(risks: impl IntoIterator<Item = &'a Risk>) -> RiskSummary {
+    let mut summary = RiskSummary {
+        level: RiskLevel::Best,
+        best: 0,
+        low: 0,
+        medium: 0,
+        high: 0,
+    };
+
+    for risk in risks {
+        summary.level = summary.level.max(risk.level);
+        match risk.level {
+            RiskLevel::Best => summary.best += 1,
+            RiskLevel::Low => summary.low += 1,
+            RiskLevel::Medium => summary.medium += 1,
+            RiskLevel::High => summary.high += 1,
+        }
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use sui_types::base_types::ObjectID;
+
+    use super::*;
+
+    #[test]
+    fn framework_packages_match_stored_ids() {
+        for id in FRAMEWORK_PACKAGES {
+            let long = format!("0x{:0>64}", id.trim_start_matches("0x"));
+            assert_eq!(
+                ObjectID::from_hex_literal(&long).unwrap().to_hex_literal(),
+                id
+            );
+        }
+    }
+
+    /// README risk matrix, levels follow `UpgradeCompatibilityPolicyEnum::ALL`.
+    fn matrix() -> Vec<(OwnerClass, [RiskLevel; 4])> {
+        use RiskLevel::*;
+
+        let multisig = OwnerClass::MultiSig {
+            threshold: 2,
+            members: vec!["0xa".to_string(), "0xb".to_string()],
+            weights: vec![1, 1],
+        };
+
+        vec![
+            (OwnerClass::SingleKey, [High, Medium, Low, Best]),
+            (OwnerClass::ZkLogin, [High, Medium, Low, Best]),
+            (OwnerClass::Passkey, [High, Medium, Low, Best]),
+            (OwnerClass::Unknown, [High, Medium, Low, Best]),
+            (multisig.clone(), [Medium, Medium, Low, Best]),
+            (OwnerClass::SharedObject, [Medium, Medium, Low, Best]),
+            (
+                OwnerClass::Wrapped { controller: None },
+                [Medium, Medium, Low, Best],
+            ),
+            // a wrapper held by a single account is as risky as the account owning the cap.
+            (
+                OwnerClass::Wrapped {
+                    controller: Some(Box::new(OwnerClass::SingleKey)),
+                },
+                [High, Medium, Low, Best],
+            ),
+            (
+                OwnerClass::Wrapped {
+                    controller: Some(Box::new(multisig.clone())),
+                },
+                [Medium, Medium, Low, Best],
+            ),
+            (
+                OwnerClass::Wrapped {
+                    controller: Some(Box::new(OwnerClass::SharedObject)),
+                },
+                [Medium, Medium, Low, Best],
+            ),
+            (OwnerClass::NoOwner, [Best, Best, Best, Best]),
+        ]
+    }
+
+    #[test]
+    fn assess_follows_matrix() {
+        let now = Utc::now();
+        let recent = now - Duration::days(RECENT_UPGRADE_DAYS - 1);
+        let old = now - Duration::days(RECENT_UPGRADE_DAYS + 1);
+
+        for (owner, levels) in matrix() {
+            for (policy, level) in UpgradeCompatibilityPolicyEnum::ALL.iter().zip(levels) {
+                let case = format!("{:?} {:?}", owner, policy);
+
+                assert_eq!(
+                    assess(policy, &owner, false, None, &now).level,
+                    level,
+                    "{}",
+                    case
+                );
+                assert_eq!(
+                    assess(policy, &owner, false, Some(&old), &now).level,
+                    level,
+                    "{} upgraded long ago",
+                    case
+                );
+
+                // a recent upgrade raises a cap that can still be upgraded to medium.
+                let recent_level = match level {
+                    RiskLevel::Best => RiskLevel::Best,
+                    level => level.max(RiskLevel::Medium),
+                };
+                assert_eq!(
+                    assess(policy, &owner, false, Some(&recent), &now).level,
+                    recent_level,
+                    "{} upgraded recently",
+                    case
+                );
+
+                assert_eq!(
+                    assess(policy, &owner, true, Some(&recent), &now).level,
+                    RiskLevel::Best,
+                    "{} destroyed",
+                    case
+                );
+            }
+        }
+    }
+
+    fn risk(level: RiskLevel) -> Risk {
+        Risk {
+            level,
+            reasons: vec![level.to_string()],
+        }
+    }
+
+    #[test]
+    fn roll_up_picks_the_closest_riskiest_dependency() {
+        let own = risk(RiskLevel::Low);
+        let (medium, high, closer_high) = (
+            risk(RiskLevel::Medium),
+            risk(RiskLevel::High),
+            risk(RiskLevel::High),
+        );
+
+        // dependencies are ordered from the closest.
+        let roll_up = roll_up(
+            Some(&own),
+            &[
+                ("0x1a", Some(&medium)),
+                ("0x1b", Some(&closer_high)),
+                ("0x2a", Some(&high)),
+            ],
+        );
+
+        assert_eq!(roll_up.level, RiskLevel::High);
+        assert_eq!(roll_up.weakest_link.as_deref(), Some("0x1b"));
+        assert_eq!(roll_up.reasons.len(), 1);
+        assert!(roll_up.reasons[0].starts_with("Depends on"));
+    }
+
+    #[test]
+    fn roll_up_keeps_the_package_risk_without_riskier_dependency() {
+        let own = risk(RiskLevel::Medium);
+        let (low, medium) = (risk(RiskLevel::Low), risk(RiskLevel::Medium));
+
+        let roll_up = roll_up(Some(&own), &[("0x1", Some(&low)), ("0x2", Some(&medium))]);
+
+        assert_eq!(roll_up.level, RiskLevel::Medium);
+        assert_eq!(roll_up.weakest_link, None);
+        assert_eq!(
+            roll_up.reasons,
+            ["No dependency is riskier than the package itself"]
+        );
+    }
+
+    #[test]
+    fn roll_up_counts_unassessed_dependencies() {
+        let low = risk(RiskLevel::Low);
+
+        let roll_up = roll_up(None, &[("0x1", None), ("0x2", Some(&low)), ("0x3", None)]);
+
+        assert_eq!(roll_up.level, RiskLevel::Low);
+        assert_eq!(roll_up.weakest_link.as_deref(), Some("0x2"));
+        assert_eq!(
+            roll_up.reasons.last().map(String::as_str),
+            Some("2 dependencies have no indexed UpgradeCap and weren't assessed")
+        );
+
+        let unassessed = super::roll_up(None, &[("0x1", None)]);
+        assert_eq!(unassessed.level, RiskLevel::Best);
+        assert_eq!(unassessed.weakest_link, None);
+        assert_eq!(
+            unassessed.reasons,
+            ["1 dependency has no indexed UpgradeCap and wasn't assessed"]
+        );
+    }
+}
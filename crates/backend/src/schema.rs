@@ -1,49 +0,0 @@
-// @generated automatically by Diesel CLI.
-
-pub mod sql_types {
-    #[derive(diesel::sql_types::SqlType)]
-    #[diesel(postgres_type(name = "upgrade_compatibility_policy"))]
-    pub struct UpgradeCompatibilityPolicy;
-}
-
-diesel::table! {
-    upgrade_cap_transfers (object_id, tx_digest) {
-        object_id -> Text,
-        old_owner_address -> Text,
-        new_owner_address -> Text,
-        seq_checkpoint -> Int8,
-        tx_digest -> Text,
-        timestamp -> Timestamptz,
-    }
-}
-
-diesel::table! {
-    upgrade_cap_versions (object_id, version) {
-        object_id -> Text,
-        package_id -> Text,
-        version -> Int8,
-        seq_checkpoint -> Int8,
-        tx_digest -> Text,
-        publisher -> Text,
-        timestamp -> Timestamptz,
-    }
-}
-
-diesel::table! {
-    use diesel::sql_types::*;
-    use super::sql_types::UpgradeCompatibilityPolicy;
-
-    upgrade_caps (object_id) {
-        object_id -> Text,
-        policy -> UpgradeCompatibilityPolicy,
-        created_seq_checkpoint -> Int8,
-        created_tx_digest -> Text,
-        created_at -> Timestamptz,
-    }
-}
-
-diesel::allow_tables_to_appear_in_same_query!(
-    upgrade_cap_transfers,
-    upgrade_cap_versions,
-    upgrade_caps,
-);
@@ -7,22 +7,49 @@ pub struct NotFound;
 #[derive(Template)]
 #[template(path = "index.html")]
 pub struct HomePage {
+    pub search_url: String,
     pub upgrade_caps_count: i64,
     pub packages_count: i64,
     pub transfers_count: i64,
+    pub upgrades: Vec<LatestUpgrade>,
+    pub upgrades_limit: i64,
+    pub live_upgrades_url: String,
+    pub feed_url: String,
+}
+
+/// Rows of the latest upgrades table, also pushed one by one as they are indexed.
+#[derive(Template)]
+#[template(path = "latest_upgrades.html")]
+pub struct LatestUpgrades {
+    pub upgrades: Vec<LatestUpgrade>,
+}
+
+pub struct LatestUpgrade {
+    pub package_id: String,
+    pub package_id_full: String,
+    pub package_url: String,
+    pub version: i64,
+    pub cap_id: String,
+    pub cap_url: String,
+    pub publisher: String,
+    pub publisher_full: String,
+    pub publisher_url: String,
+    pub tx_page_url: String,
+    pub time_ago: String,
 }
 
 #[derive(Template, Debug)]
 #[template(path = "search.html")]
 pub enum SearchResult {
-    Cap(String),
-    Package(String),
+    Cap { id: String, url: String },
+    Package { id: String, url: String },
 }
 
 #[derive(Template)]
 #[template(path = "upgrade_cap.html")]
 pub struct Cap {
     pub id: String,
+    pub url: String,
     pub short_id: String,
     pub package: String,
     pub package_full: String,
@@ -32,50 +59,324 @@ pub struct Cap {
     pub owner: String,
     pub owner_full: String,
     pub owner_url: String,
+    pub holder: Option<CapHolder>,
+    pub owner_type: String,
+    pub owner_members: String,
+    pub badges: Badges,
+    pub audit: UpgradeAudit,
     pub created_by: String,
     pub created_by_full: String,
     pub created_by_url: String,
     pub tx_digest_url: String,
     pub time_ago: String,
+    pub destroyed: bool,
+    pub destroyed_tx_url: String,
+    pub destroyed_time_ago: String,
+    pub feed_url: String,
 }
 
 #[derive(Template)]
 #[template(path = "cap_versions.html")]
 pub struct CapVersions {
+    pub cap_url: String,
     pub versions: Vec<CapVersion>,
+    pub next_cursor: Option<String>,
+    pub limit: i64,
 }
 
 #[derive(Template)]
 #[template(path = "cap_transfers.html")]
 pub struct CapTransfers {
+    pub cap_url: String,
     pub transfers: Vec<CapTransfer>,
+    pub next_cursor: Option<String>,
+    pub limit: i64,
+}
+
+#[derive(Template)]
+#[template(path = "cap_policies.html")]
+pub struct CapPolicies {
+    pub policies: Vec<CapPolicyChange>,
 }
 
 #[derive(Template)]
 #[template(path = "package.html")]
 pub struct Package {
     pub id: String,
+    pub url: String,
     pub short_id: String,
     // id_url: String,
     pub upgrade_cap_id: String,
     pub upgrade_cap_id_full: String,
     pub upgrade_cap_id_url: String,
     pub version: i64,
+    pub is_latest: bool,
+    pub latest_id: String,
+    pub latest_url: String,
+    pub latest_version: i64,
+    pub lineage: Vec<PackageVersion>,
+    pub modules: Vec<PackageModule>,
+    pub dependencies: Vec<PackageDependency>,
+    pub owner_type: String,
+    pub badges: Badges,
+    pub risk_roll_up: String,
+    pub risk_roll_up_class: String,
+    pub risk_roll_up_reasons: Vec<String>,
     pub published_by: String,
     pub published_by_full: String,
     pub published_by_url: String,
     pub tx_digest_url: String,
     pub time_ago: String,
+    pub feed_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "address.html")]
+pub struct Address {
+    pub address: String,
+    pub short_address: String,
+    pub explorer_url: String,
+    pub owner_type: String,
+    pub owner_members: String,
+    pub risk: String,
+    pub risk_class: String,
+    pub best_count: usize,
+    pub low_count: usize,
+    pub medium_count: usize,
+    pub high_count: usize,
+    pub current_caps: Vec<AddressCap>,
+    pub held_caps: Vec<AddressCap>,
+    pub previous_caps: Vec<AddressCap>,
+    pub published: Vec<PublishedPackage>,
+    pub limit: i64,
+    pub feed_url: String,
+}
+
+pub struct AddressCap {
+    pub cap_id: String,
+    pub cap_id_full: String,
+    pub cap_url: String,
+    pub package_id: String,
+    pub package_id_full: String,
+    pub package_url: String,
+    pub risk: String,
+    pub risk_class: String,
+    pub risk_reasons: String,
+    pub time_ago: String,
+}
+
+pub struct PublishedPackage {
+    pub package_id: String,
+    pub package_id_full: String,
+    pub package_url: String,
+    pub version: i64,
+    pub cap_id: String,
+    pub cap_url: String,
+    pub tx_url: String,
+    pub time_ago: String,
+}
+
+#[derive(Template)]
+#[template(path = "package_graph.html")]
+pub struct PackageGraph {
+    pub risk: String,
+    pub risk_class: String,
+    pub risk_reasons: Vec<String>,
+    pub weakest_link: String,
+    pub dependencies_count: usize,
+    pub dependencies: Vec<GraphPackage>,
+    pub dependents: Vec<GraphPackage>,
+    pub limit: i64,
+}
+
+pub struct GraphPackage {
+    pub package_id: String,
+    pub package_id_full: String,
+    pub package_url: String,
+    pub direct: bool,
+    pub depth: i32,
+    pub risk: String,
+    pub risk_class: String,
+    pub risk_reasons: String,
+    pub cap_url: String,
+}
+
+#[derive(Template)]
+#[template(path = "package_diff.html")]
+pub struct PackageDiff {
+    pub from: String,
+    pub from_full: String,
+    pub from_url: String,
+    pub to: String,
+    pub to_full: String,
+    pub to_url: String,
+    pub modules: Vec<ModuleDiff>,
+}
+
+#[derive(Template)]
+#[template(path = "transaction.html")]
+pub struct Transaction {
+    pub digest: String,
+    pub short_digest: String,
+    pub explorer_url: String,
+    pub involved: bool,
+    pub seq_checkpoint: i64,
+    pub seq_checkpoint_url: String,
+    pub time_ago: String,
+    pub created: Vec<TransactionCap>,
+    pub versions: Vec<TransactionVersion>,
+    pub transfers: Vec<TransactionTransfer>,
+    pub policy_changes: Vec<TransactionPolicyChange>,
+    pub destroyed: Vec<TransactionCap>,
+}
+
+pub struct TransactionCap {
+    pub cap_id: String,
+    pub cap_id_full: String,
+    pub cap_url: String,
+}
+
+pub struct TransactionVersion {
+    pub cap_id: String,
+    pub cap_url: String,
+    pub version: i64,
+    pub upgrade_class: String,
+    pub upgrade_class_badge: String,
+    pub package_id: String,
+    pub package_id_full: String,
+    pub package_url: String,
+    pub publisher: String,
+    pub publisher_full: String,
+    pub publisher_url: String,
+}
+
+pub struct TransactionTransfer {
+    pub cap_id: String,
+    pub cap_url: String,
+    pub from: String,
+    pub from_full: String,
+    pub from_url: String,
+    pub to: String,
+    pub to_full: String,
+    pub to_url: String,
+    pub holder: Option<CapHolder>,
+}
+
+pub struct TransactionPolicyChange {
+    pub cap_id: String,
+    pub cap_url: String,
+    pub old_policy: String,
+    pub new_policy: String,
+}
+
+/// Explorer page with no single PhantomCap counterpart, e.g. a transaction touching
+/// several caps or an object with no UpgradeCap involvement.
+#[derive(Template)]
+#[template(path = "translation.html")]
+pub struct Translation {
+    pub kind: String,
+    pub id: String,
+    pub short_id: String,
+    pub explorer_url: String,
+    pub message: String,
+    pub matches: Vec<TranslationMatch>,
+}
+
+pub struct TranslationMatch {
+    pub kind: String,
+    pub id: String,
+    pub id_full: String,
+    pub url: String,
+    pub events: String,
+}
+
+pub struct ModuleDiff {
+    pub name: String,
+    pub kind: String,
+    pub kind_class: String,
+    pub exposed_changes: bool,
+    pub functions: Vec<ItemChange>,
+    pub structs: Vec<ItemChange>,
+    pub enums: Vec<ItemChange>,
+}
+
+pub struct ItemChange {
+    pub name: String,
+    pub kind: String,
+    pub kind_class: String,
+    pub old: String,
+    pub new: String,
+    pub exposed: bool,
+}
+
+pub struct Badges {
+    pub risk: String,
+    pub risk_class: String,
+    pub risk_reasons: Vec<String>,
+    pub policy: String,
+    pub owner_type: String,
+}
+
+pub struct UpgradeAudit {
+    pub effective_class: String,
+    pub effective_class_badge: String,
+    pub findings: Vec<String>,
+    pub dep_only_under_compatible: bool,
+}
+
+pub struct CapHolder {
+    pub holder_type: String,
+    pub holder_type_full: String,
+    pub holder_id: String,
+    pub holder_id_full: String,
+    pub holder_url: String,
+    pub controller: String,
+    pub controller_full: String,
+    pub controller_url: String,
+}
+
+pub struct PackageVersion {
+    pub version: i64,
+    pub diff_url: String,
+    pub package_id: String,
+    pub package_id_full: String,
+    pub package_url: String,
+    pub time_ago: String,
+    pub current: bool,
+}
+
+pub struct PackageModule {
+    pub name: String,
+    pub types: Vec<PackageType>,
+}
+
+pub struct PackageType {
+    pub name: String,
+    pub origin: String,
+    pub origin_url: String,
+    pub introduced_here: bool,
+}
+
+pub struct PackageDependency {
+    pub dependency_id: String,
+    pub dependency_id_full: String,
+    pub linked_id: String,
+    pub linked_id_full: String,
+    pub linked_url: String,
+    pub linked_version: i64,
 }
 
 pub struct CapVersion {
     pub version: i64,
+    pub upgrade_class: String,
+    pub upgrade_class_badge: String,
     pub package_id: String,
     pub package_id_full: String,
     pub package_url: String,
     pub tx_digest: String,
     pub tx_digest_full: String,
     pub tx_url: String,
+    pub tx_page_url: String,
     pub seq_checkpoint: i64,
     pub seq_checkpoint_url: String,
     pub time_ago: String,
@@ -85,6 +386,7 @@ pub struct CapTransfer {
     pub tx_digest: String,
     pub tx_digest_full: String,
     pub tx_url: String,
+    pub tx_page_url: String,
     pub seq_checkpoint: i64,
     pub seq_checkpoint_url: String,
     pub time_ago: String,
@@ -95,3 +397,35 @@ pub struct CapTransfer {
     pub to_full: String,
     pub to_url: String,
 }
+
+pub struct CapPolicyChange {
+    pub old_policy: String,
+    pub new_policy: String,
+    pub tx_digest: String,
+    pub tx_digest_full: String,
+    pub tx_url: String,
+    pub tx_page_url: String,
+    pub seq_checkpoint: i64,
+    pub seq_checkpoint_url: String,
+    pub time_ago: String,
+}
+
+/// Atom feed, timestamps are RFC 3339 and urls absolute.
+#[derive(Template)]
+#[template(path = "feed.xml")]
+pub struct Feed {
+    pub id: String,
+    pub title: String,
+    pub self_url: String,
+    pub page_url: String,
+    pub updated: String,
+    pub entries: Vec<FeedEntry>,
+}
+
+pub struct FeedEntry {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub updated: String,
+    pub summary: String,
+}
@@ -0,0 +1,297 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use diesel::{ExpressionMethods, JoinOnDsl, QueryDsl, SelectableHelper};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use futures_util::future;
+use log::{info, warn};
+use phantom_cap_db::Network;
+use phantom_cap_db::models::{WebhookDelivery, WebhookDeliveryStatusEnum, WebhookSubscription};
+use phantom_cap_db::schema::webhook_deliveries::dsl as webhook_deliveries_dsl;
+use phantom_cap_db::schema::webhook_subscriptions::dsl as webhook_subscriptions_dsl;
+use phantom_cap_db::webhooks;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::redirect;
+use url::Url;
+
+use crate::DbPool;
+use crate::signature;
+
+/// Deliveries claimed at once.
+const BATCH_SIZE: i64 = 32;
+/// Time a receiver has to answer.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Claimed deliveries are retried after this long, should the dispatcher stop mid-way.
+const CLAIM_LEASE: Duration = Duration::from_secs(60);
+/// Deliveries are given up after this many attempts, about an hour after the first.
+const MAX_ATTEMPTS: i32 = 8;
+const FIRST_RETRY: Duration = Duration::from_secs(30);
+const MAX_RETRY: Duration = Duration::from_secs(60 * 60);
+
+/// Longer errors are cut in the delivery log.
+const MAX_ERROR_LEN: usize = 512;
+
+/// Client delivering webhooks. Redirects aren't followed and hosts only resolve to public
+/// addresses, so that receivers can't point deliveries at the services next to the dispatcher.
+pub fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .redirect(redirect::Policy::none())
+        .dns_resolver(Arc::new(PublicResolver))
+        .build()
+        .expect("Failed to build the webhook client")
+}
+
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addrs = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| webhooks::is_public_ip(addr.ip()))
+                .collect::<Vec<_>>();
+            if addrs.is_empty() {
+                return Err(format!("{} doesn't resolve to a public address", host).into());
+            }
+
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Result of one attempt at a delivery.
+struct Attempt {
+    status_code: Option<i32>,
+    error: Option<String>,
+}
+
+impl Attempt {
+    fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Delivers the deliveries currently due, returns how many were attempted.
+pub async fn deliver_due(
+    network: Network,
+    pool: &DbPool,
+    client: &reqwest::Client,
+) -> anyhow::Result<usize> {
+    let mut conn = pool.get().await.context("Failed to get db connection")?;
+    let due = claim_due(&mut conn).await?;
+    // connections aren't held while waiting on receivers.
+    drop(conn);
+
+    let attempts = future::join_all(
+        due.iter()
+            .map(|(delivery, subscription)| post(network, client, delivery, subscription)),
+    )
+    .await;
+
+    let mut conn = pool.get().await.context("Failed to get db connection")?;
+    for ((delivery, subscription), attempt) in due.iter().zip(&attempts) {
+        match &attempt.error {
+            None => info!("Delivered {} to {}", delivery.id, subscription.url),
+            Some(e) => warn!(
+                "Delivery {} to {} failed (attempt {}): {}",
+                delivery.id,
+                subscription.url,
+                delivery.attempts + 1,
+                e
+            ),
+        }
+        record(&mut conn, delivery, attempt).await?;
+    }
+
+    Ok(due.len())
+}
+
+/// Pending deliveries due now, pushed back by the claim lease so that concurrent
+/// dispatchers skip them.
+async fn claim_due(
+    conn: &mut AsyncPgConnection,
+) -> anyhow::Result<Vec<(WebhookDelivery, WebhookSubscription)>> {
+    let claimed = conn
+        .transaction::<_, diesel::result::Error, _>(|tx_conn| {
+            async move {
+                let ids = webhook_deliveries_dsl::webhook_deliveries
+                    .select(webhook_deliveries_dsl::id)
+                    .filter(webhook_deliveries_dsl::status.eq(WebhookDeliveryStatusEnum::Pending))
+                    .filter(webhook_deliveries_dsl::next_attempt_at.le(Utc::now()))
+                    .order(webhook_deliveries_dsl::next_attempt_at.asc())
+                    .limit(BATCH_SIZE)
+                    .for_update()
+                    .skip_locked()
+                    .load::<i64>(tx_conn)
+                    .await?;
+
+                if !ids.is_empty() {
+                    diesel::update(
+                        webhook_deliveries_dsl::webhook_deliveries
+                            .filter(webhook_deliveries_dsl::id.eq_any(&ids)),
+                    )
+                    .set(webhook_deliveries_dsl::next_attempt_at.eq(after(CLAIM_LEASE)))
+                    .execute(tx_conn)
+                    .await?;
+                }
+
+                Ok(ids)
+            }
+            .scope_boxed()
+        })
+        .await
+        .context("Failed to claim due deliveries")?;
+
+    if claimed.is_empty() {
+        return Ok(vec![]);
+    }
+
+    webhook_deliveries_dsl::webhook_deliveries
+        .inner_join(
+            webhook_subscriptions_dsl::webhook_subscriptions
+                .on(webhook_subscriptions_dsl::id.eq(webhook_deliveries_dsl::subscription_id)),
+        )
+        .filter(webhook_deliveries_dsl::id.eq_any(&claimed))
+        .order(webhook_deliveries_dsl::id.asc())
+        .select((
+            WebhookDelivery::as_select(),
+            WebhookSubscription::as_select(),
+        ))
+        .load::<(WebhookDelivery, WebhookSubscription)>(conn)
+        .await
+        .context("Failed to load claimed deliveries")
+}
+
+async fn post(
+    network: Network,
+    client: &reqwest::Client,
+    delivery: &WebhookDelivery,
+    subscription: &WebhookSubscription,
+) -> Attempt {
+    // addresses in the url aren't resolved, webhooks registered before they were
+    // checked are refused here.
+    let checked = Url::parse(&subscription.url)
+        .map_err(|e| e.to_string())
+        .and_then(|url| webhooks::check_url(&url));
+    if let Err(e) = checked {
+        return Attempt {
+            status_code: None,
+            error: Some(e),
+        };
+    }
+
+    let timestamp = Utc::now().timestamp();
+
+    let response = client
+        .post(&subscription.url)
+        .timeout(REQUEST_TIMEOUT)
+        .header("Content-Type", "application/json")
+        .header("User-Agent", "PhantomCap-Webhooks")
+        .header("X-PhantomCap-Delivery", delivery.id.to_string())
+        .header("X-PhantomCap-Event", &delivery.event_kind)
+        .header("X-PhantomCap-Network", network.as_str())
+        .header("X-PhantomCap-Timestamp", timestamp.to_string())
+        .header(
+            "X-PhantomCap-Signature",
+            signature::sign(&subscription.secret, timestamp, &delivery.payload),
+        )
+        .body(delivery.payload.clone())
+        .send()
+        .await;
+
+    match response {
+        Ok(response) if response.status().is_success() => Attempt {
+            status_code: Some(response.status().as_u16() as i32),
+            error: None,
+        },
+        Ok(response) => Attempt {
+            status_code: Some(response.status().as_u16() as i32),
+            error: Some(format!("Receiver answered {}", response.status().as_u16())),
+        },
+        Err(e) => Attempt {
+            status_code: None,
+            // the chain names the cause, e.g. a refused connection.
+            error: Some(
+                format!("{:#}", anyhow::Error::from(e))
+                    .chars()
+                    .take(MAX_ERROR_LEN)
+                    .collect(),
+            ),
+        },
+    }
+}
+
+/// Logs an attempt, and schedules the next one when it failed.
+async fn record(
+    conn: &mut AsyncPgConnection,
+    delivery: &WebhookDelivery,
+    attempt: &Attempt,
+) -> anyhow::Result<()> {
+    let attempts = delivery.attempts + 1;
+    let (status, next_attempt_at) = if attempt.succeeded() {
+        (
+            WebhookDeliveryStatusEnum::Delivered,
+            delivery.next_attempt_at,
+        )
+    } else if attempts >= MAX_ATTEMPTS {
+        (WebhookDeliveryStatusEnum::Failed, delivery.next_attempt_at)
+    } else {
+        (
+            WebhookDeliveryStatusEnum::Pending,
+            after(retry_delay(attempts)),
+        )
+    };
+
+    diesel::update(webhook_deliveries_dsl::webhook_deliveries.find(delivery.id))
+        .set((
+            webhook_deliveries_dsl::status.eq(status),
+            webhook_deliveries_dsl::attempts.eq(attempts),
+            webhook_deliveries_dsl::next_attempt_at.eq(next_attempt_at),
+            webhook_deliveries_dsl::last_attempt_at.eq(Some(Utc::now())),
+            webhook_deliveries_dsl::last_status_code.eq(attempt.status_code),
+            webhook_deliveries_dsl::last_error.eq(&attempt.error),
+        ))
+        .execute(conn)
+        .await
+        .with_context(|| format!("Failed to record delivery {}", delivery.id))?;
+
+    Ok(())
+}
+
+/// Exponential backoff: 30s after the first failure, doubling up to an hour.
+fn retry_delay(attempts: i32) -> Duration {
+    let doublings = (attempts - 1).clamp(0, 16) as u32;
+    FIRST_RETRY
+        .saturating_mul(2u32.pow(doublings))
+        .min(MAX_RETRY)
+}
+
+fn after(delay: Duration) -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::from_std(delay).expect("Delays fit chrono durations")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_delay_doubles_up_to_an_hour() {
+        let delays = (1..=9)
+            .map(|attempts| retry_delay(attempts).as_secs())
+            .collect::<Vec<_>>();
+        assert_eq!(delays, [30, 60, 120, 240, 480, 960, 1920, 3600, 3600]);
+
+        assert_eq!(retry_delay(0), FIRST_RETRY);
+        assert_eq!(retry_delay(i32::MAX), MAX_RETRY);
+    }
+
+    #[test]
+    fn deliveries_are_given_up_about_an_hour_after_the_first_attempt() {
+        let retried = (1..MAX_ATTEMPTS).map(retry_delay).sum::<Duration>();
+        assert_eq!(retried.as_secs(), 3810);
+    }
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use diesel_async::{
+    AsyncPgConnection,
+    pooled_connection::{AsyncDieselConnectionManager, bb8::Pool},
+};
+use futures_util::future;
+use log::{error, info};
+use phantom_cap_db::Network;
+use url::Url;
+
+mod delivery;
+mod signature;
+
+type DbPool = Pool<AsyncPgConnection>;
+
+/// Delay before looking for due deliveries again once there are none.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    env_logger::init();
+
+    let database_url = std::env::var("DATABASE_URL")
+        .expect("DATABASE_URL must be set")
+        .parse::<Url>()
+        .expect("Invalid database URL");
+
+    let networks = Network::parse_list(
+        &std::env::var("NETWORKS").unwrap_or_else(|_| Network::Mainnet.to_string()),
+    )
+    .expect("Invalid NETWORKS");
+
+    let client = delivery::client();
+
+    let mut dispatchers = Vec::with_capacity(networks.len());
+    for network in networks {
+        let manager = AsyncDieselConnectionManager::<AsyncPgConnection>::new(
+            network.database_url(&database_url),
+        );
+        let pool = Pool::builder().build(manager).await?;
+        dispatchers.push(tokio::spawn(dispatch(network, pool, client.clone())));
+    }
+
+    future::try_join_all(dispatchers).await?;
+    Ok(())
+}
+
+/// Delivers the webhooks of a network for as long as the dispatcher runs.
+async fn dispatch(network: Network, pool: DbPool, client: reqwest::Client) {
+    info!("Dispatching {} webhooks", network);
+
+    loop {
+        match delivery::deliver_due(network, &pool, &client).await {
+            // more may be due right away.
+            Ok(count) if count > 0 => continue,
+            Ok(_) => {}
+            Err(e) => error!("Failed to dispatch {} webhooks: {:#}", network, e),
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
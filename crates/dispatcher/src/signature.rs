@@ -0,0 +1,36 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// `X-PhantomCap-Signature` of a delivery: the hex HMAC-SHA256 of `{timestamp}.{body}`
+/// keyed by the subscription's secret. The timestamp lets receivers reject replays.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signs_timestamp_and_body() {
+        // HMAC-SHA256 of `1700000000.{"kind":"version"}` keyed by `Jefe`.
+        assert_eq!(
+            sign("Jefe", 1_700_000_000, r#"{"kind":"version"}"#),
+            "sha256=bf2f8422da56d1bda0d319735eb9c7d0d56c4b381d1c13106cb0045364e8f9f7"
+        );
+    }
+
+    #[test]
+    fn signature_depends_on_every_input() {
+        let signature = sign("Jefe", 1_700_000_000, "{}");
+        assert_ne!(signature, sign("Jeff", 1_700_000_000, "{}"));
+        assert_ne!(signature, sign("Jefe", 1_700_000_001, "{}"));
+        assert_ne!(signature, sign("Jefe", 1_700_000_000, "{ }"));
+    }
+}
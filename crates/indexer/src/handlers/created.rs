@@ -1,5 +1,6 @@
 use anyhow::Result;
 use chrono::DateTime;
+use diesel::SelectableHelper;
 use diesel::result::Error;
 use diesel_async::scoped_futures::ScopedFutureExt;
 use diesel_async::{AsyncConnection, RunQueryDsl};
@@ -10,7 +11,6 @@ use sui_indexer_alt_framework::{
     pipeline::sequential::Handler,
     postgres::{Connection, Db},
 };
-use sui_types::object::Owner;
 use sui_types::storage::ObjectKey;
 use sui_types::{
     effects::TransactionEffectsAPI,
@@ -20,17 +20,21 @@ use sui_types::{
     transaction::{Command, TransactionDataAPI, TransactionKind},
 };
 
-use crate::models::FullUpgradeCap;
-use crate::schema::upgrade_cap_transfers::dsl::{
+use crate::models::{FullUpgradeCap, UpgradeCapTransfer, UpgradeCapVersion};
+use crate::owner::owner_to_string;
+use crate::{models::UpgradeCap, models::UpgradeCompatibilityPolicyEnum};
+use phantom_cap_db::events::{self, CapEvent};
+use phantom_cap_db::schema::upgrade_cap_transfers::dsl::{
     object_id as upgrade_cap_transfers_object_id, tx_digest as upgrade_cap_transfers_tx_digest,
     upgrade_cap_transfers,
 };
-use crate::schema::upgrade_cap_versions::dsl::{
+use phantom_cap_db::schema::upgrade_cap_versions::dsl::{
     object_id as upgrade_cap_versions_object_id, upgrade_cap_versions,
     version as upgrade_cap_versions_version,
 };
-use crate::schema::upgrade_caps::dsl::*;
-use crate::{models::UpgradeCap, models::UpgradeCompatibilityPolicyEnum};
+use phantom_cap_db::schema::upgrade_caps::dsl::*;
+use phantom_cap_db::state;
+use phantom_cap_db::webhooks;
 
 pub struct UpgradeCapHandler;
 
@@ -73,18 +77,7 @@ fn get_created_upgrade_caps(
             .get(&ObjectKey(effects.0.0, effects.0.1))
             .unwrap();
 
-        let owner = match obj.owner() {
-            Owner::AddressOwner(address) => address.to_string(),
-            Owner::ObjectOwner(address) => address.to_string(),
-            Owner::Shared {
-                initial_shared_version: _,
-            } => "shared".to_string(),
-            Owner::Immutable => "immutable".to_string(),
-            Owner::ConsensusAddressOwner {
-                start_version: _,
-                owner: address,
-            } => address.to_string(),
-        };
+        let owner = owner_to_string(obj.owner());
 
         match &obj.data {
             Data::Move(move_object) => {
@@ -187,6 +180,11 @@ impl Handler for UpgradeCapHandler {
             .map(|cap| cap.creation_version())
             .collect::<Vec<_>>();
 
+        let creation_states = batch
+            .iter()
+            .map(|cap| cap.creation_state())
+            .collect::<Vec<_>>();
+
         let result = conn
             .transaction::<usize, Error, _>(|tx_conn| {
                 async move {
@@ -197,23 +195,38 @@ impl Handler for UpgradeCapHandler {
                         .execute(tx_conn)
                         .await?;
 
-                    diesel::insert_into(upgrade_cap_transfers)
+                    let transfers = diesel::insert_into(upgrade_cap_transfers)
                         .values(creation_transfers)
                         .on_conflict((
                             upgrade_cap_transfers_object_id,
                             upgrade_cap_transfers_tx_digest,
                         ))
                         .do_nothing()
-                        .execute(tx_conn)
+                        .returning(UpgradeCapTransfer::as_returning())
+                        .get_results::<UpgradeCapTransfer>(tx_conn)
                         .await?;
 
-                    diesel::insert_into(upgrade_cap_versions)
+                    let versions = diesel::insert_into(upgrade_cap_versions)
                         .values(creation_versions)
                         .on_conflict((upgrade_cap_versions_object_id, upgrade_cap_versions_version))
                         .do_nothing()
-                        .execute(tx_conn)
+                        .returning(UpgradeCapVersion::as_returning())
+                        .get_results::<UpgradeCapVersion>(tx_conn)
                         .await?;
 
+                    for creation_state in creation_states.iter() {
+                        state::created(creation_state).execute(tx_conn).await?;
+                    }
+
+                    // the publish comes before the cap's first transfer to its owner.
+                    let events = versions
+                        .into_iter()
+                        .map(CapEvent::Version)
+                        .chain(transfers.into_iter().map(CapEvent::Transfer))
+                        .collect::<Vec<_>>();
+                    events::notify(tx_conn, &events).await?;
+                    webhooks::enqueue(tx_conn, &events).await?;
+
                     Ok(inserted)
                 }
                 .scope_boxed()
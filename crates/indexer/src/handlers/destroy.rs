@@ -0,0 +1,212 @@
+use anyhow::Result;
+use chrono::DateTime;
+use diesel::result::Error;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use log::info;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use sui_types::base_types::ObjectID;
+use sui_types::effects::TransactionEffectsAPI;
+use sui_types::storage::ObjectKey;
+use sui_types::transaction::TransactionDataAPI;
+
+use sui_indexer_alt_framework::pipeline::Processor;
+use sui_indexer_alt_framework::{
+    pipeline::sequential::Handler,
+    postgres::{Connection, Db},
+};
+use sui_types::full_checkpoint_content::Checkpoint;
+
+use crate::models::{UpgradeCapRemovals, WrappedUpgradeCap};
+use phantom_cap_db::schema::wrapped_upgrade_caps::dsl::{object_id, wrapped_upgrade_caps};
+use phantom_cap_db::state;
+
+pub struct UpgradeCapHandler;
+
+#[async_trait::async_trait]
+impl Processor for UpgradeCapHandler {
+    const NAME: &'static str = "destroy_handler";
+
+    type Value = UpgradeCapRemovals;
+
+    async fn process(&self, checkpoint: &Arc<Checkpoint>) -> Result<Vec<Self::Value>> {
+        let checkpoint_seq = checkpoint.summary.sequence_number as i64;
+        let checkpoint_timestamp =
+            DateTime::from_timestamp_millis(checkpoint.summary.timestamp_ms as i64).unwrap();
+
+        Ok(checkpoint
+            .transactions
+            .iter()
+            .filter(|tx| tx.effects.status().is_ok())
+            .filter_map(|tx| {
+                let input_versions = tx.effects.modified_at_versions();
+                let is_input_cap = |id: &ObjectID| {
+                    input_versions
+                        .iter()
+                        .find(|(input_id, _)| input_id.eq(id))
+                        .and_then(|(id, input_version)| {
+                            checkpoint.object_set.get(&ObjectKey(*id, *input_version))
+                        })
+                        .and_then(|obj| obj.type_())
+                        .is_some_and(|obj_type| obj_type.is_upgrade_cap())
+                };
+
+                // deleted and wrapped objects are upgrade caps if their input version is.
+                let deleted = tx
+                    .effects
+                    .deleted()
+                    .into_iter()
+                    .filter(|deleted_ref| is_input_cap(&deleted_ref.0))
+                    .map(|deleted_ref| deleted_ref.0.to_hex_literal())
+                    .collect::<Vec<_>>();
+
+                let wrapped = tx
+                    .effects
+                    .wrapped()
+                    .into_iter()
+                    .filter(|wrapped_ref| is_input_cap(&wrapped_ref.0))
+                    .map(|wrapped_ref| wrapped_ref.0.to_hex_literal())
+                    .collect::<Vec<_>>();
+
+                let unwrapped = tx
+                    .effects
+                    .unwrapped()
+                    .into_iter()
+                    .filter(|(output_ref, _)| {
+                        checkpoint
+                            .object_set
+                            .get(&ObjectKey(output_ref.0, output_ref.1))
+                            .and_then(|obj| obj.type_())
+                            .is_some_and(|obj_type| obj_type.is_upgrade_cap())
+                    })
+                    .map(|(output_ref, _)| output_ref.0.to_hex_literal())
+                    .collect::<Vec<_>>();
+
+                // wrapped objects have no input version to check,
+                // they are matched against the known caps on commit.
+                let unwrapped_then_deleted = tx
+                    .effects
+                    .unwrapped_then_deleted()
+                    .into_iter()
+                    .map(|deleted_ref| deleted_ref.0.to_hex_literal())
+                    .collect::<Vec<_>>();
+
+                if deleted.is_empty()
+                    && wrapped.is_empty()
+                    && unwrapped.is_empty()
+                    && unwrapped_then_deleted.is_empty()
+                {
+                    return None;
+                }
+
+                Some(UpgradeCapRemovals {
+                    seq_checkpoint: checkpoint_seq,
+                    tx_digest: tx.transaction.digest().to_string(),
+                    timestamp: checkpoint_timestamp,
+                    wrapped,
+                    unwrapped,
+                    deleted,
+                    unwrapped_then_deleted,
+                })
+            })
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl Handler for UpgradeCapHandler {
+    type Store = Db;
+    type Batch = Vec<Self::Value>;
+
+    fn batch(&self, batch: &mut Self::Batch, values: std::vec::IntoIter<Self::Value>) {
+        batch.extend(values);
+    }
+
+    async fn commit<'a>(&self, batch: &Self::Batch, conn: &mut Connection<'a>) -> Result<usize> {
+        let result = conn
+            .transaction::<usize, Error, _>(|tx_conn| {
+                async move {
+                    let mut destroyed = 0;
+
+                    // batch is ordered by transaction, a cap is wrapped before it's deleted.
+                    for removals in batch.iter() {
+                        if !removals.wrapped.is_empty() {
+                            let wrapped = removals
+                                .wrapped
+                                .iter()
+                                .map(|cap_id| WrappedUpgradeCap {
+                                    object_id: cap_id.clone(),
+                                    seq_checkpoint: removals.seq_checkpoint,
+                                })
+                                .collect::<Vec<_>>();
+
+                            diesel::insert_into(wrapped_upgrade_caps)
+                                .values(wrapped)
+                                .on_conflict(object_id)
+                                .do_nothing()
+                                .execute(tx_conn)
+                                .await?;
+                        }
+
+                        let mut removed = removals.unwrapped.clone();
+                        removed.extend(removals.deleted.iter().cloned());
+
+                        if !removed.is_empty() {
+                            diesel::delete(wrapped_upgrade_caps.filter(object_id.eq_any(removed)))
+                                .execute(tx_conn)
+                                .await?;
+                        }
+
+                        // the other objects may be anything: only caps seen wrapped here,
+                        // or indexed by another pipeline, e.g. wrapped before this one started.
+                        let mut destroyed_caps = removals.deleted.clone();
+                        if !removals.unwrapped_then_deleted.is_empty() {
+                            let wrapped = diesel::delete(
+                                wrapped_upgrade_caps
+                                    .filter(object_id.eq_any(&removals.unwrapped_then_deleted)),
+                            )
+                            .returning(object_id)
+                            .get_results::<String>(tx_conn)
+                            .await?;
+                            let indexed = state::indexed(&removals.unwrapped_then_deleted)
+                                .load::<String>(tx_conn)
+                                .await?;
+
+                            destroyed_caps.extend(
+                                wrapped.into_iter().chain(indexed).collect::<BTreeSet<_>>(),
+                            );
+                        }
+
+                        for cap_id in destroyed_caps.iter() {
+                            state::destroyed(
+                                cap_id,
+                                removals.seq_checkpoint,
+                                &removals.tx_digest,
+                                removals.timestamp,
+                            )
+                            .execute(tx_conn)
+                            .await?;
+                        }
+
+                        if !destroyed_caps.is_empty() {
+                            info!(
+                                "[DESTROYED] Tx: {} Caps: {}",
+                                removals.tx_digest,
+                                destroyed_caps.len()
+                            );
+                        }
+
+                        destroyed += destroyed_caps.len();
+                    }
+
+                    Ok(destroyed)
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,138 @@
+use anyhow::Result;
+use chrono::DateTime;
+use diesel_async::RunQueryDsl;
+use log::info;
+use std::sync::Arc;
+use sui_types::base_types::ObjectID;
+use sui_types::effects::TransactionEffectsAPI;
+use sui_types::object::{Object, Owner};
+use sui_types::storage::ObjectKey;
+use sui_types::transaction::TransactionDataAPI;
+
+use sui_indexer_alt_framework::pipeline::Processor;
+use sui_indexer_alt_framework::{
+    pipeline::sequential::Handler,
+    postgres::{Connection, Db},
+};
+use sui_types::full_checkpoint_content::Checkpoint;
+
+use crate::models::UpgradeCapHolder;
+use crate::owner::{find_wrapper, object_type, resolve_holder_chain};
+use phantom_cap_db::schema::upgrade_cap_holders::dsl::*;
+
+pub struct UpgradeCapHandler;
+
+#[async_trait::async_trait]
+impl Processor for UpgradeCapHandler {
+    const NAME: &'static str = "holder_handler";
+
+    type Value = UpgradeCapHolder;
+
+    async fn process(&self, checkpoint: &Arc<Checkpoint>) -> Result<Vec<Self::Value>> {
+        let checkpoint_seq = checkpoint.summary.sequence_number as i64;
+        let checkpoint_timestamp =
+            DateTime::from_timestamp_millis(checkpoint.summary.timestamp_ms as i64).unwrap();
+
+        Ok(checkpoint
+            .transactions
+            .iter()
+            .filter(|tx| tx.effects.status().is_ok())
+            .flat_map(|tx| {
+                let input_versions = tx.effects.modified_at_versions();
+                let input_object = |id: &ObjectID| -> Option<&Object> {
+                    let (_, input_version) = input_versions
+                        .iter()
+                        .find(|(input_id, _)| input_id.eq(id))?;
+                    checkpoint.object_set.get(&ObjectKey(*id, *input_version))
+                };
+
+                let written = tx
+                    .effects
+                    .all_changed_objects()
+                    .into_iter()
+                    .filter_map(|(obj_ref, _, _)| {
+                        checkpoint.object_set.get(&ObjectKey(obj_ref.0, obj_ref.1))
+                    })
+                    .collect::<Vec<_>>();
+
+                // caps newly owned by an object, e.g. added as dynamic object field.
+                let object_owned = written
+                    .iter()
+                    .copied()
+                    .filter(|obj| {
+                        matches!(obj.owner(), Owner::ObjectOwner(_))
+                            && obj.type_().is_some_and(|t| t.is_upgrade_cap())
+                            && !input_object(&obj.id())
+                                .is_some_and(|input| input.owner() == obj.owner())
+                    })
+                    .filter_map(|cap| {
+                        let Owner::ObjectOwner(parent) = cap.owner() else {
+                            return None;
+                        };
+
+                        let parent_id = ObjectID::from(*parent);
+                        let parent_obj =
+                            written.iter().copied().find(|obj| obj.id() == parent_id)?;
+
+                        Some((cap.id(), parent_obj))
+                    });
+
+                // caps wrapped into another object or a dynamic field.
+                let wrapped = tx
+                    .effects
+                    .wrapped()
+                    .into_iter()
+                    .filter_map(|wrapped_ref| input_object(&wrapped_ref.0))
+                    .filter(|cap| cap.type_().is_some_and(|t| t.is_upgrade_cap()))
+                    .filter_map(|cap| Some((cap.id(), find_wrapper(&written, cap)?)));
+
+                object_owned
+                    .chain(wrapped)
+                    .map(|(cap_id, parent_obj)| {
+                        let chain = resolve_holder_chain(&written, parent_obj);
+                        let holder = chain.holder();
+
+                        info!(
+                            "[HOLDER] Tx: {} Id: {} Holder: {}",
+                            tx.transaction.digest(),
+                            cap_id.to_hex_literal(),
+                            holder.id().to_hex_literal()
+                        );
+
+                        UpgradeCapHolder {
+                            object_id: cap_id.to_hex_literal(),
+                            holder_id: holder.id().to_hex_literal(),
+                            holder_type: object_type(holder),
+                            chain: chain.ids(),
+                            root_owner: chain.root_owner(),
+                            seq_checkpoint: checkpoint_seq,
+                            tx_digest: tx.transaction.digest().to_string(),
+                            timestamp: checkpoint_timestamp,
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl Handler for UpgradeCapHandler {
+    type Store = Db;
+    type Batch = Vec<Self::Value>;
+
+    fn batch(&self, batch: &mut Self::Batch, values: std::vec::IntoIter<Self::Value>) {
+        batch.extend(values);
+    }
+
+    async fn commit<'a>(&self, batch: &Self::Batch, conn: &mut Connection<'a>) -> Result<usize> {
+        let inserted = diesel::insert_into(upgrade_cap_holders)
+            .values(batch)
+            .on_conflict((object_id, tx_digest))
+            .do_nothing()
+            .execute(conn)
+            .await?;
+
+        Ok(inserted)
+    }
+}
@@ -1,3 +1,8 @@
 pub mod created;
+pub mod destroy;
+pub mod holder;
+pub mod package;
+pub mod policy;
+pub mod signer;
 pub mod transfer;
 pub mod upgrade;
@@ -0,0 +1,334 @@
+use anyhow::Result;
+use chrono::DateTime;
+use diesel::result::Error;
+use diesel::{ExpressionMethods, QueryDsl};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use log::{info, warn};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use sui_types::effects::TransactionEffectsAPI;
+use sui_types::storage::ObjectKey;
+use sui_types::transaction::{Command, TransactionDataAPI, TransactionKind};
+
+use sui_indexer_alt_framework::pipeline::Processor;
+use sui_indexer_alt_framework::{
+    pipeline::sequential::Handler,
+    postgres::{Connection, Db},
+};
+use sui_types::full_checkpoint_content::Checkpoint;
+
+use crate::models::{
+    FullPackage, Package, PackageDependency, PackageLinkage, PackageModule, PackageTypeOrigin,
+    UpgradeClassEnum,
+};
+use phantom_cap_db::query;
+use phantom_cap_db::schema::package_dependencies::dsl as package_dependencies_dsl;
+use phantom_cap_db::schema::package_linkage::dsl as package_linkage_dsl;
+use phantom_cap_db::schema::package_modules::dsl as package_modules_dsl;
+use phantom_cap_db::schema::package_type_origins::dsl as package_type_origins_dsl;
+use phantom_cap_db::schema::packages::dsl as packages_dsl;
+
+/// Rows per insert statement, packages expand into many rows and
+/// postgres limits the number of bind parameters of a statement.
+const INSERT_CHUNK_SIZE: usize = 1000;
+
+pub struct UpgradeCapHandler;
+
+#[async_trait::async_trait]
+impl Processor for UpgradeCapHandler {
+    const NAME: &'static str = "package_handler";
+
+    type Value = FullPackage;
+
+    async fn process(&self, checkpoint: &Arc<Checkpoint>) -> Result<Vec<Self::Value>> {
+        let checkpoint_seq = checkpoint.summary.sequence_number as i64;
+        let checkpoint_timestamp =
+            DateTime::from_timestamp_millis(checkpoint.summary.timestamp_ms as i64).unwrap();
+
+        Ok(checkpoint
+            .transactions
+            .iter()
+            .filter(|tx| tx.effects.status().is_ok())
+            .filter(|tx| match tx.transaction.kind() {
+                TransactionKind::ProgrammableTransaction(pt)
+                | TransactionKind::ProgrammableSystemTransaction(pt) => {
+                    pt.commands.iter().any(|cmd| {
+                        matches!(cmd, Command::Publish(_, _) | Command::Upgrade(_, _, _, _))
+                    })
+                }
+                _ => false,
+            })
+            .flat_map(|tx| {
+                // publish and upgrade both create a new package object.
+                tx.effects
+                    .created()
+                    .into_iter()
+                    .filter_map(|(obj_ref, _)| {
+                        checkpoint
+                            .object_set
+                            .get(&ObjectKey(obj_ref.0, obj_ref.1))?
+                            .data
+                            .try_as_package()
+                    })
+                    .map(|move_package| {
+                        let package_id = move_package.id().to_hex_literal();
+
+                        info!(
+                            "[PACKAGE] Tx: {} Id: {} Modules: {}",
+                            tx.transaction.digest(),
+                            package_id,
+                            move_package.serialized_module_map().len()
+                        );
+
+                        let modules = move_package
+                            .serialized_module_map()
+                            .iter()
+                            .map(|(name, bytecode)| PackageModule {
+                                package_id: package_id.clone(),
+                                module_name: name.clone(),
+                                bytecode: bytecode.clone(),
+                            })
+                            .collect();
+
+                        let type_origins = move_package
+                            .type_origin_table()
+                            .iter()
+                            .map(|origin| PackageTypeOrigin {
+                                package_id: package_id.clone(),
+                                module_name: origin.module_name.clone(),
+                                datatype_name: origin.datatype_name.clone(),
+                                origin_package_id: origin.package.to_hex_literal(),
+                            })
+                            .collect();
+
+                        let linkage = move_package
+                            .linkage_table()
+                            .iter()
+                            .map(|(dependency, upgrade_info)| PackageLinkage {
+                                package_id: package_id.clone(),
+                                dependency_id: dependency.to_hex_literal(),
+                                upgraded_id: upgrade_info.upgraded_id.to_hex_literal(),
+                                upgraded_version: upgrade_info.upgraded_version.value() as i64,
+                            })
+                            .collect::<Vec<_>>();
+
+                        let original_id = move_package.original_package_id().to_hex_literal();
+                        let dependencies = direct_dependencies(
+                            &package_id,
+                            &original_id,
+                            move_package.serialized_module_map(),
+                            &linkage,
+                        );
+
+                        FullPackage {
+                            package: Package {
+                                package_id: package_id.clone(),
+                                original_id,
+                                version: move_package.version().value() as i64,
+                                seq_checkpoint: checkpoint_seq,
+                                tx_digest: tx.transaction.digest().to_string(),
+                                timestamp: checkpoint_timestamp,
+                            },
+                            modules,
+                            type_origins,
+                            linkage,
+                            dependencies,
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl Handler for UpgradeCapHandler {
+    type Store = Db;
+    type Batch = Vec<Self::Value>;
+
+    fn batch(&self, batch: &mut Self::Batch, values: std::vec::IntoIter<Self::Value>) {
+        batch.extend(values);
+    }
+
+    async fn commit<'a>(&self, batch: &Self::Batch, conn: &mut Connection<'a>) -> Result<usize> {
+        let packages = batch.iter().map(|p| p.package.clone()).collect::<Vec<_>>();
+        let modules = batch
+            .iter()
+            .flat_map(|p| p.modules.iter().cloned())
+            .collect::<Vec<_>>();
+        let type_origins = batch
+            .iter()
+            .flat_map(|p| p.type_origins.iter().cloned())
+            .collect::<Vec<_>>();
+        let linkage = batch
+            .iter()
+            .flat_map(|p| p.linkage.iter().cloned())
+            .collect::<Vec<_>>();
+        let dependencies = batch
+            .iter()
+            .flat_map(|p| p.dependencies.iter().cloned())
+            .collect::<Vec<_>>();
+
+        let result = conn
+            .transaction::<usize, Error, _>(|tx_conn| {
+                async move {
+                    let inserted = diesel::insert_into(packages_dsl::packages)
+                        .values(&packages)
+                        .on_conflict(packages_dsl::package_id)
+                        .do_nothing()
+                        .execute(tx_conn)
+                        .await?;
+
+                    for chunk in modules.chunks(INSERT_CHUNK_SIZE) {
+                        diesel::insert_into(package_modules_dsl::package_modules)
+                            .values(chunk)
+                            .on_conflict((
+                                package_modules_dsl::package_id,
+                                package_modules_dsl::module_name,
+                            ))
+                            .do_nothing()
+                            .execute(tx_conn)
+                            .await?;
+                    }
+
+                    for chunk in type_origins.chunks(INSERT_CHUNK_SIZE) {
+                        diesel::insert_into(package_type_origins_dsl::package_type_origins)
+                            .values(chunk)
+                            .on_conflict((
+                                package_type_origins_dsl::package_id,
+                                package_type_origins_dsl::module_name,
+                                package_type_origins_dsl::datatype_name,
+                            ))
+                            .do_nothing()
+                            .execute(tx_conn)
+                            .await?;
+                    }
+
+                    for chunk in linkage.chunks(INSERT_CHUNK_SIZE) {
+                        diesel::insert_into(package_linkage_dsl::package_linkage)
+                            .values(chunk)
+                            .on_conflict((
+                                package_linkage_dsl::package_id,
+                                package_linkage_dsl::dependency_id,
+                            ))
+                            .do_nothing()
+                            .execute(tx_conn)
+                            .await?;
+                    }
+
+                    for chunk in dependencies.chunks(INSERT_CHUNK_SIZE) {
+                        diesel::insert_into(package_dependencies_dsl::package_dependencies)
+                            .values(chunk)
+                            .on_conflict((
+                                package_dependencies_dsl::package_id,
+                                package_dependencies_dsl::dependency_id,
+                            ))
+                            .do_nothing()
+                            .execute(tx_conn)
+                            .await?;
+                    }
+
+                    // batches are committed in order, the previous version is
+                    // already stored even when published in the same batch.
+                    for package in batch.iter().filter(|p| p.package.version > 1) {
+                        let Some(class) = audit_upgrade(tx_conn, package).await? else {
+                            continue;
+                        };
+
+                        diesel::update(packages_dsl::packages.find(&package.package.package_id))
+                            .set(packages_dsl::upgrade_class.eq(class))
+                            .execute(tx_conn)
+                            .await?;
+                    }
+
+                    Ok(inserted)
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        Ok(result)
+    }
+}
+
+/// Direct dependencies of a package, each resolved to the version it links against.
+/// System packages aren't upgraded through a cap and may be missing from the linkage.
+fn direct_dependencies(
+    package_id: &str,
+    original_id: &str,
+    modules: &BTreeMap<String, Vec<u8>>,
+    linkage: &[PackageLinkage],
+) -> Vec<PackageDependency> {
+    let dependencies = match package_diff::direct_dependencies(modules, original_id) {
+        Ok(dependencies) => dependencies,
+        Err(e) => {
+            warn!("Failed to read dependencies of {}: {:#}", package_id, e);
+            return vec![];
+        }
+    };
+
+    dependencies
+        .into_iter()
+        .map(|dependency_id| PackageDependency {
+            package_id: package_id.to_string(),
+            linked_id: linkage
+                .iter()
+                .find(|l| l.dependency_id == dependency_id)
+                .map_or(dependency_id.clone(), |l| l.upgraded_id.clone()),
+            dependency_id,
+        })
+        .collect()
+}
+
+/// Effective class of an upgrade, comparing the package to its previous version.
+/// `None` when the previous version or its bytecode isn't indexed.
+async fn audit_upgrade(
+    conn: &mut AsyncPgConnection,
+    package: &FullPackage,
+) -> Result<Option<UpgradeClassEnum>, Error> {
+    let previous_id = query::get_package_at_version(
+        conn,
+        &package.package.original_id,
+        package.package.version - 1,
+    )
+    .await?;
+
+    let Some(previous_id) = previous_id else {
+        return Ok(None);
+    };
+
+    let previous = query::get_package_bytecode(conn, &previous_id)
+        .await?
+        .into_iter()
+        .map(|m| Some((m.module_name, m.bytecode?)))
+        .collect::<Option<BTreeMap<_, _>>>();
+
+    // modules indexed before bytecode was stored can't be compared.
+    let Some(previous) = previous.filter(|modules| !modules.is_empty()) else {
+        return Ok(None);
+    };
+
+    let current = package
+        .modules
+        .iter()
+        .map(|m| (m.module_name.clone(), m.bytecode.clone()))
+        .collect::<BTreeMap<_, _>>();
+
+    match package_diff::classify_upgrade(&previous, &current) {
+        Ok(class) => {
+            info!(
+                "[PACKAGE] Id: {} Previous: {} Upgrade: {}",
+                package.package.package_id, previous_id, class
+            );
+            Ok(Some(class.into()))
+        }
+        Err(e) => {
+            warn!(
+                "Failed to audit upgrade of {}: {:#}",
+                package.package.package_id, e
+            );
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use anyhow::Result;
+use chrono::DateTime;
+use diesel::result::Error;
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
+use log::info;
+use std::sync::Arc;
+use sui_types::effects::TransactionEffectsAPI;
+use sui_types::move_package::UpgradeCap;
+use sui_types::object::{Data, Object};
+use sui_types::storage::ObjectKey;
+use sui_types::transaction::TransactionDataAPI;
+
+use sui_indexer_alt_framework::pipeline::Processor;
+use sui_indexer_alt_framework::{
+    pipeline::sequential::Handler,
+    postgres::{Connection, Db},
+};
+use sui_types::full_checkpoint_content::Checkpoint;
+
+use crate::models::{UpgradeCapPolicyChange, UpgradeCompatibilityPolicyEnum};
+use phantom_cap_db::events::{self, CapEvent};
+use phantom_cap_db::schema::upgrade_cap_policy_changes::dsl::{
+    object_id as upgrade_cap_policy_changes_object_id,
+    tx_digest as upgrade_cap_policy_changes_tx_digest, upgrade_cap_policy_changes,
+};
+use phantom_cap_db::schema::upgrade_caps::dsl::{object_id, policy, upgrade_caps};
+use phantom_cap_db::state;
+use phantom_cap_db::webhooks;
+
+pub struct UpgradeCapHandler;
+
+fn upgrade_cap_policy(obj: &Object) -> Option<UpgradeCompatibilityPolicyEnum> {
+    let Data::Move(move_data) = &obj.data else {
+        return None;
+    };
+
+    if !move_data.type_().is_upgrade_cap() {
+        return None;
+    }
+
+    let upgrade_cap = obj.to_rust::<UpgradeCap>()?;
+
+    UpgradeCompatibilityPolicyEnum::from_u8(upgrade_cap.policy).ok()
+}
+
+#[async_trait::async_trait]
+impl Processor for UpgradeCapHandler {
+    const NAME: &'static str = "policy_handler";
+
+    type Value = UpgradeCapPolicyChange;
+
+    async fn process(&self, checkpoint: &Arc<Checkpoint>) -> Result<Vec<Self::Value>> {
+        let checkpoint_seq = checkpoint.summary.sequence_number as i64;
+        let checkpoint_timestamp =
+            DateTime::from_timestamp_millis(checkpoint.summary.timestamp_ms as i64).unwrap();
+
+        Ok(checkpoint
+            .transactions
+            .iter()
+            .filter(|tx| tx.effects.status().is_ok())
+            .flat_map(|tx| {
+                let input_versions = tx.effects.modified_at_versions();
+
+                // whatever function restricted it, a cap's policy is only changed
+                // by mutating the cap, compare it before and after the transaction.
+                tx.effects
+                    .mutated_excluding_gas()
+                    .into_iter()
+                    .filter_map(|(output_ref, _)| {
+                        // unwrapped caps have no input version to compare with.
+                        let (_, input_version) =
+                            input_versions.iter().find(|(id, _)| id.eq(&output_ref.0))?;
+
+                        // policy before the transaction.
+                        let old_policy = checkpoint
+                            .object_set
+                            .get(&ObjectKey(output_ref.0, *input_version))
+                            .and_then(upgrade_cap_policy)?;
+
+                        // policy after the transaction.
+                        let new_policy = checkpoint
+                            .object_set
+                            .get(&ObjectKey(output_ref.0, output_ref.1))
+                            .and_then(upgrade_cap_policy)?;
+
+                        if old_policy == new_policy {
+                            return None;
+                        }
+
+                        info!(
+                            "[POLICY] Tx: {} Id: {} {:?} -> {:?}",
+                            tx.transaction.digest(),
+                            output_ref.0.to_hex_literal(),
+                            old_policy,
+                            new_policy
+                        );
+
+                        Some(UpgradeCapPolicyChange {
+                            object_id: output_ref.0.to_hex_literal(),
+                            old_policy,
+                            new_policy,
+                            seq_checkpoint: checkpoint_seq,
+                            tx_digest: tx.transaction.digest().to_string(),
+                            timestamp: checkpoint_timestamp,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl Handler for UpgradeCapHandler {
+    type Store = Db;
+    type Batch = Vec<Self::Value>;
+
+    fn batch(&self, batch: &mut Self::Batch, values: std::vec::IntoIter<Self::Value>) {
+        batch.extend(values);
+    }
+
+    async fn commit<'a>(&self, batch: &Self::Batch, conn: &mut Connection<'a>) -> Result<usize> {
+        let result = conn
+            .transaction::<usize, Error, _>(|tx_conn| {
+                async move {
+                    let inserted = diesel::insert_into(upgrade_cap_policy_changes)
+                        .values(batch)
+                        .on_conflict((
+                            upgrade_cap_policy_changes_object_id,
+                            upgrade_cap_policy_changes_tx_digest,
+                        ))
+                        .do_nothing()
+                        .returning(UpgradeCapPolicyChange::as_returning())
+                        .get_results::<UpgradeCapPolicyChange>(tx_conn)
+                        .await?;
+
+                    // batch is ordered by checkpoint, so the latest change wins.
+                    for change in batch.iter() {
+                        diesel::update(upgrade_caps.filter(object_id.eq(&change.object_id)))
+                            .set(policy.eq(change.new_policy.clone()))
+                            .execute(tx_conn)
+                            .await?;
+
+                        state::policy(
+                            &change.object_id,
+                            change.new_policy.clone(),
+                            change.seq_checkpoint,
+                        )
+                        .execute(tx_conn)
+                        .await?;
+                    }
+
+                    let count = inserted.len();
+                    let events = inserted
+                        .into_iter()
+                        .map(CapEvent::PolicyChange)
+                        .collect::<Vec<_>>();
+                    events::notify(tx_conn, &events).await?;
+                    webhooks::enqueue(tx_conn, &events).await?;
+
+                    Ok(count)
+                }
+                .scope_boxed()
+            })
+            .await?;
+
+        Ok(result)
+    }
+}
@@ -0,0 +1,198 @@
+use anyhow::Result;
+use chrono::DateTime;
+use diesel_async::RunQueryDsl;
+use log::info;
+use std::sync::Arc;
+use sui_types::base_types::SuiAddress;
+use sui_types::effects::TransactionEffectsAPI;
+use sui_types::multisig::MultiSigPublicKey;
+use sui_types::signature::GenericSignature;
+use sui_types::storage::ObjectKey;
+use sui_types::transaction::TransactionDataAPI;
+
+use sui_indexer_alt_framework::pipeline::Processor;
+use sui_indexer_alt_framework::{
+    pipeline::sequential::Handler,
+    postgres::{Connection, Db},
+};
+use sui_types::full_checkpoint_content::Checkpoint;
+
+use crate::models::{CapOwner, OwnerTypeEnum};
+use phantom_cap_db::schema::cap_owners::dsl::*;
+
+pub struct UpgradeCapHandler;
+
+/// Owner type of the signer, with its committee for multisig.
+fn signer_type(signature: &GenericSignature) -> (OwnerTypeEnum, Option<&MultiSigPublicKey>) {
+    match signature {
+        GenericSignature::MultiSig(multisig) => (OwnerTypeEnum::MultiSig, Some(multisig.get_pk())),
+        GenericSignature::ZkLoginAuthenticator(_) => (OwnerTypeEnum::ZkLogin, None),
+        GenericSignature::PasskeyAuthenticator(_) => (OwnerTypeEnum::Passkey, None),
+        // Ed25519, Secp256k1 and Secp256r1 keys.
+        GenericSignature::Signature(_) => (OwnerTypeEnum::SingleKey, None),
+    }
+}
+
+/// Signature of the sender. Sponsored transactions also carry the sponsor's, in no set order.
+fn sender_signature(
+    signatures: &[GenericSignature],
+    sender: SuiAddress,
+) -> Option<&GenericSignature> {
+    signatures
+        .iter()
+        .find(|signature| SuiAddress::try_from(*signature).is_ok_and(|signer| signer == sender))
+}
+
+#[async_trait::async_trait]
+impl Processor for UpgradeCapHandler {
+    const NAME: &'static str = "signer_handler";
+
+    type Value = CapOwner;
+
+    async fn process(&self, checkpoint: &Arc<Checkpoint>) -> Result<Vec<Self::Value>> {
+        let checkpoint_seq = checkpoint.summary.sequence_number as i64;
+        let checkpoint_timestamp =
+            DateTime::from_timestamp_millis(checkpoint.summary.timestamp_ms as i64).unwrap();
+
+        Ok(checkpoint
+            .transactions
+            .iter()
+            .filter(|tx| tx.effects.status().is_ok())
+            .filter_map(|tx| {
+                let sender = tx.transaction.sender();
+
+                let inputs = tx
+                    .effects
+                    .modified_at_versions()
+                    .into_iter()
+                    .map(|(id, input_version)| ObjectKey(id, input_version));
+                let outputs = tx
+                    .effects
+                    .all_changed_objects()
+                    .into_iter()
+                    .map(|(obj_ref, _, _)| ObjectKey(obj_ref.0, obj_ref.1));
+
+                // the sender owns a cap if it used or received one.
+                let sender_owns_cap = inputs
+                    .chain(outputs)
+                    .filter_map(|key| checkpoint.object_set.get(&key))
+                    .any(|obj| {
+                        obj.type_().is_some_and(|t| t.is_upgrade_cap())
+                            && obj.get_single_owner() == Some(sender)
+                    });
+
+                if !sender_owns_cap {
+                    return None;
+                }
+
+                let (signer, multisig_pk) = signer_type(sender_signature(&tx.signatures, sender)?);
+
+                info!(
+                    "[SIGNER] Tx: {} Address: {} Type: {:?}",
+                    tx.transaction.digest(),
+                    sender,
+                    signer
+                );
+
+                Some(CapOwner {
+                    address: sender.to_string(),
+                    owner_type: signer,
+                    multisig_threshold: multisig_pk.map(|pk| *pk.threshold() as i32),
+                    multisig_members: multisig_pk.map(|pk| {
+                        pk.pubkeys()
+                            .iter()
+                            .map(|(member, _)| SuiAddress::from(member).to_string())
+                            .collect()
+                    }),
+                    multisig_weights: multisig_pk.map(|pk| {
+                        pk.pubkeys()
+                            .iter()
+                            .map(|(_, weight)| *weight as i16)
+                            .collect()
+                    }),
+                    seq_checkpoint: checkpoint_seq,
+                    tx_digest: tx.transaction.digest().to_string(),
+                    timestamp: checkpoint_timestamp,
+                })
+            })
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl Handler for UpgradeCapHandler {
+    type Store = Db;
+    type Batch = Vec<Self::Value>;
+
+    fn batch(&self, batch: &mut Self::Batch, values: std::vec::IntoIter<Self::Value>) {
+        batch.extend(values);
+    }
+
+    async fn commit<'a>(&self, batch: &Self::Batch, conn: &mut Connection<'a>) -> Result<usize> {
+        // the address is derived from the signer's keys, so its type never changes.
+        let inserted = diesel::insert_into(cap_owners)
+            .values(batch)
+            .on_conflict(address)
+            .do_nothing()
+            .execute(conn)
+            .await?;
+
+        Ok(inserted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sui_types::crypto::{AccountKeyPair, Signature, SuiKeyPair, get_key_pair};
+    use sui_types::multisig::MultiSig;
+
+    use super::*;
+
+    fn key() -> SuiKeyPair {
+        SuiKeyPair::Ed25519(get_key_pair::<AccountKeyPair>().1)
+    }
+
+    #[test]
+    fn sponsored_transactions_classify_the_sender() {
+        let message = b"transaction";
+        let sender = key();
+        let sender_address = SuiAddress::from(&sender.public());
+        let sender_sig = GenericSignature::Signature(Signature::new_hashed(message, &sender));
+
+        // a multisig sponsor, which the sender must not be mistaken for.
+        let members = [key(), key()];
+        let sponsor_pk =
+            MultiSigPublicKey::new(members.iter().map(|k| k.public()).collect(), vec![1, 1], 2)
+                .unwrap();
+        let sponsor_address = SuiAddress::from(&sponsor_pk);
+        let sponsor_sig = GenericSignature::MultiSig(
+            MultiSig::combine(
+                members
+                    .iter()
+                    .map(|k| Signature::new_hashed(message, k))
+                    .collect(),
+                sponsor_pk,
+            )
+            .unwrap(),
+        );
+
+        for signatures in [
+            vec![sender_sig.clone(), sponsor_sig.clone()],
+            vec![sponsor_sig.clone(), sender_sig.clone()],
+        ] {
+            let signature = sender_signature(&signatures, sender_address).unwrap();
+            assert!(matches!(
+                signer_type(signature),
+                (OwnerTypeEnum::SingleKey, None)
+            ));
+
+            let signature = sender_signature(&signatures, sponsor_address).unwrap();
+            assert!(matches!(
+                signer_type(signature),
+                (OwnerTypeEnum::MultiSig, Some(_))
+            ));
+        }
+
+        assert!(sender_signature(&[sponsor_sig], sender_address).is_none());
+    }
+}
@@ -1,6 +1,9 @@
-use anyhow::{Ok, Result};
+use anyhow::Result;
 use chrono::DateTime;
-use diesel_async::RunQueryDsl;
+use diesel::result::Error;
+use diesel::{ExpressionMethods, QueryDsl, SelectableHelper};
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
 use log::info;
 use std::sync::Arc;
 use sui_indexer_alt_framework::pipeline::Processor;
@@ -8,18 +11,23 @@ use sui_indexer_alt_framework::{
     pipeline::sequential::Handler,
     postgres::{Connection, Db},
 };
+use sui_types::base_types::ObjectID;
 use sui_types::effects::TransactionEffectsAPI;
+use sui_types::full_checkpoint_content::Checkpoint;
+use sui_types::object::Object;
 use sui_types::storage::ObjectKey;
-use sui_types::transaction::{Argument, CallArg, ObjectArg};
-use sui_types::{
-    base_types::SuiAddress,
-    full_checkpoint_content::Checkpoint,
-    object::Data,
-    transaction::{Command, TransactionDataAPI, TransactionKind},
+use sui_types::transaction::TransactionDataAPI;
+
+use crate::models::{UpgradeCapTransfer, UpgradeCompatibilityPolicyEnum};
+use crate::owner::{IMMUTABLE_OWNER, WRAPPED_OWNER, owner_to_string};
+use phantom_cap_db::events::{self, CapEvent};
+use phantom_cap_db::schema::upgrade_cap_transfers::dsl::{
+    object_id as upgrade_cap_transfers_object_id, tx_digest as upgrade_cap_transfers_tx_digest,
+    upgrade_cap_transfers,
 };
-
-use crate::models::UpgradeCapTransfer;
-use crate::schema::upgrade_cap_transfers::dsl::*;
+use phantom_cap_db::schema::upgrade_caps::dsl::{object_id, policy, upgrade_caps};
+use phantom_cap_db::state;
+use phantom_cap_db::webhooks;
 
 pub struct UpgradeCapHandler;
 
@@ -39,75 +47,81 @@ impl Processor for UpgradeCapHandler {
             .iter()
             .filter(|tx| tx.effects.status().is_ok())
             .flat_map(|tx| {
-                let pt = match tx.transaction.kind() {
-                    TransactionKind::ProgrammableTransaction(pt) => pt,
-                    TransactionKind::ProgrammableSystemTransaction(pt) => pt,
-                    _ => return vec![],
+                let input_versions = tx.effects.modified_at_versions();
+                let input_cap = |id: &ObjectID| -> Option<&Object> {
+                    let (_, input_version) = input_versions
+                        .iter()
+                        .find(|(input_id, _)| input_id.eq(id))?;
+                    let obj = checkpoint.object_set.get(&ObjectKey(*id, *input_version))?;
+
+                    obj.type_()?.is_upgrade_cap().then_some(obj)
                 };
 
-                pt.commands
-                    .iter()
-                    .filter_map(|command| {
-                        if let Command::TransferObjects(objects, receiver) = command {
-                            Some((objects, receiver))
-                        } else {
-                            None
-                        }
-                    })
-                    .flat_map(|(objects, receiver)| {
-                        let receiver_address = if let Argument::Input(idx) = receiver {
-                            pt.inputs
-                                .get(*idx as usize)
-                                .and_then(|arg| {
-                                    if let CallArg::Pure(bytes) = arg {
-                                        SuiAddress::from_bytes(bytes).ok()
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .unwrap_or_default()
-                        } else {
-                            SuiAddress::default()
-                        };
-
-                        objects.iter().filter_map(move |object_arg| {
-                            let Argument::Input(idx) = object_arg else {
-                                return None;
-                            };
-
-                            let CallArg::Object(ObjectArg::ImmOrOwnedObject(obj_ref)) =
-                                pt.inputs.get(*idx as usize)?
-                            else {
-                                return None;
-                            };
-
+                // compare input and output owner of every mutated upgrade cap,
+                // regardless of the commands that moved it.
+                let mutated = tx.effects.mutated_excluding_gas().into_iter().filter_map(
+                    |(output_ref, new_owner)| {
+                        let obj = input_cap(&output_ref.0)?;
+                        Some((
+                            output_ref.0,
+                            owner_to_string(obj.owner()),
+                            owner_to_string(&new_owner),
+                        ))
+                    },
+                );
+
+                // wrapped caps have no output object.
+                let wrapped = tx.effects.wrapped().into_iter().filter_map(|wrapped_ref| {
+                    let obj = input_cap(&wrapped_ref.0)?;
+                    Some((
+                        wrapped_ref.0,
+                        owner_to_string(obj.owner()),
+                        WRAPPED_OWNER.to_string(),
+                    ))
+                });
+
+                // unwrapped caps have no input object.
+                let unwrapped =
+                    tx.effects
+                        .unwrapped()
+                        .into_iter()
+                        .filter_map(|(output_ref, new_owner)| {
                             let obj = checkpoint
                                 .object_set
-                                .get(&ObjectKey(obj_ref.0, obj_ref.1))?;
+                                .get(&ObjectKey(output_ref.0, output_ref.1))?;
 
-                            let Data::Move(move_data) = &obj.data else {
-                                return None;
-                            };
-
-                            if !move_data.type_().is_upgrade_cap() {
+                            if !obj.type_()?.is_upgrade_cap() {
                                 return None;
                             }
 
-                            info!(
-                                "[TRANSFER] Tx: {} Id: {}",
-                                tx.transaction.digest().to_string(),
-                                obj.id().to_hex_literal()
-                            );
-
-                            Some(UpgradeCapTransfer {
-                                object_id: obj.id().to_hex_literal(),
-                                old_owner_address: obj.get_single_owner().unwrap().to_string(),
-                                new_owner_address: receiver_address.to_string(),
-                                tx_digest: tx.transaction.digest().to_string(),
-                                seq_checkpoint: checkpoint_seq,
-                                timestamp: checkpoint_timestamp,
-                            })
-                        })
+                            Some((
+                                output_ref.0,
+                                WRAPPED_OWNER.to_string(),
+                                owner_to_string(&new_owner),
+                            ))
+                        });
+
+                mutated
+                    .chain(wrapped)
+                    .chain(unwrapped)
+                    .filter(|(_, old_owner_address, new_owner_address)| {
+                        old_owner_address != new_owner_address
+                    })
+                    .map(|(cap_id, old_owner_address, new_owner_address)| {
+                        info!(
+                            "[TRANSFER] Tx: {} Id: {}",
+                            tx.transaction.digest(),
+                            cap_id.to_hex_literal()
+                        );
+
+                        UpgradeCapTransfer {
+                            object_id: cap_id.to_hex_literal(),
+                            old_owner_address,
+                            new_owner_address,
+                            tx_digest: tx.transaction.digest().to_string(),
+                            seq_checkpoint: checkpoint_seq,
+                            timestamp: checkpoint_timestamp,
+                        }
                     })
                     .collect::<Vec<_>>()
             })
@@ -125,14 +139,63 @@ impl Handler for UpgradeCapHandler {
     }
 
     async fn commit<'a>(&self, batch: &Self::Batch, conn: &mut Connection<'a>) -> Result<usize> {
-        // TODO: check if new owner is "immutable",
-        // update upgrade cap policy as immutable.
-        let inserted = diesel::insert_into(upgrade_cap_transfers)
-            .values(batch)
-            .on_conflict((object_id, tx_digest))
-            .do_nothing()
-            .execute(conn)
+        // frozen caps can't authorize upgrades anymore.
+        let frozen_caps = batch
+            .iter()
+            .filter(|transfer| transfer.new_owner_address == IMMUTABLE_OWNER)
+            .map(|transfer| transfer.object_id.as_str())
+            .collect::<Vec<_>>();
+
+        let result = conn
+            .transaction::<usize, Error, _>(|tx_conn| {
+                async move {
+                    let inserted = diesel::insert_into(upgrade_cap_transfers)
+                        .values(batch)
+                        .on_conflict((
+                            upgrade_cap_transfers_object_id,
+                            upgrade_cap_transfers_tx_digest,
+                        ))
+                        .do_nothing()
+                        .returning(UpgradeCapTransfer::as_returning())
+                        .get_results::<UpgradeCapTransfer>(tx_conn)
+                        .await?;
+
+                    if !frozen_caps.is_empty() {
+                        diesel::update(upgrade_caps.filter(object_id.eq_any(frozen_caps)))
+                            .set(policy.eq(UpgradeCompatibilityPolicyEnum::Immutable))
+                            .execute(tx_conn)
+                            .await?;
+                    }
+
+                    // batch is ordered by checkpoint, so the latest transfer wins.
+                    for transfer in batch.iter() {
+                        state::owner(transfer).execute(tx_conn).await?;
+
+                        if transfer.new_owner_address == IMMUTABLE_OWNER {
+                            state::policy(
+                                &transfer.object_id,
+                                UpgradeCompatibilityPolicyEnum::Immutable,
+                                transfer.seq_checkpoint,
+                            )
+                            .execute(tx_conn)
+                            .await?;
+                        }
+                    }
+
+                    let count = inserted.len();
+                    let events = inserted
+                        .into_iter()
+                        .map(CapEvent::Transfer)
+                        .collect::<Vec<_>>();
+                    events::notify(tx_conn, &events).await?;
+                    webhooks::enqueue(tx_conn, &events).await?;
+
+                    Ok(count)
+                }
+                .scope_boxed()
+            })
             .await?;
-        Ok(inserted)
+
+        Ok(result)
     }
 }
@@ -1,12 +1,15 @@
 use anyhow::Result;
 use chrono::DateTime;
-use diesel_async::RunQueryDsl;
+use diesel::SelectableHelper;
+use diesel::result::Error;
+use diesel_async::scoped_futures::ScopedFutureExt;
+use diesel_async::{AsyncConnection, RunQueryDsl};
 use log::info;
 use std::sync::Arc;
 use sui_types::SUI_FRAMEWORK_PACKAGE_ID;
 use sui_types::effects::TransactionEffectsAPI;
 use sui_types::move_package::UpgradeCap;
-use sui_types::object::{Data, Owner};
+use sui_types::object::Data;
 use sui_types::storage::ObjectKey;
 use sui_types::transaction::{
     Argument, CallArg, Command, ObjectArg, TransactionDataAPI, TransactionKind,
@@ -20,7 +23,11 @@ use sui_indexer_alt_framework::{
 use sui_types::full_checkpoint_content::Checkpoint;
 
 use crate::models::UpgradeCapVersion;
-use crate::schema::upgrade_cap_versions::dsl::*;
+use crate::owner::owner_to_string;
+use phantom_cap_db::events::{self, CapEvent};
+use phantom_cap_db::schema::upgrade_cap_versions::dsl::*;
+use phantom_cap_db::state;
+use phantom_cap_db::webhooks;
 
 pub struct UpgradeCapHandler;
 
@@ -99,18 +106,7 @@ impl Processor for UpgradeCapHandler {
 
                                     let upgrade_cap = obj.to_rust::<UpgradeCap>().unwrap();
 
-                                    let owner = match obj.owner() {
-                                        Owner::AddressOwner(address) => address.to_string(),
-                                        Owner::ObjectOwner(address) => address.to_string(),
-                                        Owner::Shared {
-                                            initial_shared_version: _,
-                                        } => "shared".to_string(),
-                                        Owner::Immutable => "immutable".to_string(),
-                                        Owner::ConsensusAddressOwner {
-                                            start_version: _,
-                                            owner: address,
-                                        } => address.to_string(),
-                                    };
+                                    let owner = owner_to_string(obj.owner());
 
                                     Some((upgrade_cap, owner))
                                 })
@@ -152,13 +148,36 @@ impl Handler for UpgradeCapHandler {
     }
 
     async fn commit<'a>(&self, batch: &Self::Batch, conn: &mut Connection<'a>) -> Result<usize> {
-        let inserted = diesel::insert_into(upgrade_cap_versions)
-            .values(batch)
-            .on_conflict((object_id, version))
-            .do_nothing()
-            .execute(conn)
+        let result = conn
+            .transaction::<usize, Error, _>(|tx_conn| {
+                async move {
+                    let inserted = diesel::insert_into(upgrade_cap_versions)
+                        .values(batch)
+                        .on_conflict((object_id, version))
+                        .do_nothing()
+                        .returning(UpgradeCapVersion::as_returning())
+                        .get_results::<UpgradeCapVersion>(tx_conn)
+                        .await?;
+
+                    for upgrade in batch.iter() {
+                        state::package(upgrade).execute(tx_conn).await?;
+                    }
+
+                    // rows already indexed before a restart aren't announced again.
+                    let count = inserted.len();
+                    let events = inserted
+                        .into_iter()
+                        .map(CapEvent::Version)
+                        .collect::<Vec<_>>();
+                    events::notify(tx_conn, &events).await?;
+                    webhooks::enqueue(tx_conn, &events).await?;
+
+                    Ok(count)
+                }
+                .scope_boxed()
+            })
             .await?;
 
-        Ok(inserted)
+        Ok(result)
     }
 }
@@ -1,16 +1,19 @@
 mod handlers;
 mod models;
-mod schema;
+mod owner;
 
 use log::info;
 
 use handlers::{
-    created::UpgradeCapHandler as CreatedHandler, transfer::UpgradeCapHandler as TransferHandler,
-    upgrade::UpgradeCapHandler as UpgradeHandler,
+    created::UpgradeCapHandler as CreatedHandler, destroy::UpgradeCapHandler as DestroyHandler,
+    holder::UpgradeCapHandler as HolderHandler, package::UpgradeCapHandler as PackageHandler,
+    policy::UpgradeCapHandler as PolicyHandler, signer::UpgradeCapHandler as SignerHandler,
+    transfer::UpgradeCapHandler as TransferHandler, upgrade::UpgradeCapHandler as UpgradeHandler,
 };
 
 use anyhow::Result;
 use clap::Parser;
+use phantom_cap_db::Network;
 use sui_indexer_alt_framework::{
     cluster::{Args, IndexerCluster},
     ingestion::{
@@ -26,15 +29,22 @@ async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
     env_logger::init();
 
-    let database_url = std::env::var("DATABASE_URL")
-        .expect("DATABASE_URL must be set")
-        .parse::<Url>()
-        .expect("Invalid database URL");
+    let network = std::env::var("NETWORK")
+        .map(|n| n.parse::<Network>().expect("Invalid NETWORK"))
+        .unwrap_or(Network::Mainnet);
+
+    // Every network is indexed into its own schema, see `Network::schema`.
+    let database_url = network.database_url(
+        &std::env::var("DATABASE_URL")
+            .expect("DATABASE_URL must be set")
+            .parse::<Url>()
+            .expect("Invalid database URL"),
+    );
 
     let args = Args::try_parse().expect("Failed to parse arguments");
 
     let remote_store_url = std::env::var("REMOTE_STORE_URL")
-        .expect("REMOTE_STORE_URL must be set")
+        .unwrap_or_else(|_| format!("https://checkpoints.{}.sui.io", network))
         .parse::<Url>()
         .expect("Invalid remote store URL");
 
@@ -66,7 +76,27 @@ async fn main() -> Result<()> {
         .sequential_pipeline(UpgradeHandler, SequentialConfig::default())
         .await?;
 
-    info!("Running Sequential Indexer");
+    cluster
+        .sequential_pipeline(PolicyHandler, SequentialConfig::default())
+        .await?;
+
+    cluster
+        .sequential_pipeline(DestroyHandler, SequentialConfig::default())
+        .await?;
+
+    cluster
+        .sequential_pipeline(HolderHandler, SequentialConfig::default())
+        .await?;
+
+    cluster
+        .sequential_pipeline(SignerHandler, SequentialConfig::default())
+        .await?;
+
+    cluster
+        .sequential_pipeline(PackageHandler, SequentialConfig::default())
+        .await?;
+
+    info!("Running Sequential Indexer on {}", network);
 
     let handle = cluster.run().await?;
     handle.await?;
@@ -1,70 +1,6 @@
-use crate::schema::sql_types::UpgradeCompatibilityPolicy;
-use crate::schema::*;
-use anyhow::{Error, anyhow};
 use chrono::{DateTime, Utc};
-use diesel::deserialize::{FromSql, FromSqlRow};
-use diesel::expression::AsExpression;
-use diesel::pg::{Pg, PgValue};
-use diesel::prelude::*;
-use diesel::serialize::{IsNull, Output, ToSql};
-use diesel::*;
-use std::io::Write;
-use sui_indexer_alt_framework::FieldCount;
+pub use phantom_cap_db::models::*;
 use sui_types::base_types::SuiAddress;
-use sui_types::move_package::UpgradePolicy;
-
-#[derive(Debug, PartialEq, FromSqlRow, AsExpression, Eq, Clone)]
-#[diesel(sql_type = UpgradeCompatibilityPolicy)]
-pub enum UpgradeCompatibilityPolicyEnum {
-    Compatible,
-    Additive,
-    DepOnly,
-    Immutable,
-}
-
-impl UpgradeCompatibilityPolicyEnum {
-    pub fn from_u8(policy: u8) -> Result<Self, Error> {
-        match policy {
-            val if val == UpgradePolicy::Compatible as u8 => {
-                Ok(UpgradeCompatibilityPolicyEnum::Compatible)
-            }
-            val if val == UpgradePolicy::Additive as u8 => {
-                Ok(UpgradeCompatibilityPolicyEnum::Additive)
-            }
-            val if val == UpgradePolicy::DepOnly as u8 => {
-                Ok(UpgradeCompatibilityPolicyEnum::DepOnly)
-            }
-            _ => Err(anyhow!(format!(
-                "Invalid UpgradeCompatibilityPolicy: {}",
-                policy
-            ))),
-        }
-    }
-}
-
-impl ToSql<UpgradeCompatibilityPolicy, Pg> for UpgradeCompatibilityPolicyEnum {
-    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
-        match *self {
-            UpgradeCompatibilityPolicyEnum::Compatible => out.write_all(b"compatible")?,
-            UpgradeCompatibilityPolicyEnum::Additive => out.write_all(b"additive")?,
-            UpgradeCompatibilityPolicyEnum::DepOnly => out.write_all(b"dep_only")?,
-            UpgradeCompatibilityPolicyEnum::Immutable => out.write_all(b"immutable")?,
-        }
-        Ok(IsNull::No)
-    }
-}
-
-impl FromSql<UpgradeCompatibilityPolicy, Pg> for UpgradeCompatibilityPolicyEnum {
-    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
-        match bytes.as_bytes() {
-            b"compatible" => Ok(UpgradeCompatibilityPolicyEnum::Compatible),
-            b"additive" => Ok(UpgradeCompatibilityPolicyEnum::Additive),
-            b"dep_only" => Ok(UpgradeCompatibilityPolicyEnum::DepOnly),
-            b"immutable" => Ok(UpgradeCompatibilityPolicyEnum::Immutable),
-            _ => Err("Unrecognized enum variant".into()),
-        }
-    }
-}
 
 pub struct FullUpgradeCap {
     pub object_id: String,
@@ -110,37 +46,46 @@ impl FullUpgradeCap {
             timestamp: self.created_at,
         }
     }
-}
 
-#[derive(Insertable, Clone, FieldCount)]
-#[diesel(table_name = upgrade_caps)]
-pub struct UpgradeCap {
-    pub object_id: String,
-    pub policy: UpgradeCompatibilityPolicyEnum,
-    pub created_seq_checkpoint: i64,
-    pub created_tx_digest: String,
-    pub created_at: DateTime<Utc>,
+    pub fn creation_state(&self) -> UpgradeCapState {
+        UpgradeCapState {
+            object_id: self.object_id.clone(),
+            owner_address: self.owner_address.clone(),
+            owner_tx_digest: self.created_tx_digest.clone(),
+            package_id: self.package_id.clone(),
+            version: self.version,
+            last_upgrade_at: None,
+            policy: self.policy.clone(),
+            destroyed: false,
+            last_activity_checkpoint: self.created_seq_checkpoint,
+            owner_checkpoint: self.created_seq_checkpoint,
+            destroyed_seq_checkpoint: None,
+            destroyed_tx_digest: None,
+            destroyed_at: None,
+        }
+    }
 }
 
-#[derive(Insertable, Clone, FieldCount, Debug)]
-#[diesel(table_name = upgrade_cap_transfers)]
-pub struct UpgradeCapTransfer {
-    pub object_id: String,
-    pub old_owner_address: String,
-    pub new_owner_address: String,
+/// Caps leaving the object set in a transaction.
+#[derive(Clone, Debug)]
+pub struct UpgradeCapRemovals {
     pub seq_checkpoint: i64,
     pub tx_digest: String,
     pub timestamp: DateTime<Utc>,
+    /// Caps wrapped in another object.
+    pub wrapped: Vec<String>,
+    /// Wrapped caps back in the object set.
+    pub unwrapped: Vec<String>,
+    pub deleted: Vec<String>,
+    /// Deleted objects that were wrapped, destroyed caps if wrapped as one.
+    pub unwrapped_then_deleted: Vec<String>,
 }
 
-#[derive(Insertable, Clone, FieldCount, Debug)]
-#[diesel(table_name = upgrade_cap_versions)]
-pub struct UpgradeCapVersion {
-    pub object_id: String,
-    pub package_id: String,
-    pub version: i64,
-    pub seq_checkpoint: i64,
-    pub tx_digest: String,
-    pub publisher: String,
-    pub timestamp: DateTime<Utc>,
+/// Contents of a published or upgraded package.
+pub struct FullPackage {
+    pub package: Package,
+    pub modules: Vec<PackageModule>,
+    pub type_origins: Vec<PackageTypeOrigin>,
+    pub linkage: Vec<PackageLinkage>,
+    pub dependencies: Vec<PackageDependency>,
 }
@@ -0,0 +1,126 @@
+use sui_types::base_types::ObjectID;
+use sui_types::object::{Data, Object, Owner};
+
+pub use phantom_cap_db::models::{IMMUTABLE_OWNER, SHARED_OWNER, WRAPPED_OWNER};
+
+/// Maximum number of parent objects followed from an UpgradeCap to its controller.
+const MAX_HOLDER_DEPTH: usize = 16;
+
+/// Stored representation of an object owner.
+pub fn owner_to_string(owner: &Owner) -> String {
+    match owner {
+        Owner::AddressOwner(address) => address.to_string(),
+        Owner::ObjectOwner(address) => address.to_string(),
+        Owner::Shared {
+            initial_shared_version: _,
+        } => SHARED_OWNER.to_string(),
+        Owner::Immutable => IMMUTABLE_OWNER.to_string(),
+        Owner::ConsensusAddressOwner {
+            start_version: _,
+            owner: address,
+        } => address.to_string(),
+    }
+}
+
+pub fn object_type(obj: &Object) -> String {
+    obj.struct_tag()
+        .map(|tag| tag.to_canonical_string(true))
+        .unwrap_or_default()
+}
+
+/// Objects holding an UpgradeCap, from its direct parent up to the root owner.
+pub struct HolderChain<'a> {
+    pub objects: Vec<&'a Object>,
+    /// Parent of the last object, when it wasn't written by the transaction.
+    pub unresolved_parent: Option<ObjectID>,
+}
+
+impl<'a> HolderChain<'a> {
+    /// Direct holder, skipping the dynamic field objects in between.
+    pub fn holder(&self) -> &'a Object {
+        self.objects
+            .iter()
+            .find(|obj| !obj.type_().is_some_and(|t| t.is_dynamic_field()))
+            .or(self.objects.first())
+            .copied()
+            .unwrap()
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        self.objects
+            .iter()
+            .map(|obj| obj.id())
+            .chain(self.unresolved_parent)
+            .map(|id| id.to_hex_literal())
+            .collect()
+    }
+
+    /// Owner of the outermost object at the time of the transaction, unknown
+    /// if the chain is unresolved. Later transfers of the outermost object aren't followed.
+    pub fn root_owner(&self) -> Option<String> {
+        if self.unresolved_parent.is_some() {
+            return None;
+        }
+
+        self.objects.last().map(|obj| owner_to_string(obj.owner()))
+    }
+}
+
+fn move_contents(obj: &Object) -> Option<&[u8]> {
+    let Data::Move(move_data) = &obj.data else {
+        return None;
+    };
+
+    Some(move_data.contents())
+}
+
+/// Finds the written object `cap` was wrapped into. A dynamic field ends with
+/// its value, from there the effects give its parent object as owner. Any other
+/// wrapper embeds the whole serialized cap, an object only storing its id doesn't.
+pub fn find_wrapper<'a>(written: &[&'a Object], cap: &Object) -> Option<&'a Object> {
+    let cap_contents = move_contents(cap)?;
+    let others = written
+        .iter()
+        .copied()
+        .filter(|obj| obj.id() != cap.id())
+        .collect::<Vec<_>>();
+
+    let dynamic_field = others.iter().copied().find(|obj| {
+        obj.type_().is_some_and(|t| t.is_dynamic_field())
+            && move_contents(obj).is_some_and(|contents| contents.ends_with(cap_contents))
+    });
+
+    dynamic_field.or_else(|| {
+        others.iter().copied().find(|obj| {
+            move_contents(obj).is_some_and(|contents| {
+                contents
+                    .windows(cap_contents.len())
+                    .any(|bytes| bytes == cap_contents)
+            })
+        })
+    })
+}
+
+/// Follows object ownership from `holder` through the objects written by the transaction.
+pub fn resolve_holder_chain<'a>(written: &[&'a Object], holder: &'a Object) -> HolderChain<'a> {
+    let mut objects = vec![holder];
+    let mut unresolved_parent = None;
+
+    while let Owner::ObjectOwner(parent) = objects.last().unwrap().owner() {
+        let parent_id = ObjectID::from(*parent);
+        let parent_obj = written.iter().copied().find(|obj| obj.id() == parent_id);
+
+        match parent_obj {
+            Some(parent_obj) if objects.len() < MAX_HOLDER_DEPTH => objects.push(parent_obj),
+            _ => {
+                unresolved_parent = Some(parent_id);
+                break;
+            }
+        }
+    }
+
+    HolderChain {
+        objects,
+        unresolved_parent,
+    }
+}
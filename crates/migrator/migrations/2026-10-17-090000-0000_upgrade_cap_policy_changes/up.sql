@@ -0,0 +1,13 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS upgrade_cap_policy_changes (
+    object_id TEXT NOT NULL,
+    old_policy upgrade_compatibility_policy NOT NULL,
+    new_policy upgrade_compatibility_policy NOT NULL,
+    seq_checkpoint BIGINT NOT NULL,
+    tx_digest TEXT NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (object_id, tx_digest)
+);
+
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_policy_changes_object_idx ON upgrade_cap_policy_changes USING HASH (object_id);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE upgrade_caps
+    DROP COLUMN IF EXISTS destroyed_seq_checkpoint,
+    DROP COLUMN IF EXISTS destroyed_tx_digest,
+    DROP COLUMN IF EXISTS destroyed_at;
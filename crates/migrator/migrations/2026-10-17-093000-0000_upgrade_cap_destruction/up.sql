@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE upgrade_caps
+    ADD COLUMN IF NOT EXISTS destroyed_seq_checkpoint BIGINT,
+    ADD COLUMN IF NOT EXISTS destroyed_tx_digest TEXT,
+    ADD COLUMN IF NOT EXISTS destroyed_at TIMESTAMPTZ;
@@ -0,0 +1,15 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS upgrade_cap_holders (
+    object_id TEXT NOT NULL,
+    holder_id TEXT NOT NULL,
+    holder_type TEXT NOT NULL,
+    chain TEXT[] NOT NULL,
+    root_owner TEXT,
+    seq_checkpoint BIGINT NOT NULL,
+    tx_digest TEXT NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (object_id, tx_digest)
+);
+
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_holders_object_idx ON upgrade_cap_holders USING HASH (object_id);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS cap_owners;
+DROP TYPE IF EXISTS owner_type;
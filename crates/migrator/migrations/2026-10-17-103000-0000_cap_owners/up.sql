@@ -0,0 +1,18 @@
+-- Your SQL goes here
+CREATE TYPE owner_type AS ENUM (
+    'single_key',
+    'multisig',
+    'zklogin',
+    'passkey'
+);
+
+CREATE TABLE IF NOT EXISTS cap_owners (
+    address TEXT PRIMARY KEY,
+    owner_type owner_type NOT NULL,
+    multisig_threshold INTEGER,
+    multisig_members TEXT[],
+    multisig_weights SMALLINT[],
+    seq_checkpoint BIGINT NOT NULL,
+    tx_digest TEXT NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
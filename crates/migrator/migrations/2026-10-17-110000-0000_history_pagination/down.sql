@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS upgrade_cap_versions_object_version_idx;
+DROP INDEX IF EXISTS upgrade_cap_transfers_object_seq_idx;
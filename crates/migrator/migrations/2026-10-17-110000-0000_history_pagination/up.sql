@@ -0,0 +1,6 @@
+-- Your SQL goes here
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_transfers_object_seq_idx ON upgrade_cap_transfers (object_id, seq_checkpoint DESC, tx_digest DESC);
+
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_versions_object_version_idx ON upgrade_cap_versions (object_id, version DESC);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS package_linkage;
+DROP TABLE IF EXISTS package_type_origins;
+DROP TABLE IF EXISTS package_modules;
+DROP TABLE IF EXISTS packages;
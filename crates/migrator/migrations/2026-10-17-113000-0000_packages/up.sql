@@ -0,0 +1,34 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS packages (
+    package_id TEXT PRIMARY KEY,
+    original_id TEXT NOT NULL,
+    version BIGINT NOT NULL,
+    seq_checkpoint BIGINT NOT NULL,
+    tx_digest TEXT NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS 
+    packages_original_idx ON packages USING HASH (original_id);
+
+CREATE TABLE IF NOT EXISTS package_modules (
+    package_id TEXT NOT NULL,
+    module_name TEXT NOT NULL,
+    PRIMARY KEY (package_id, module_name)
+);
+
+CREATE TABLE IF NOT EXISTS package_type_origins (
+    package_id TEXT NOT NULL,
+    module_name TEXT NOT NULL,
+    datatype_name TEXT NOT NULL,
+    origin_package_id TEXT NOT NULL,
+    PRIMARY KEY (package_id, module_name, datatype_name)
+);
+
+CREATE TABLE IF NOT EXISTS package_linkage (
+    package_id TEXT NOT NULL,
+    dependency_id TEXT NOT NULL,
+    upgraded_id TEXT NOT NULL,
+    upgraded_version BIGINT NOT NULL,
+    PRIMARY KEY (package_id, dependency_id)
+);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE package_modules
+    DROP COLUMN IF EXISTS bytecode;
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE package_modules
+    ADD COLUMN IF NOT EXISTS bytecode BYTEA;
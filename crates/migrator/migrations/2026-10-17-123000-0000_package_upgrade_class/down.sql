@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE packages
+    DROP COLUMN IF EXISTS upgrade_class;
+DROP TYPE IF EXISTS upgrade_class;
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+CREATE TYPE upgrade_class AS ENUM (
+    'dep_only',
+    'additive',
+    'compatible'
+);
+
+-- NULL for the first version or when the previous version isn't indexed.
+ALTER TABLE packages
+    ADD COLUMN IF NOT EXISTS upgrade_class upgrade_class;
@@ -0,0 +1,11 @@
+-- Your SQL goes here
+-- Direct dependencies found in the bytecode, package_linkage also lists transitive ones.
+CREATE TABLE IF NOT EXISTS package_dependencies (
+    package_id TEXT NOT NULL,
+    dependency_id TEXT NOT NULL,
+    linked_id TEXT NOT NULL,
+    PRIMARY KEY (package_id, dependency_id)
+);
+
+CREATE INDEX IF NOT EXISTS 
+    package_dependencies_dependency_idx ON package_dependencies USING HASH (dependency_id);
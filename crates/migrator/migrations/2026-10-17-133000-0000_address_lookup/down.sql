@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS upgrade_cap_versions_publisher_idx;
+DROP INDEX IF EXISTS upgrade_cap_holders_root_owner_idx;
+DROP INDEX IF EXISTS upgrade_cap_transfers_new_owner_idx;
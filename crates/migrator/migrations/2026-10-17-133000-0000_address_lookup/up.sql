@@ -0,0 +1,9 @@
+-- Your SQL goes here
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_transfers_new_owner_idx ON upgrade_cap_transfers USING HASH (new_owner_address);
+
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_holders_root_owner_idx ON upgrade_cap_holders USING HASH (root_owner);
+
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_versions_publisher_idx ON upgrade_cap_versions USING HASH (publisher);
@@ -0,0 +1,54 @@
+-- Your SQL goes here
+CREATE TABLE IF NOT EXISTS upgrade_cap_state (
+    object_id TEXT PRIMARY KEY,
+    owner_address TEXT NOT NULL,
+    owner_tx_digest TEXT NOT NULL,
+    package_id TEXT NOT NULL,
+    version BIGINT NOT NULL,
+    last_upgrade_at TIMESTAMPTZ,
+    policy upgrade_compatibility_policy NOT NULL,
+    destroyed BOOLEAN NOT NULL DEFAULT FALSE,
+    last_activity_checkpoint BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_state_owner_idx ON upgrade_cap_state USING HASH (owner_address);
+
+-- backfill from the history tables, the indexer keeps it up to date from here.
+INSERT INTO upgrade_cap_state
+SELECT c.object_id,
+    t.new_owner_address,
+    t.tx_digest,
+    v.package_id,
+    v.version,
+    CASE WHEN v.version > 1 THEN v.timestamp END,
+    c.policy,
+    c.destroyed_tx_digest IS NOT NULL,
+    GREATEST(
+        c.created_seq_checkpoint,
+        t.seq_checkpoint,
+        v.seq_checkpoint,
+        p.seq_checkpoint,
+        c.destroyed_seq_checkpoint
+    )
+FROM upgrade_caps c
+JOIN LATERAL (
+    SELECT new_owner_address, tx_digest, seq_checkpoint
+    FROM upgrade_cap_transfers
+    WHERE object_id = c.object_id
+    ORDER BY seq_checkpoint DESC, tx_digest DESC
+    LIMIT 1
+) t ON TRUE
+JOIN LATERAL (
+    SELECT package_id, version, seq_checkpoint, timestamp
+    FROM upgrade_cap_versions
+    WHERE object_id = c.object_id
+    ORDER BY version DESC
+    LIMIT 1
+) v ON TRUE
+LEFT JOIN LATERAL (
+    SELECT MAX(seq_checkpoint) AS seq_checkpoint
+    FROM upgrade_cap_policy_changes
+    WHERE object_id = c.object_id
+) p ON TRUE
+ON CONFLICT (object_id) DO NOTHING;
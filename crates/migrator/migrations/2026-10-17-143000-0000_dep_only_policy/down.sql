@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TYPE upgrade_compatibility_policy RENAME VALUE 'dep_only' TO 'dep-only';
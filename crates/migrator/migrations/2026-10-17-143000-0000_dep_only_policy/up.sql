@@ -0,0 +1,2 @@
+-- Your SQL goes here
+ALTER TYPE upgrade_compatibility_policy RENAME VALUE 'dep-only' TO 'dep_only';
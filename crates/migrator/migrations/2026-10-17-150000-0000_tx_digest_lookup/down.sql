@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS upgrade_cap_holders_holder_idx;
+DROP INDEX IF EXISTS upgrade_cap_policy_changes_tx_idx;
+DROP INDEX IF EXISTS upgrade_cap_versions_tx_idx;
+DROP INDEX IF EXISTS upgrade_cap_transfers_tx_idx;
+DROP INDEX IF EXISTS upgrade_caps_destroyed_tx_idx;
+DROP INDEX IF EXISTS upgrade_caps_created_tx_idx;
@@ -0,0 +1,18 @@
+-- Your SQL goes here
+CREATE INDEX IF NOT EXISTS 
+    upgrade_caps_created_tx_idx ON upgrade_caps USING HASH (created_tx_digest);
+
+CREATE INDEX IF NOT EXISTS 
+    upgrade_caps_destroyed_tx_idx ON upgrade_caps USING HASH (destroyed_tx_digest);
+
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_transfers_tx_idx ON upgrade_cap_transfers USING HASH (tx_digest);
+
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_versions_tx_idx ON upgrade_cap_versions USING HASH (tx_digest);
+
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_policy_changes_tx_idx ON upgrade_cap_policy_changes USING HASH (tx_digest);
+
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_holders_holder_idx ON upgrade_cap_holders USING HASH (holder_id);
@@ -0,0 +1,3 @@
+-- Your SQL goes here
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_versions_seq_idx ON upgrade_cap_versions (seq_checkpoint DESC);
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE upgrade_cap_state DROP COLUMN IF EXISTS owner_checkpoint;
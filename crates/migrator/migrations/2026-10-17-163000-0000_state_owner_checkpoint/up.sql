@@ -0,0 +1,9 @@
+-- Your SQL goes here
+-- pipelines commit independently, the owner is only replaced by a transfer
+-- from a later checkpoint than the one it was set at.
+ALTER TABLE upgrade_cap_state ADD COLUMN IF NOT EXISTS owner_checkpoint BIGINT NOT NULL DEFAULT -1;
+
+UPDATE upgrade_cap_state s
+SET owner_checkpoint = t.seq_checkpoint
+FROM upgrade_cap_transfers t
+WHERE t.object_id = s.object_id AND t.tx_digest = s.owner_tx_digest;
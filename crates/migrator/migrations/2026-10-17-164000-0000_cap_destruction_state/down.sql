@@ -0,0 +1,24 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS wrapped_upgrade_caps;
+
+ALTER TABLE upgrade_caps
+    ADD COLUMN IF NOT EXISTS destroyed_seq_checkpoint BIGINT,
+    ADD COLUMN IF NOT EXISTS destroyed_tx_digest TEXT,
+    ADD COLUMN IF NOT EXISTS destroyed_at TIMESTAMPTZ;
+
+UPDATE upgrade_caps c
+SET destroyed_seq_checkpoint = s.destroyed_seq_checkpoint,
+    destroyed_tx_digest = s.destroyed_tx_digest,
+    destroyed_at = s.destroyed_at
+FROM upgrade_cap_state s
+WHERE s.object_id = c.object_id AND s.destroyed_tx_digest IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS 
+    upgrade_caps_destroyed_tx_idx ON upgrade_caps USING HASH (destroyed_tx_digest);
+
+DROP INDEX IF EXISTS upgrade_cap_state_destroyed_tx_idx;
+
+ALTER TABLE upgrade_cap_state
+    DROP COLUMN IF EXISTS destroyed_seq_checkpoint,
+    DROP COLUMN IF EXISTS destroyed_tx_digest,
+    DROP COLUMN IF EXISTS destroyed_at;
@@ -0,0 +1,37 @@
+-- Your SQL goes here
+-- destructions are merged into `upgrade_cap_state` like every other change,
+-- whether the created or the destroy pipeline commits first.
+ALTER TABLE upgrade_cap_state
+    ADD COLUMN IF NOT EXISTS destroyed_seq_checkpoint BIGINT,
+    ADD COLUMN IF NOT EXISTS destroyed_tx_digest TEXT,
+    ADD COLUMN IF NOT EXISTS destroyed_at TIMESTAMPTZ;
+
+UPDATE upgrade_cap_state s
+SET destroyed_seq_checkpoint = c.destroyed_seq_checkpoint,
+    destroyed_tx_digest = c.destroyed_tx_digest,
+    destroyed_at = c.destroyed_at
+FROM upgrade_caps c
+WHERE c.object_id = s.object_id AND c.destroyed_tx_digest IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS 
+    upgrade_cap_state_destroyed_tx_idx ON upgrade_cap_state USING HASH (destroyed_tx_digest);
+
+DROP INDEX IF EXISTS upgrade_caps_destroyed_tx_idx;
+
+ALTER TABLE upgrade_caps
+    DROP COLUMN IF EXISTS destroyed_seq_checkpoint,
+    DROP COLUMN IF EXISTS destroyed_tx_digest,
+    DROP COLUMN IF EXISTS destroyed_at;
+
+-- caps wrapped in another object, maintained by the destroy pipeline alone:
+-- once unwrapped and deleted in one transaction their type isn't in the checkpoint.
+CREATE TABLE IF NOT EXISTS wrapped_upgrade_caps (
+    object_id TEXT PRIMARY KEY,
+    seq_checkpoint BIGINT NOT NULL
+);
+
+INSERT INTO wrapped_upgrade_caps
+SELECT object_id, owner_checkpoint
+FROM upgrade_cap_state
+WHERE owner_address = 'wrapped' AND NOT destroyed
+ON CONFLICT (object_id) DO NOTHING;
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+UPDATE package_dependencies
+SET dependency_id = '0x' || lpad(substr(dependency_id, 3), 64, '0');
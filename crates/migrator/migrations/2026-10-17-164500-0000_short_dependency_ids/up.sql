@@ -0,0 +1,16 @@
+-- Your SQL goes here
+-- dependency ids kept their leading zeros, unlike every other package id.
+UPDATE package_dependencies
+SET dependency_id = '0x' || COALESCE(NULLIF(ltrim(substr(dependency_id, 3), '0'), ''), '0'),
+    linked_id = '0x' || COALESCE(NULLIF(ltrim(substr(linked_id, 3), '0'), ''), '0');
+
+-- a package whose id had leading zeros was recorded as its own dependency.
+DELETE FROM package_dependencies d
+USING packages p
+WHERE p.package_id = d.package_id AND p.original_id = d.dependency_id;
+
+-- and its dependencies missed their linked version.
+UPDATE package_dependencies d
+SET linked_id = l.upgraded_id
+FROM package_linkage l
+WHERE l.package_id = d.package_id AND l.dependency_id = d.dependency_id;
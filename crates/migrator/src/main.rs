@@ -1,13 +1,12 @@
 use diesel::PgConnection;
 use diesel::prelude::*;
-use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
+use diesel_migrations::MigrationHarness;
 use log::info;
+use phantom_cap_db::{MIGRATIONS, Network};
 use url::Url;
 
 type Db = diesel::pg::Pg;
 
-const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
-
 fn main() {
     dotenvy::dotenv().ok();
     env_logger::init();
@@ -17,12 +16,28 @@ fn main() {
         .parse::<Url>()
         .expect("Invalid database URL");
 
+    let networks = Network::parse_list(
+        &std::env::var("NETWORKS").unwrap_or_else(|_| Network::Mainnet.to_string()),
+    )
+    .expect("Invalid NETWORKS");
+
     let mut conn =
         PgConnection::establish(database_url.as_str()).expect("Failed to connect to database");
 
-    info!("Running migrations ...");
+    for network in networks {
+        diesel::sql_query(format!("CREATE SCHEMA IF NOT EXISTS {}", network.schema()))
+            .execute(&mut conn)
+            .expect("Could not create network schema");
+
+        // Migrations are unqualified, the connection's search_path picks the schema.
+        let mut network_conn =
+            PgConnection::establish(network.database_url(&database_url).as_str())
+                .expect("Failed to connect to database");
+
+        info!("Running {} migrations ...", network);
 
-    run_db_migrations(&mut conn);
+        run_db_migrations(&mut network_conn);
+    }
 
     info!("Migrations complete.");
 }
@@ -0,0 +1,30 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use move_core_types::account_address::AccountAddress;
+
+use crate::deserialize;
+
+/// Packages the modules refer to, by original id, leaving out the package itself.
+/// The linkage table holds every transitive dependency, this only keeps direct ones.
+/// Ids are hex literals without leading zeros, like every other id the indexer stores.
+pub fn direct_dependencies(
+    modules: &BTreeMap<String, Vec<u8>>,
+    self_id: &str,
+) -> anyhow::Result<BTreeSet<String>> {
+    let self_id = AccountAddress::from_hex_literal(self_id)
+        .map_err(|e| anyhow::anyhow!("Invalid package id {}: {}", self_id, e))?;
+    let mut dependencies = BTreeSet::new();
+
+    for (name, bytecode) in modules {
+        let module = deserialize(name, bytecode)?;
+
+        for handle in module.module_handles() {
+            let address = module.address_identifier_at(handle.address);
+            if *address != self_id {
+                dependencies.insert(address.to_hex_literal());
+            }
+        }
+    }
+
+    Ok(dependencies)
+}
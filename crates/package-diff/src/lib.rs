@@ -0,0 +1,399 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use move_binary_format::CompiledModule;
+use move_binary_format::file_format::{
+    AbilitySet, DatatypeHandleIndex, FieldDefinition, SignatureToken, StructFieldInformation,
+    Visibility,
+};
+use serde::Serialize;
+
+mod deps;
+mod upgrade;
+
+pub use deps::direct_dependencies;
+pub use upgrade::{UpgradeClass, classify_upgrade};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Changed,
+    Unchanged,
+}
+
+impl ChangeKind {
+    pub fn badge_class(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "badge-best",
+            ChangeKind::Removed => "badge-high",
+            ChangeKind::Changed => "badge-medium",
+            ChangeKind::Unchanged => "badge-neutral",
+        }
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChangeKind::Added => write!(f, "Added"),
+            ChangeKind::Removed => write!(f, "Removed"),
+            ChangeKind::Changed => write!(f, "Changed"),
+            ChangeKind::Unchanged => write!(f, "Unchanged"),
+        }
+    }
+}
+
+/// Signature change of a function or struct, `old`/`new` are `None` when added/removed.
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemChange {
+    pub name: String,
+    pub kind: ChangeKind,
+    pub old: Option<String>,
+    pub new: Option<String>,
+    /// Function is `public` or `entry` in either version, callers can observe the change.
+    pub exposed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleDiff {
+    pub name: String,
+    pub kind: ChangeKind,
+    pub functions: Vec<ItemChange>,
+    pub structs: Vec<ItemChange>,
+    pub enums: Vec<ItemChange>,
+}
+
+impl ModuleDiff {
+    /// Whether any `public` or `entry` function changed.
+    pub fn exposed_changes(&self) -> bool {
+        self.functions.iter().any(|f| f.exposed)
+    }
+}
+
+struct Item {
+    signature: String,
+    exposed: bool,
+}
+
+/// Diffs two packages given their module name to bytecode maps.
+pub fn diff_packages(
+    old: &BTreeMap<String, Vec<u8>>,
+    new: &BTreeMap<String, Vec<u8>>,
+) -> anyhow::Result<Vec<ModuleDiff>> {
+    let names = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let old_module = old.get(name).map(|b| deserialize(name, b)).transpose()?;
+            let new_module = new.get(name).map(|b| deserialize(name, b)).transpose()?;
+
+            let functions = diff_items(
+                &old_module.as_ref().map(functions).unwrap_or_default(),
+                &new_module.as_ref().map(functions).unwrap_or_default(),
+            );
+            let structs = diff_items(
+                &old_module.as_ref().map(structs).unwrap_or_default(),
+                &new_module.as_ref().map(structs).unwrap_or_default(),
+            );
+            let enums = diff_items(
+                &old_module.as_ref().map(enums).unwrap_or_default(),
+                &new_module.as_ref().map(enums).unwrap_or_default(),
+            );
+
+            let kind = match (old.get(name), new.get(name)) {
+                (None, _) => ChangeKind::Added,
+                (_, None) => ChangeKind::Removed,
+                // function bodies may change without touching any signature.
+                (Some(old_bytes), Some(new_bytes))
+                    if old_bytes != new_bytes
+                        || !functions.is_empty()
+                        || !structs.is_empty()
+                        || !enums.is_empty() =>
+                {
+                    ChangeKind::Changed
+                }
+                _ => ChangeKind::Unchanged,
+            };
+
+            Ok(ModuleDiff {
+                name: name.clone(),
+                kind,
+                functions,
+                structs,
+                enums,
+            })
+        })
+        .collect()
+}
+
+fn deserialize(name: &str, bytecode: &[u8]) -> anyhow::Result<CompiledModule> {
+    CompiledModule::deserialize_with_defaults(bytecode)
+        .map_err(|e| anyhow::anyhow!("Failed to deserialize module {}: {:?}", name, e))
+}
+
+fn diff_items(old: &BTreeMap<String, Item>, new: &BTreeMap<String, Item>) -> Vec<ItemChange> {
+    let names = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let old_item = old.get(name);
+            let new_item = new.get(name);
+
+            let kind = match (old_item, new_item) {
+                (None, Some(_)) => ChangeKind::Added,
+                (Some(_), None) => ChangeKind::Removed,
+                (Some(o), Some(n)) if o.signature != n.signature => ChangeKind::Changed,
+                _ => return None,
+            };
+
+            Some(ItemChange {
+                name: name.clone(),
+                kind,
+                old: old_item.map(|i| i.signature.clone()),
+                new: new_item.map(|i| i.signature.clone()),
+                exposed: old_item.is_some_and(|i| i.exposed) || new_item.is_some_and(|i| i.exposed),
+            })
+        })
+        .collect()
+}
+
+fn functions(module: &CompiledModule) -> BTreeMap<String, Item> {
+    module
+        .function_defs()
+        .iter()
+        .map(|def| {
+            let handle = module.function_handle_at(def.function);
+            let name = module.identifier_at(handle.name).to_string();
+
+            let visibility = match def.visibility {
+                Visibility::Public => "public ",
+                Visibility::Friend => "public(package) ",
+                Visibility::Private => "",
+            };
+            let entry = if def.is_entry { "entry " } else { "" };
+
+            let params = module
+                .signature_at(handle.parameters)
+                .0
+                .iter()
+                .map(|t| type_name(module, t))
+                .collect::<Vec<_>>();
+            let returns = module
+                .signature_at(handle.return_)
+                .0
+                .iter()
+                .map(|t| type_name(module, t))
+                .collect::<Vec<_>>();
+            let returns = match returns.len() {
+                0 => String::new(),
+                1 => format!(": {}", returns[0]),
+                _ => format!(": ({})", returns.join(", ")),
+            };
+
+            let type_params = type_params(
+                handle
+                    .type_parameters
+                    .iter()
+                    .map(|constraints| (false, *constraints)),
+            );
+
+            let signature = format!(
+                "{}{}fun {}{}({}){}",
+                visibility,
+                entry,
+                name,
+                type_params,
+                params.join(", "),
+                returns
+            );
+
+            let item = Item {
+                signature,
+                exposed: def.visibility == Visibility::Public || def.is_entry,
+            };
+
+            (name, item)
+        })
+        .collect()
+}
+
+fn structs(module: &CompiledModule) -> BTreeMap<String, Item> {
+    module
+        .struct_defs()
+        .iter()
+        .map(|def| {
+            let handle = module.datatype_handle_at(def.struct_handle);
+            let name = module.identifier_at(handle.name).to_string();
+
+            let fields = match &def.field_information {
+                StructFieldInformation::Native => "native".to_string(),
+                StructFieldInformation::Declared(fields) => fields_signature(module, fields),
+            };
+
+            let item = Item {
+                signature: format!(
+                    "struct {}{}{} {{ {} }}",
+                    name,
+                    datatype_params(module, def.struct_handle),
+                    datatype_abilities(module, def.struct_handle),
+                    fields
+                ),
+                exposed: false,
+            };
+
+            (name, item)
+        })
+        .collect()
+}
+
+fn enums(module: &CompiledModule) -> BTreeMap<String, Item> {
+    module
+        .enum_defs()
+        .iter()
+        .map(|def| {
+            let handle = module.datatype_handle_at(def.enum_handle);
+            let name = module.identifier_at(handle.name).to_string();
+
+            let variants = def
+                .variants
+                .iter()
+                .map(|v| {
+                    let variant = module.identifier_at(v.variant_name);
+                    if v.fields.is_empty() {
+                        variant.to_string()
+                    } else {
+                        format!("{} {{ {} }}", variant, fields_signature(module, &v.fields))
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            let item = Item {
+                signature: format!(
+                    "enum {}{}{} {{ {} }}",
+                    name,
+                    datatype_params(module, def.enum_handle),
+                    datatype_abilities(module, def.enum_handle),
+                    variants
+                ),
+                exposed: false,
+            };
+
+            (name, item)
+        })
+        .collect()
+}
+
+fn datatype_params(module: &CompiledModule, idx: DatatypeHandleIndex) -> String {
+    type_params(
+        module
+            .datatype_handle_at(idx)
+            .type_parameters
+            .iter()
+            .map(|p| (p.is_phantom, p.constraints)),
+    )
+}
+
+fn datatype_abilities(module: &CompiledModule, idx: DatatypeHandleIndex) -> String {
+    let abilities = abilities(module.datatype_handle_at(idx).abilities);
+    if abilities.is_empty() {
+        String::new()
+    } else {
+        format!(" has {}", abilities.join(", "))
+    }
+}
+
+fn fields_signature(module: &CompiledModule, fields: &[FieldDefinition]) -> String {
+    fields
+        .iter()
+        .map(|f| {
+            format!(
+                "{}: {}",
+                module.identifier_at(f.name),
+                type_name(module, &f.signature.0)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn abilities(set: AbilitySet) -> Vec<&'static str> {
+    [
+        (set.has_copy(), "copy"),
+        (set.has_drop(), "drop"),
+        (set.has_store(), "store"),
+        (set.has_key(), "key"),
+    ]
+    .into_iter()
+    .filter_map(|(has, name)| has.then_some(name))
+    .collect()
+}
+
+fn type_params(params: impl Iterator<Item = (bool, AbilitySet)>) -> String {
+    let params = params
+        .enumerate()
+        .map(|(i, (is_phantom, constraints))| {
+            let phantom = if is_phantom { "phantom " } else { "" };
+            let constraints = abilities(constraints);
+            if constraints.is_empty() {
+                format!("{}T{}", phantom, i)
+            } else {
+                format!("{}T{}: {}", phantom, i, constraints.join(" + "))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", params.join(", "))
+    }
+}
+
+fn datatype_name(module: &CompiledModule, idx: DatatypeHandleIndex) -> String {
+    let handle = module.datatype_handle_at(idx);
+    let module_handle = module.module_handle_at(handle.module);
+
+    format!(
+        "{}::{}::{}",
+        module
+            .address_identifier_at(module_handle.address)
+            .to_hex_literal(),
+        module.identifier_at(module_handle.name),
+        module.identifier_at(handle.name)
+    )
+}
+
+fn type_name(module: &CompiledModule, token: &SignatureToken) -> String {
+    match token {
+        SignatureToken::Bool => "bool".to_string(),
+        SignatureToken::U8 => "u8".to_string(),
+        SignatureToken::U16 => "u16".to_string(),
+        SignatureToken::U32 => "u32".to_string(),
+        SignatureToken::U64 => "u64".to_string(),
+        SignatureToken::U128 => "u128".to_string(),
+        SignatureToken::U256 => "u256".to_string(),
+        SignatureToken::Address => "address".to_string(),
+        SignatureToken::Signer => "signer".to_string(),
+        SignatureToken::Vector(inner) => format!("vector<{}>", type_name(module, inner)),
+        SignatureToken::Datatype(idx) => datatype_name(module, *idx),
+        SignatureToken::DatatypeInstantiation(inst) => {
+            let (idx, type_args) = &**inst;
+            let type_args = type_args
+                .iter()
+                .map(|t| type_name(module, t))
+                .collect::<Vec<_>>();
+            format!("{}<{}>", datatype_name(module, *idx), type_args.join(", "))
+        }
+        SignatureToken::Reference(inner) => format!("&{}", type_name(module, inner)),
+        SignatureToken::MutableReference(inner) => format!("&mut {}", type_name(module, inner)),
+        SignatureToken::TypeParameter(idx) => format!("T{}", idx),
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,419 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use move_binary_format::CompiledModule;
+use move_binary_format::file_format::{
+    AbilitySet, AddressIdentifierIndex, Bytecode, CodeUnit, Constant, ConstantPoolIndex,
+    DatatypeHandle, DatatypeHandleIndex, EnumDefinition, FieldDefinition, FunctionDefinition,
+    FunctionHandle, FunctionHandleIndex, IdentifierIndex, ModuleHandle, ModuleHandleIndex,
+    Signature, SignatureIndex, SignatureToken, StructDefinition, StructFieldInformation,
+    TypeSignature, VariantDefinition, Visibility, empty_module,
+};
+use move_core_types::account_address::AccountAddress;
+use move_core_types::identifier::Identifier;
+
+use crate::{ChangeKind, UpgradeClass, classify_upgrade, diff_packages, direct_dependencies};
+
+/// Module built item by item, each item is appended to the end of its pool.
+struct ModuleBuilder(CompiledModule);
+
+impl ModuleBuilder {
+    fn identifier(&mut self, name: &str) -> IdentifierIndex {
+        self.0.identifiers.push(Identifier::new(name).unwrap());
+        IdentifierIndex(self.0.identifiers.len() as u16 - 1)
+    }
+
+    fn signature(&mut self, tokens: Vec<SignatureToken>) -> SignatureIndex {
+        self.0.signatures.push(Signature(tokens));
+        SignatureIndex(self.0.signatures.len() as u16 - 1)
+    }
+
+    fn module_handle(&mut self, address: &str, name: &str) -> ModuleHandleIndex {
+        self.0
+            .address_identifiers
+            .push(AccountAddress::from_hex_literal(address).unwrap());
+        let address = AddressIdentifierIndex(self.0.address_identifiers.len() as u16 - 1);
+        let name = self.identifier(name);
+        self.0.module_handles.push(ModuleHandle { address, name });
+        ModuleHandleIndex(self.0.module_handles.len() as u16 - 1)
+    }
+
+    fn function_handle(
+        &mut self,
+        module: ModuleHandleIndex,
+        name: &str,
+        params: Vec<SignatureToken>,
+    ) -> FunctionHandleIndex {
+        let name = self.identifier(name);
+        let parameters = self.signature(params);
+        let return_ = self.signature(vec![]);
+        self.0.function_handles.push(FunctionHandle {
+            module,
+            name,
+            parameters,
+            return_,
+            type_parameters: vec![],
+        });
+        FunctionHandleIndex(self.0.function_handles.len() as u16 - 1)
+    }
+
+    /// Call of a function of another package.
+    fn call(&mut self, address: &str, module: &str, name: &str) -> Bytecode {
+        let module = self.module_handle(address, module);
+        Bytecode::Call(self.function_handle(module, name, vec![]))
+    }
+
+    fn constant(&mut self, value: u64) -> Bytecode {
+        self.0.constant_pool.push(Constant {
+            type_: SignatureToken::U64,
+            data: value.to_le_bytes().to_vec(),
+        });
+        Bytecode::LdConst(ConstantPoolIndex(self.0.constant_pool.len() as u16 - 1))
+    }
+
+    fn function(
+        &mut self,
+        name: &str,
+        visibility: Visibility,
+        params: Vec<SignatureToken>,
+        code: Vec<Bytecode>,
+    ) {
+        let function = self.function_handle(self.0.self_module_handle_idx, name, params);
+        let locals = self.signature(vec![]);
+        self.0.function_defs.push(FunctionDefinition {
+            function,
+            visibility,
+            is_entry: false,
+            acquires_global_resources: vec![],
+            code: Some(CodeUnit {
+                locals,
+                code,
+                jump_tables: vec![],
+            }),
+        });
+    }
+
+    fn datatype(&mut self, name: &str) -> DatatypeHandleIndex {
+        let name = self.identifier(name);
+        self.0.datatype_handles.push(DatatypeHandle {
+            module: self.0.self_module_handle_idx,
+            name,
+            abilities: AbilitySet::EMPTY,
+            type_parameters: vec![],
+        });
+        DatatypeHandleIndex(self.0.datatype_handles.len() as u16 - 1)
+    }
+
+    fn fields(&mut self, fields: &[(&str, SignatureToken)]) -> Vec<FieldDefinition> {
+        fields
+            .iter()
+            .map(|(name, token)| FieldDefinition {
+                name: self.identifier(name),
+                signature: TypeSignature(token.clone()),
+            })
+            .collect()
+    }
+
+    fn struct_(&mut self, name: &str, fields: &[(&str, SignatureToken)]) {
+        let struct_handle = self.datatype(name);
+        let fields = self.fields(fields);
+        self.0.struct_defs.push(StructDefinition {
+            struct_handle,
+            field_information: StructFieldInformation::Declared(fields),
+        });
+    }
+
+    fn enum_(&mut self, name: &str, variants: &[&str]) {
+        let enum_handle = self.datatype(name);
+        let variants = variants
+            .iter()
+            .map(|variant| VariantDefinition {
+                variant_name: self.identifier(variant),
+                fields: vec![],
+            })
+            .collect();
+        self.0.enum_defs.push(EnumDefinition {
+            enum_handle,
+            variants,
+        });
+    }
+}
+
+fn module(build: impl FnOnce(&mut ModuleBuilder)) -> Vec<u8> {
+    let mut builder = ModuleBuilder(empty_module());
+    build(&mut builder);
+
+    let mut bytes = vec![];
+    builder
+        .0
+        .serialize_with_version(builder.0.version, &mut bytes)
+        .unwrap();
+    bytes
+}
+
+fn package(modules: &[(&str, &Vec<u8>)]) -> BTreeMap<String, Vec<u8>> {
+    modules
+        .iter()
+        .map(|(name, bytes)| (name.to_string(), bytes.to_vec()))
+        .collect()
+}
+
+/// `public fun value(params)` loading `constant` and calling `0x2::coin::{callee}`.
+fn value_function(m: &mut ModuleBuilder, constant: u64, callee: &str, params: Vec<SignatureToken>) {
+    let constant = m.constant(constant);
+    let call = m.call("0x2", "coin", callee);
+    m.function(
+        "value",
+        Visibility::Public,
+        params,
+        vec![constant, Bytecode::Pop, call, Bytecode::Ret],
+    );
+}
+
+fn base(m: &mut ModuleBuilder) {
+    value_function(m, 1, "value", vec![]);
+}
+
+#[test]
+fn diff_reports_module_and_item_changes() {
+    let old = module(|m| {
+        base(m);
+        m.struct_("Pool", &[("balance", SignatureToken::U64)]);
+        m.enum_("State", &["Open"]);
+    });
+    let new = module(|m| {
+        value_function(m, 1, "value", vec![SignatureToken::U64]);
+        m.function("helper", Visibility::Private, vec![], vec![Bytecode::Ret]);
+        m.struct_("Pool", &[("balance", SignatureToken::U128)]);
+        m.enum_("State", &["Open", "Closed"]);
+    });
+    let same = module(base);
+
+    let diff = diff_packages(
+        &package(&[("pool", &old), ("gone", &same), ("kept", &same)]),
+        &package(&[("pool", &new), ("added", &same), ("kept", &same)]),
+    )
+    .unwrap();
+
+    let kinds = diff
+        .iter()
+        .map(|m| (m.name.as_str(), m.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            ("added", ChangeKind::Added),
+            ("gone", ChangeKind::Removed),
+            ("kept", ChangeKind::Unchanged),
+            ("pool", ChangeKind::Changed),
+        ]
+    );
+
+    let pool = &diff[3];
+    let functions = pool
+        .functions
+        .iter()
+        .map(|f| (f.name.as_str(), f.kind, f.exposed))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        functions,
+        [
+            ("helper", ChangeKind::Added, false),
+            ("value", ChangeKind::Changed, true),
+        ]
+    );
+    assert!(pool.exposed_changes());
+    assert_eq!(pool.structs.len(), 1);
+    assert_eq!(pool.structs[0].kind, ChangeKind::Changed);
+    assert_eq!(pool.enums.len(), 1);
+    assert_eq!(pool.enums[0].kind, ChangeKind::Changed);
+    assert_eq!(
+        pool.enums[0].new.as_deref(),
+        Some("enum State { Open, Closed }")
+    );
+}
+
+#[test]
+fn diff_reports_body_only_changes() {
+    let old = module(|m| {
+        let one = m.constant(1);
+        m.function(
+            "f",
+            Visibility::Public,
+            vec![],
+            vec![one, Bytecode::Pop, Bytecode::Ret],
+        );
+    });
+    let new = module(|m| {
+        let two = m.constant(2);
+        m.function(
+            "f",
+            Visibility::Public,
+            vec![],
+            vec![two, Bytecode::Pop, Bytecode::Ret],
+        );
+    });
+
+    let diff = diff_packages(&package(&[("m", &old)]), &package(&[("m", &new)])).unwrap();
+
+    assert_eq!(diff[0].kind, ChangeKind::Changed);
+    assert!(diff[0].functions.is_empty() && diff[0].structs.is_empty());
+}
+
+#[test]
+fn identical_modules_are_dep_only() {
+    let old = module(base);
+    let class = classify_upgrade(&package(&[("m", &old)]), &package(&[("m", &old)])).unwrap();
+
+    assert_eq!(class, UpgradeClass::DepOnly);
+}
+
+#[test]
+fn new_items_are_additive() {
+    let old = module(base);
+    // the new items come first so that the pool indices of the kept ones shift.
+    let new = module(|m| {
+        let zero = m.constant(0);
+        m.function(
+            "zero",
+            Visibility::Public,
+            vec![],
+            vec![zero, Bytecode::Pop, Bytecode::Ret],
+        );
+        m.struct_("Pool", &[("balance", SignatureToken::U64)]);
+        m.enum_("State", &["Open"]);
+        base(m);
+    });
+    let other = module(base);
+
+    let class = classify_upgrade(
+        &package(&[("m", &old)]),
+        &package(&[("m", &new), ("other", &other)]),
+    )
+    .unwrap();
+
+    assert_eq!(class, UpgradeClass::Additive);
+}
+
+#[test]
+fn shifted_operands_are_additive() {
+    let old = module(base);
+    // the same constant and call, at other pool indices.
+    let new = module(|m| {
+        m.constant(7);
+        m.call("0x2", "coin", "zero");
+        base(m);
+    });
+
+    let class = classify_upgrade(&package(&[("m", &old)]), &package(&[("m", &new)])).unwrap();
+
+    assert_eq!(class, UpgradeClass::Additive);
+}
+
+#[test]
+fn changed_items_are_compatible() {
+    let old = module(|m| {
+        base(m);
+        m.struct_("Pool", &[("balance", SignatureToken::U64)]);
+        m.enum_("State", &["Open"]);
+    });
+    let cases: [(&str, Vec<u8>); 5] = [
+        (
+            "call target",
+            module(|m| {
+                value_function(m, 1, "zero", vec![]);
+                m.struct_("Pool", &[("balance", SignatureToken::U64)]);
+                m.enum_("State", &["Open"]);
+            }),
+        ),
+        (
+            "constant value",
+            module(|m| {
+                value_function(m, 2, "value", vec![]);
+                m.struct_("Pool", &[("balance", SignatureToken::U64)]);
+                m.enum_("State", &["Open"]);
+            }),
+        ),
+        (
+            "struct field",
+            module(|m| {
+                base(m);
+                m.struct_("Pool", &[("balance", SignatureToken::U128)]);
+                m.enum_("State", &["Open"]);
+            }),
+        ),
+        (
+            "enum variant",
+            module(|m| {
+                base(m);
+                m.struct_("Pool", &[("balance", SignatureToken::U64)]);
+                m.enum_("State", &["Open", "Closed"]);
+            }),
+        ),
+        (
+            "function signature",
+            module(|m| {
+                value_function(m, 1, "value", vec![SignatureToken::Bool]);
+                m.struct_("Pool", &[("balance", SignatureToken::U64)]);
+                m.enum_("State", &["Open"]);
+            }),
+        ),
+    ];
+
+    for (case, new) in cases {
+        let class = classify_upgrade(&package(&[("m", &old)]), &package(&[("m", &new)])).unwrap();
+        assert_eq!(class, UpgradeClass::Compatible, "{}", case);
+    }
+
+    let removed = classify_upgrade(
+        &package(&[("m", &old), ("other", &old)]),
+        &package(&[("m", &old)]),
+    )
+    .unwrap();
+    assert_eq!(removed, UpgradeClass::Compatible, "removed module");
+}
+
+#[test]
+fn direct_dependencies_leave_out_the_package() {
+    let self_id = AccountAddress::ZERO.to_hex_literal();
+    let coin = module(base);
+    let token = module(|m| {
+        let call = m.call("0x2", "balance", "zero");
+        let other = m.call("0xabc", "oracle", "price");
+        m.function(
+            "f",
+            Visibility::Public,
+            vec![],
+            vec![call, other, Bytecode::Ret],
+        );
+    });
+
+    let dependencies =
+        direct_dependencies(&package(&[("coin", &coin), ("token", &token)]), &self_id).unwrap();
+
+    assert_eq!(
+        dependencies,
+        BTreeSet::from(["0x2".to_string(), "0xabc".to_string()])
+    );
+}
+
+#[test]
+fn direct_dependencies_match_leading_zero_ids() {
+    // the indexer stores `to_hex_literal` ids, which drop the leading zero.
+    let self_id = "0x0c1";
+    let dependency = "0x0000000000000000000000000000000000000000000000000000000000000abc";
+    let pool = module(|m| {
+        m.0.address_identifiers[0] = AccountAddress::from_hex_literal(self_id).unwrap();
+        let own = m.call(self_id, "pool", "price");
+        let other = m.call(dependency, "oracle", "price");
+        m.function(
+            "f",
+            Visibility::Public,
+            vec![],
+            vec![own, other, Bytecode::Ret],
+        );
+    });
+
+    let dependencies = direct_dependencies(&package(&[("pool", &pool)]), "0xc1").unwrap();
+
+    assert_eq!(dependencies, BTreeSet::from(["0xabc".to_string()]));
+}
@@ -0,0 +1,297 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use move_binary_format::CompiledModule;
+use move_binary_format::file_format::{
+    Bytecode, EnumDefinition, FieldHandleIndex, FunctionHandleIndex, SignatureIndex,
+    StructDefinitionIndex, StructFieldInformation, VariantHandle, VariantInstantiationHandle,
+    VariantTag,
+};
+use serde::Serialize;
+
+use crate::{datatype_name, deserialize, enums, functions, structs, type_name};
+
+/// Least permissive upgrade policy an upgrade would have passed, ordered
+/// from the most to the least restrictive one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeClass {
+    DepOnly,
+    Additive,
+    Compatible,
+}
+
+impl fmt::Display for UpgradeClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UpgradeClass::DepOnly => write!(f, "DepOnly"),
+            UpgradeClass::Additive => write!(f, "Additive"),
+            UpgradeClass::Compatible => write!(f, "Compatible"),
+        }
+    }
+}
+
+/// Classifies the upgrade from `old` to `new` given their module name to bytecode maps.
+///
+/// - dep-only: modules are byte for byte the same, only the linkage changed.
+/// - additive: existing modules keep every struct, enum and function, signature and body.
+/// - compatible: anything else the compatibility check allows.
+pub fn classify_upgrade(
+    old: &BTreeMap<String, Vec<u8>>,
+    new: &BTreeMap<String, Vec<u8>>,
+) -> anyhow::Result<UpgradeClass> {
+    if old == new {
+        return Ok(UpgradeClass::DepOnly);
+    }
+
+    for (name, old_bytes) in old {
+        let Some(new_bytes) = new.get(name) else {
+            return Ok(UpgradeClass::Compatible);
+        };
+        if old_bytes == new_bytes {
+            continue;
+        }
+
+        let old_module = deserialize(name, old_bytes)?;
+        let new_module = deserialize(name, new_bytes)?;
+
+        if !is_superset(&old_module, &new_module) {
+            return Ok(UpgradeClass::Compatible);
+        }
+    }
+
+    Ok(UpgradeClass::Additive)
+}
+
+/// Whether `new` keeps every struct, enum and function of `old` unchanged.
+fn is_superset(old: &CompiledModule, new: &CompiledModule) -> bool {
+    let new_structs = structs(new);
+    let structs_kept = structs(old).iter().all(|(name, item)| {
+        new_structs
+            .get(name)
+            .is_some_and(|n| n.signature == item.signature)
+    });
+
+    let new_enums = enums(new);
+    let enums_kept = enums(old).iter().all(|(name, item)| {
+        new_enums
+            .get(name)
+            .is_some_and(|n| n.signature == item.signature)
+    });
+
+    let new_functions = functions(new);
+    let functions_kept = functions(old).iter().all(|(name, item)| {
+        new_functions
+            .get(name)
+            .is_some_and(|n| n.signature == item.signature)
+    });
+
+    let new_bodies = bodies(new);
+    let bodies_kept = bodies(old)
+        .iter()
+        .all(|(name, body)| new_bodies.get(name) == Some(body));
+
+    structs_kept && enums_kept && functions_kept && bodies_kept
+}
+
+/// Locals and instructions of each function body. Pool indices shift whenever
+/// new items are added to a module, so operands are resolved to the functions,
+/// types, fields and constants they point to.
+fn bodies(module: &CompiledModule) -> BTreeMap<String, Vec<String>> {
+    module
+        .function_defs()
+        .iter()
+        .map(|def| {
+            let handle = module.function_handle_at(def.function);
+            let name = module.identifier_at(handle.name).to_string();
+            let body = def
+                .code
+                .as_ref()
+                .map(|code| {
+                    let jump_tables = code.jump_tables.iter().map(|table| {
+                        let def = module.enum_def_at(table.head_enum);
+                        format!(
+                            "JumpTable {} {:?}",
+                            datatype_name(module, def.enum_handle),
+                            table.jump_table
+                        )
+                    });
+
+                    std::iter::once(format!("locals {}", signature(module, code.locals)))
+                        .chain(jump_tables)
+                        .chain(code.code.iter().map(|i| instruction(module, i)))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            (name, body)
+        })
+        .collect()
+}
+
+fn instruction(module: &CompiledModule, instruction: &Bytecode) -> String {
+    match instruction {
+        Bytecode::LdConst(idx) => {
+            let constant = module.constant_at(*idx);
+            format!(
+                "LdConst {} {:?}",
+                type_name(module, &constant.type_),
+                constant.data
+            )
+        }
+        Bytecode::Call(idx) => format!("Call {}", function_name(module, *idx)),
+        Bytecode::CallGeneric(idx) => {
+            let inst = module.function_instantiation_at(*idx);
+            format!(
+                "CallGeneric {}<{}>",
+                function_name(module, inst.handle),
+                signature(module, inst.type_parameters)
+            )
+        }
+        Bytecode::Pack(idx) => format!("Pack {}", struct_name(module, *idx)),
+        Bytecode::Unpack(idx) => format!("Unpack {}", struct_name(module, *idx)),
+        Bytecode::PackGeneric(idx) | Bytecode::UnpackGeneric(idx) => {
+            let inst = module.struct_instantiation_at(*idx);
+            format!(
+                "{} {}<{}>",
+                opcode(instruction),
+                struct_name(module, inst.def),
+                signature(module, inst.type_parameters)
+            )
+        }
+        Bytecode::MutBorrowField(idx) | Bytecode::ImmBorrowField(idx) => {
+            format!("{} {}", opcode(instruction), field_name(module, *idx))
+        }
+        Bytecode::MutBorrowFieldGeneric(idx) | Bytecode::ImmBorrowFieldGeneric(idx) => {
+            let inst = module.field_instantiation_at(*idx);
+            format!(
+                "{} {}<{}>",
+                opcode(instruction),
+                field_name(module, inst.handle),
+                signature(module, inst.type_parameters)
+            )
+        }
+        Bytecode::VecPack(idx, len) | Bytecode::VecUnpack(idx, len) => {
+            format!(
+                "{}<{}> {}",
+                opcode(instruction),
+                signature(module, *idx),
+                len
+            )
+        }
+        Bytecode::VecLen(idx)
+        | Bytecode::VecImmBorrow(idx)
+        | Bytecode::VecMutBorrow(idx)
+        | Bytecode::VecPushBack(idx)
+        | Bytecode::VecPopBack(idx)
+        | Bytecode::VecSwap(idx) => {
+            format!("{}<{}>", opcode(instruction), signature(module, *idx))
+        }
+        Bytecode::PackVariant(idx)
+        | Bytecode::UnpackVariant(idx)
+        | Bytecode::UnpackVariantImmRef(idx)
+        | Bytecode::UnpackVariantMutRef(idx) => {
+            let handle = module.variant_handle_at(*idx);
+            format!("{} {}", opcode(instruction), variant_name(module, handle))
+        }
+        Bytecode::PackVariantGeneric(idx)
+        | Bytecode::UnpackVariantGeneric(idx)
+        | Bytecode::UnpackVariantGenericImmRef(idx)
+        | Bytecode::UnpackVariantGenericMutRef(idx) => {
+            let handle = module.variant_instantiation_handle_at(*idx);
+            format!(
+                "{} {}",
+                opcode(instruction),
+                variant_instantiation_name(module, handle)
+            )
+        }
+        // locals, code offsets and literals don't depend on the module pools.
+        _ => format!("{:?}", instruction),
+    }
+}
+
+/// Instruction name without its operands.
+fn opcode(instruction: &Bytecode) -> String {
+    let debug = format!("{:?}", instruction);
+    match debug.split_once('(') {
+        Some((opcode, _)) => opcode.to_string(),
+        None => debug,
+    }
+}
+
+fn signature(module: &CompiledModule, idx: SignatureIndex) -> String {
+    module
+        .signature_at(idx)
+        .0
+        .iter()
+        .map(|t| type_name(module, t))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn function_name(module: &CompiledModule, idx: FunctionHandleIndex) -> String {
+    let handle = module.function_handle_at(idx);
+    let module_handle = module.module_handle_at(handle.module);
+
+    format!(
+        "{}::{}::{}",
+        module
+            .address_identifier_at(module_handle.address)
+            .to_hex_literal(),
+        module.identifier_at(module_handle.name),
+        module.identifier_at(handle.name)
+    )
+}
+
+fn struct_name(module: &CompiledModule, idx: StructDefinitionIndex) -> String {
+    datatype_name(module, module.struct_def_at(idx).struct_handle)
+}
+
+fn field_name(module: &CompiledModule, idx: FieldHandleIndex) -> String {
+    let handle = module.field_handle_at(idx);
+    let def = module.struct_def_at(handle.owner);
+    let field = match &def.field_information {
+        StructFieldInformation::Declared(fields) => fields
+            .get(handle.field as usize)
+            .map(|f| module.identifier_at(f.name).to_string()),
+        StructFieldInformation::Native => None,
+    };
+
+    format!(
+        "{}.{}",
+        datatype_name(module, def.struct_handle),
+        field.unwrap_or_else(|| handle.field.to_string())
+    )
+}
+
+fn variant_name(module: &CompiledModule, handle: &VariantHandle) -> String {
+    let def = module.enum_def_at(handle.enum_def);
+
+    format!(
+        "{}::{}",
+        datatype_name(module, def.enum_handle),
+        variant(module, def, handle.variant)
+    )
+}
+
+fn variant_instantiation_name(
+    module: &CompiledModule,
+    handle: &VariantInstantiationHandle,
+) -> String {
+    let inst = module.enum_instantiation_at(handle.enum_def);
+    let def = module.enum_def_at(inst.def);
+
+    format!(
+        "{}<{}>::{}",
+        datatype_name(module, def.enum_handle),
+        signature(module, inst.type_parameters),
+        variant(module, def, handle.variant)
+    )
+}
+
+fn variant(module: &CompiledModule, def: &EnumDefinition, tag: VariantTag) -> String {
+    def.variants.get(tag as usize).map_or_else(
+        || tag.to_string(),
+        |v| module.identifier_at(v.variant_name).to_string(),
+    )
+}
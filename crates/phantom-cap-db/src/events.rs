@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use diesel::result::Error;
+use diesel::sql_types::{Array, Text};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::{Deserialize, Serialize};
+
+use crate::Network;
+use crate::models::{UpgradeCapPolicyChange, UpgradeCapTransfer, UpgradeCapVersion};
+use crate::query;
+
+/// Channels are shared by every schema of a database, the indexer of each network
+/// notifies on this prefix followed by its schema.
+const CHANNEL_PREFIX: &str = "upgrade_cap_events_";
+
+/// Every `kind` of event.
+pub const KINDS: [&str; 3] = ["version", "transfer", "policy_change"];
+
+/// Row committed by an indexer pipeline, sent to live feeds with `NOTIFY`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CapEvent {
+    Version(UpgradeCapVersion),
+    Transfer(UpgradeCapTransfer),
+    PolicyChange(UpgradeCapPolicyChange),
+}
+
+impl CapEvent {
+    /// Same as the serialized `kind`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            CapEvent::Version(_) => "version",
+            CapEvent::Transfer(_) => "transfer",
+            CapEvent::PolicyChange(_) => "policy_change",
+        }
+    }
+
+    pub fn cap_id(&self) -> &str {
+        match self {
+            CapEvent::Version(v) => &v.object_id,
+            CapEvent::Transfer(t) => &t.object_id,
+            CapEvent::PolicyChange(p) => &p.object_id,
+        }
+    }
+}
+
+/// Addresses an event concerns: the publisher of a version, both sides of a transfer, or
+/// the owner of a cap changing policy as of its checkpoint since the change doesn't record it.
+pub async fn owners(conn: &mut AsyncPgConnection, event: &CapEvent) -> Result<Vec<String>, Error> {
+    let mut owners = all_owners(conn, std::slice::from_ref(event)).await?;
+    Ok(owners.remove(0))
+}
+
+/// [`owners`] of every event, the caps changing policy loaded in one query. Pipelines commit
+/// independently, so the owner is read from the transfers rather than the current state.
+pub async fn all_owners(
+    conn: &mut AsyncPgConnection,
+    events: &[CapEvent],
+) -> Result<Vec<Vec<String>>, Error> {
+    let policy_changes = events
+        .iter()
+        .filter_map(|event| match event {
+            CapEvent::PolicyChange(p) => Some((p.object_id.clone(), p.seq_checkpoint)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let cap_owners = if policy_changes.is_empty() {
+        HashMap::new()
+    } else {
+        query::get_caps_owner_at(conn, &policy_changes)
+            .await?
+            .into_iter()
+            .map(|(cap_id, checkpoint, owner)| ((cap_id, checkpoint), owner))
+            .collect::<HashMap<_, _>>()
+    };
+
+    Ok(events
+        .iter()
+        .map(|event| match event {
+            CapEvent::Version(v) => vec![v.publisher.clone()],
+            CapEvent::Transfer(t) => {
+                vec![t.old_owner_address.clone(), t.new_owner_address.clone()]
+            }
+            CapEvent::PolicyChange(p) => cap_owners
+                .get(&(p.object_id.clone(), p.seq_checkpoint))
+                .cloned()
+                .into_iter()
+                .collect(),
+        })
+        .collect())
+}
+
+/// Package followed by a filter.
+#[derive(Debug, Clone)]
+pub struct PackageFilter {
+    pub package_id: String,
+    /// Cap upgrading the package, `None` until the package is indexed.
+    pub cap_id: Option<String>,
+}
+
+impl PackageFilter {
+    pub async fn load(conn: &mut AsyncPgConnection, package_id: String) -> Result<Self, Error> {
+        let cap_id = query::get_package_cap(conn, &package_id).await?;
+        Ok(PackageFilter { package_id, cap_id })
+    }
+}
+
+/// Criteria of live feeds and webhooks, events match all of them.
+#[derive(Debug, Clone, Default)]
+pub struct EventFilter {
+    pub cap_id: Option<String>,
+    /// The package's publish, and every event of its cap once indexed.
+    pub package: Option<PackageFilter>,
+    pub owner: Option<String>,
+    /// Every kind when empty.
+    pub kinds: Vec<String>,
+}
+
+impl EventFilter {
+    /// `owners` of the event, as given by [`owners`].
+    pub fn matches(&self, event: &CapEvent, owners: &[String]) -> bool {
+        self.cap_id.as_ref().is_none_or(|id| id == event.cap_id())
+            && self.package.as_ref().is_none_or(|package| {
+                package.cap_id.as_deref() == Some(event.cap_id())
+                    || matches!(event, CapEvent::Version(v) if v.package_id == package.package_id)
+            })
+            && self
+                .owner
+                .as_ref()
+                .is_none_or(|owner| owners.contains(owner))
+            && (self.kinds.is_empty() || self.kinds.iter().any(|kind| kind == event.kind()))
+    }
+}
+
+/// Channel the events of a network are sent on.
+pub fn channel(network: Network) -> String {
+    format!("{}{}", CHANNEL_PREFIX, network.schema())
+}
+
+/// Sends `events` on the channel of the connection's schema. Inside a transaction,
+/// they are only delivered once it commits.
+pub async fn notify(conn: &mut AsyncPgConnection, events: &[CapEvent]) -> Result<usize, Error> {
+    if events.is_empty() {
+        return Ok(0);
+    }
+
+    let payloads = events
+        .iter()
+        .map(serde_json::to_string)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Error::SerializationError(Box::new(e)))?;
+
+    diesel::sql_query(
+        "SELECT pg_notify($1 || current_schema(), payload) FROM unnest($2::text[]) AS payload",
+    )
+    .bind::<Text, _>(CHANNEL_PREFIX)
+    .bind::<Array<Text>, _>(payloads)
+    .execute(conn)
+    .await
+}
@@ -0,0 +1,17 @@
+use diesel_migrations::{EmbeddedMigrations, embed_migrations};
+
+pub mod events;
+pub mod models;
+pub mod network;
+pub mod query;
+pub mod schema;
+pub mod state;
+pub mod webhooks;
+
+pub use network::Network;
+
+#[cfg(test)]
+mod tests;
+
+/// Migrations the generated `schema.rs` is checked against.
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("../migrator/migrations");
@@ -0,0 +1,476 @@
+use std::fmt;
+use std::io::Write;
+
+use anyhow::{Error, anyhow};
+use chrono::{DateTime, Utc};
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::{Insertable, Queryable, Selectable};
+use package_diff::UpgradeClass as PackageUpgradeClass;
+use serde::{Deserialize, Serialize};
+use sui_types::move_package::UpgradePolicy;
+
+use crate::schema::sql_types::{
+    OwnerType, UpgradeClass, UpgradeCompatibilityPolicy, WebhookDeliveryStatus,
+};
+use crate::schema::*;
+
+/// Stored owner of a shared cap or holder object.
+pub const SHARED_OWNER: &str = "shared";
+/// Stored owner of a frozen cap.
+pub const IMMUTABLE_OWNER: &str = "immutable";
+/// Stored owner of a cap wrapped in another object.
+pub const WRAPPED_OWNER: &str = "wrapped";
+
+// Every Postgres enum is mapped through the `sql_label` of its variants,
+// `ALL` follows the order of the enum labels in the migrations.
+
+#[derive(Debug, PartialEq, FromSqlRow, AsExpression, Eq, Clone, Serialize, Deserialize)]
+#[diesel(sql_type = UpgradeCompatibilityPolicy)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeCompatibilityPolicyEnum {
+    Compatible,
+    Additive,
+    DepOnly,
+    Immutable,
+}
+
+impl UpgradeCompatibilityPolicyEnum {
+    pub const ALL: [UpgradeCompatibilityPolicyEnum; 4] = [
+        UpgradeCompatibilityPolicyEnum::Compatible,
+        UpgradeCompatibilityPolicyEnum::Additive,
+        UpgradeCompatibilityPolicyEnum::DepOnly,
+        UpgradeCompatibilityPolicyEnum::Immutable,
+    ];
+
+    pub fn from_u8(policy: u8) -> Result<Self, Error> {
+        match policy {
+            val if val == UpgradePolicy::Compatible as u8 => {
+                Ok(UpgradeCompatibilityPolicyEnum::Compatible)
+            }
+            val if val == UpgradePolicy::Additive as u8 => {
+                Ok(UpgradeCompatibilityPolicyEnum::Additive)
+            }
+            val if val == UpgradePolicy::DepOnly as u8 => {
+                Ok(UpgradeCompatibilityPolicyEnum::DepOnly)
+            }
+            _ => Err(anyhow!(format!(
+                "Invalid UpgradeCompatibilityPolicy: {}",
+                policy
+            ))),
+        }
+    }
+
+    pub fn sql_label(&self) -> &'static str {
+        match *self {
+            UpgradeCompatibilityPolicyEnum::Compatible => "compatible",
+            UpgradeCompatibilityPolicyEnum::Additive => "additive",
+            UpgradeCompatibilityPolicyEnum::DepOnly => "dep_only",
+            UpgradeCompatibilityPolicyEnum::Immutable => "immutable",
+        }
+    }
+}
+
+impl fmt::Display for UpgradeCompatibilityPolicyEnum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            UpgradeCompatibilityPolicyEnum::Compatible => write!(f, "Compatible"),
+            UpgradeCompatibilityPolicyEnum::Additive => write!(f, "Additive"),
+            UpgradeCompatibilityPolicyEnum::DepOnly => write!(f, "DepOnly"),
+            UpgradeCompatibilityPolicyEnum::Immutable => write!(f, "Immutable"),
+        }
+    }
+}
+
+impl ToSql<UpgradeCompatibilityPolicy, Pg> for UpgradeCompatibilityPolicyEnum {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.sql_label().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<UpgradeCompatibilityPolicy, Pg> for UpgradeCompatibilityPolicyEnum {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|policy| policy.sql_label().as_bytes() == bytes.as_bytes())
+            .cloned()
+            .ok_or_else(|| "Unrecognized enum variant".into())
+    }
+}
+
+#[derive(Debug, PartialEq, FromSqlRow, AsExpression, Eq, Clone)]
+#[diesel(sql_type = OwnerType)]
+pub enum OwnerTypeEnum {
+    SingleKey,
+    MultiSig,
+    ZkLogin,
+    Passkey,
+}
+
+impl OwnerTypeEnum {
+    pub const ALL: [OwnerTypeEnum; 4] = [
+        OwnerTypeEnum::SingleKey,
+        OwnerTypeEnum::MultiSig,
+        OwnerTypeEnum::ZkLogin,
+        OwnerTypeEnum::Passkey,
+    ];
+
+    pub fn sql_label(&self) -> &'static str {
+        match *self {
+            OwnerTypeEnum::SingleKey => "single_key",
+            OwnerTypeEnum::MultiSig => "multisig",
+            OwnerTypeEnum::ZkLogin => "zklogin",
+            OwnerTypeEnum::Passkey => "passkey",
+        }
+    }
+}
+
+impl fmt::Display for OwnerTypeEnum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            OwnerTypeEnum::SingleKey => write!(f, "Single Key"),
+            OwnerTypeEnum::MultiSig => write!(f, "MultiSig"),
+            OwnerTypeEnum::ZkLogin => write!(f, "zkLogin"),
+            OwnerTypeEnum::Passkey => write!(f, "Passkey"),
+        }
+    }
+}
+
+impl ToSql<OwnerType, Pg> for OwnerTypeEnum {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.sql_label().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<OwnerType, Pg> for OwnerTypeEnum {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|owner_type| owner_type.sql_label().as_bytes() == bytes.as_bytes())
+            .cloned()
+            .ok_or_else(|| "Unrecognized enum variant".into())
+    }
+}
+
+/// Effective class of an upgrade, ordered from the most restrictive.
+#[derive(
+    Debug, PartialEq, FromSqlRow, AsExpression, Eq, PartialOrd, Ord, Clone, Copy, Serialize,
+)]
+#[diesel(sql_type = UpgradeClass)]
+#[serde(rename_all = "snake_case")]
+pub enum UpgradeClassEnum {
+    DepOnly,
+    Additive,
+    Compatible,
+}
+
+impl UpgradeClassEnum {
+    pub const ALL: [UpgradeClassEnum; 3] = [
+        UpgradeClassEnum::DepOnly,
+        UpgradeClassEnum::Additive,
+        UpgradeClassEnum::Compatible,
+    ];
+
+    pub fn sql_label(&self) -> &'static str {
+        match *self {
+            UpgradeClassEnum::DepOnly => "dep_only",
+            UpgradeClassEnum::Additive => "additive",
+            UpgradeClassEnum::Compatible => "compatible",
+        }
+    }
+}
+
+impl From<PackageUpgradeClass> for UpgradeClassEnum {
+    fn from(class: PackageUpgradeClass) -> Self {
+        match class {
+            PackageUpgradeClass::DepOnly => UpgradeClassEnum::DepOnly,
+            PackageUpgradeClass::Additive => UpgradeClassEnum::Additive,
+            PackageUpgradeClass::Compatible => UpgradeClassEnum::Compatible,
+        }
+    }
+}
+
+impl fmt::Display for UpgradeClassEnum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            UpgradeClassEnum::DepOnly => write!(f, "DepOnly"),
+            UpgradeClassEnum::Additive => write!(f, "Additive"),
+            UpgradeClassEnum::Compatible => write!(f, "Compatible"),
+        }
+    }
+}
+
+impl ToSql<UpgradeClass, Pg> for UpgradeClassEnum {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.sql_label().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<UpgradeClass, Pg> for UpgradeClassEnum {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|class| class.sql_label().as_bytes() == bytes.as_bytes())
+            .copied()
+            .ok_or_else(|| "Unrecognized enum variant".into())
+    }
+}
+
+#[derive(Debug, PartialEq, FromSqlRow, AsExpression, Eq, Clone, Copy, Serialize)]
+#[diesel(sql_type = WebhookDeliveryStatus)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryStatusEnum {
+    Pending,
+    Delivered,
+    /// Given up after the last retry.
+    Failed,
+}
+
+impl WebhookDeliveryStatusEnum {
+    pub const ALL: [WebhookDeliveryStatusEnum; 3] = [
+        WebhookDeliveryStatusEnum::Pending,
+        WebhookDeliveryStatusEnum::Delivered,
+        WebhookDeliveryStatusEnum::Failed,
+    ];
+
+    pub fn sql_label(&self) -> &'static str {
+        match *self {
+            WebhookDeliveryStatusEnum::Pending => "pending",
+            WebhookDeliveryStatusEnum::Delivered => "delivered",
+            WebhookDeliveryStatusEnum::Failed => "failed",
+        }
+    }
+}
+
+impl ToSql<WebhookDeliveryStatus, Pg> for WebhookDeliveryStatusEnum {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.sql_label().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<WebhookDeliveryStatus, Pg> for WebhookDeliveryStatusEnum {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        Self::ALL
+            .iter()
+            .find(|status| status.sql_label().as_bytes() == bytes.as_bytes())
+            .copied()
+            .ok_or_else(|| "Unrecognized enum variant".into())
+    }
+}
+
+#[derive(Insertable, Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = upgrade_caps)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+#[diesel(primary_key(object_id))]
+pub struct UpgradeCap {
+    pub object_id: String,
+    pub policy: UpgradeCompatibilityPolicyEnum,
+    pub created_seq_checkpoint: i64,
+    pub created_tx_digest: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Current state of a cap, maintained by the indexer next to the history tables.
+#[derive(Insertable, Queryable, Selectable, Clone, Debug, PartialEq)]
+#[diesel(table_name = upgrade_cap_state)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UpgradeCapState {
+    pub object_id: String,
+    pub owner_address: String,
+    /// Transfer that gave the cap to its owner, holders are recorded under it.
+    pub owner_tx_digest: String,
+    pub package_id: String,
+    pub version: i64,
+    /// `None` when never upgraded since publish.
+    pub last_upgrade_at: Option<DateTime<Utc>>,
+    pub policy: UpgradeCompatibilityPolicyEnum,
+    pub destroyed: bool,
+    pub last_activity_checkpoint: i64,
+    /// Checkpoint of the transfer that gave the cap to its owner.
+    pub owner_checkpoint: i64,
+    pub destroyed_seq_checkpoint: Option<i64>,
+    pub destroyed_tx_digest: Option<String>,
+    pub destroyed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Insertable, Queryable, Selectable, Clone, Debug, Serialize, Deserialize)]
+#[diesel(table_name = upgrade_cap_transfers)]
+pub struct UpgradeCapTransfer {
+    pub object_id: String,
+    pub old_owner_address: String,
+    pub new_owner_address: String,
+    pub seq_checkpoint: i64,
+    pub tx_digest: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Insertable, Queryable, Selectable, Clone, Debug, Serialize, Deserialize)]
+#[diesel(table_name = upgrade_cap_versions)]
+pub struct UpgradeCapVersion {
+    pub object_id: String,
+    pub package_id: String,
+    pub version: i64,
+    pub seq_checkpoint: i64,
+    pub tx_digest: String,
+    pub publisher: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Insertable, Queryable, Selectable, Clone, Debug, Serialize, Deserialize)]
+#[diesel(table_name = upgrade_cap_policy_changes)]
+pub struct UpgradeCapPolicyChange {
+    pub object_id: String,
+    pub old_policy: UpgradeCompatibilityPolicyEnum,
+    pub new_policy: UpgradeCompatibilityPolicyEnum,
+    pub seq_checkpoint: i64,
+    pub tx_digest: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Insertable, Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = upgrade_cap_holders)]
+pub struct UpgradeCapHolder {
+    pub object_id: String,
+    pub holder_id: String,
+    pub holder_type: String,
+    pub chain: Vec<String>,
+    /// Owner of the outermost object when the cap was wrapped, later
+    /// transfers of that object aren't followed.
+    pub root_owner: Option<String>,
+    pub seq_checkpoint: i64,
+    pub tx_digest: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Insertable, Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = cap_owners)]
+#[diesel(primary_key(address))]
+pub struct CapOwner {
+    pub address: String,
+    pub owner_type: OwnerTypeEnum,
+    pub multisig_threshold: Option<i32>,
+    pub multisig_members: Option<Vec<String>>,
+    pub multisig_weights: Option<Vec<i16>>,
+    pub seq_checkpoint: i64,
+    pub tx_digest: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Package as published, its upgrade class is audited afterwards.
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = packages)]
+pub struct Package {
+    pub package_id: String,
+    pub original_id: String,
+    pub version: i64,
+    pub seq_checkpoint: i64,
+    pub tx_digest: String,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = package_modules)]
+pub struct PackageModule {
+    pub package_id: String,
+    pub module_name: String,
+    pub bytecode: Vec<u8>,
+}
+
+/// Stored module bytecode, `None` for modules indexed before bytecode was stored.
+#[derive(Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = package_modules)]
+pub struct PackageModuleBytecode {
+    pub module_name: String,
+    pub bytecode: Option<Vec<u8>>,
+}
+
+#[derive(Insertable, Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = package_type_origins)]
+pub struct PackageTypeOrigin {
+    pub package_id: String,
+    pub module_name: String,
+    pub datatype_name: String,
+    pub origin_package_id: String,
+}
+
+#[derive(Insertable, Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = package_linkage)]
+pub struct PackageLinkage {
+    pub package_id: String,
+    pub dependency_id: String,
+    pub upgraded_id: String,
+    pub upgraded_version: i64,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = package_dependencies)]
+pub struct PackageDependency {
+    pub package_id: String,
+    pub dependency_id: String,
+    pub linked_id: String,
+}
+
+#[derive(Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = webhook_subscriptions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct WebhookSubscription {
+    pub id: i64,
+    pub url: String,
+    /// Key of the HMAC signature of every delivery.
+    pub secret: String,
+    pub package_id: Option<String>,
+    pub cap_id: Option<String>,
+    pub owner_address: Option<String>,
+    pub event_kinds: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = webhook_subscriptions)]
+pub struct NewWebhookSubscription {
+    pub url: String,
+    pub secret: String,
+    pub package_id: Option<String>,
+    pub cap_id: Option<String>,
+    pub owner_address: Option<String>,
+    pub event_kinds: Vec<String>,
+}
+
+#[derive(Queryable, Selectable, Clone, Debug)]
+#[diesel(table_name = webhook_deliveries)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub subscription_id: i64,
+    pub event_kind: String,
+    pub payload: String,
+    pub status: WebhookDeliveryStatusEnum,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_attempt_at: Option<DateTime<Utc>>,
+    pub last_status_code: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = webhook_deliveries)]
+pub struct NewWebhookDelivery {
+    pub subscription_id: i64,
+    pub event_kind: String,
+    pub payload: String,
+}
+
+/// Cap wrapped in another object, its deletion doesn't show its type.
+#[derive(Insertable, Clone, Debug)]
+#[diesel(table_name = wrapped_upgrade_caps)]
+pub struct WrappedUpgradeCap {
+    pub object_id: String,
+    pub seq_checkpoint: i64,
+}
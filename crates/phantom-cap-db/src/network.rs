@@ -0,0 +1,72 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::Serialize;
+use url::Url;
+
+/// Sui network a deployment indexes, each kept in its own Postgres schema.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Devnet,
+}
+
+impl Network {
+    pub const ALL: [Network; 3] = [Network::Mainnet, Network::Testnet, Network::Devnet];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "mainnet",
+            Network::Testnet => "testnet",
+            Network::Devnet => "devnet",
+        }
+    }
+
+    /// Schema holding the network's tables. Mainnet stays in `public` so deployments
+    /// predating multi-network support keep their data.
+    pub fn schema(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "public",
+            other => other.as_str(),
+        }
+    }
+
+    /// `database_url` with its `search_path` set to the network's schema.
+    pub fn database_url(&self, database_url: &Url) -> Url {
+        let mut url = database_url.clone();
+        if *self != Network::Mainnet {
+            url.query_pairs_mut()
+                .append_pair("options", &format!("-csearch_path={}", self.schema()));
+        }
+        url
+    }
+
+    /// Parses a comma separated list such as `mainnet,testnet`.
+    pub fn parse_list(networks: &str) -> Result<Vec<Network>, String> {
+        networks
+            .split(',')
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Network {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Network::ALL
+            .into_iter()
+            .find(|n| n.as_str() == s.to_lowercase())
+            .ok_or_else(|| format!("Unknown network '{}'", s))
+    }
+}
@@ -0,0 +1,116 @@
+use diesel::result::Error;
+use diesel::sql_types::{Array, BigInt, Text};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, QueryableByName, SelectableHelper};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::models::{PackageModuleBytecode, UpgradeCapState};
+use crate::schema::package_modules::dsl as package_modules_dsl;
+use crate::schema::packages::dsl as packages_dsl;
+use crate::schema::upgrade_cap_state::dsl as upgrade_cap_state_dsl;
+use crate::schema::upgrade_cap_versions::dsl as upgrade_cap_versions_dsl;
+
+pub async fn get_cap_state(
+    conn: &mut AsyncPgConnection,
+    cap_id: &str,
+) -> Result<Option<UpgradeCapState>, Error> {
+    upgrade_cap_state_dsl::upgrade_cap_state
+        .filter(upgrade_cap_state_dsl::object_id.eq(cap_id))
+        .select(UpgradeCapState::as_select())
+        .first::<UpgradeCapState>(conn)
+        .await
+        .optional()
+}
+
+#[derive(QueryableByName)]
+struct CapOwnerAt {
+    #[diesel(sql_type = Text)]
+    object_id: String,
+    #[diesel(sql_type = BigInt)]
+    seq_checkpoint: i64,
+    #[diesel(sql_type = Text)]
+    owner_address: String,
+}
+
+/// `(cap_id, checkpoint, owner_address)` of every given cap at the given checkpoint, from
+/// its latest transfer up to it. Caps without an indexed transfer by then are left out.
+pub async fn get_caps_owner_at(
+    conn: &mut AsyncPgConnection,
+    caps: &[(String, i64)],
+) -> Result<Vec<(String, i64, String)>, Error> {
+    let (cap_ids, checkpoints): (Vec<_>, Vec<_>) = caps.iter().cloned().unzip();
+
+    let owners = diesel::sql_query(
+        "SELECT c.object_id, c.seq_checkpoint, t.new_owner_address AS owner_address
+        FROM unnest($1::TEXT[], $2::BIGINT[]) AS c (object_id, seq_checkpoint)
+        CROSS JOIN LATERAL (
+            SELECT new_owner_address
+            FROM upgrade_cap_transfers
+            WHERE object_id = c.object_id AND seq_checkpoint <= c.seq_checkpoint
+            ORDER BY seq_checkpoint DESC, tx_digest DESC
+            LIMIT 1
+        ) t",
+    )
+    .bind::<Array<Text>, _>(cap_ids)
+    .bind::<Array<BigInt>, _>(checkpoints)
+    .load::<CapOwnerAt>(conn)
+    .await?;
+
+    Ok(owners
+        .into_iter()
+        .map(|owner| (owner.object_id, owner.seq_checkpoint, owner.owner_address))
+        .collect())
+}
+
+/// Cap that published or upgraded a package.
+pub async fn get_package_cap(
+    conn: &mut AsyncPgConnection,
+    package_id: &str,
+) -> Result<Option<String>, Error> {
+    upgrade_cap_versions_dsl::upgrade_cap_versions
+        .select(upgrade_cap_versions_dsl::object_id)
+        .filter(upgrade_cap_versions_dsl::package_id.eq(package_id))
+        .first::<String>(conn)
+        .await
+        .optional()
+}
+
+/// `(package_id, cap_id)` of every given package that is indexed.
+pub async fn get_packages_cap(
+    conn: &mut AsyncPgConnection,
+    package_ids: &[String],
+) -> Result<Vec<(String, String)>, Error> {
+    upgrade_cap_versions_dsl::upgrade_cap_versions
+        .select((
+            upgrade_cap_versions_dsl::package_id,
+            upgrade_cap_versions_dsl::object_id,
+        ))
+        .filter(upgrade_cap_versions_dsl::package_id.eq_any(package_ids))
+        .load::<(String, String)>(conn)
+        .await
+}
+
+/// Id of a package at the given version of its lineage.
+pub async fn get_package_at_version(
+    conn: &mut AsyncPgConnection,
+    original_id: &str,
+    version: i64,
+) -> Result<Option<String>, Error> {
+    packages_dsl::packages
+        .select(packages_dsl::package_id)
+        .filter(packages_dsl::original_id.eq(original_id))
+        .filter(packages_dsl::version.eq(version))
+        .first::<String>(conn)
+        .await
+        .optional()
+}
+
+pub async fn get_package_bytecode(
+    conn: &mut AsyncPgConnection,
+    package_id: &str,
+) -> Result<Vec<PackageModuleBytecode>, Error> {
+    package_modules_dsl::package_modules
+        .filter(package_modules_dsl::package_id.eq(package_id))
+        .select(PackageModuleBytecode::as_select())
+        .load::<PackageModuleBytecode>(conn)
+        .await
+}
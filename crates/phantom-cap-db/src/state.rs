@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use diesel::pg::Pg;
+use diesel::query_builder::{BoxedSqlQuery, SqlQuery};
+use diesel::sql_types::{BigInt, Bool, Nullable, Text, Timestamptz};
+use diesel::{ExpressionMethods, QueryDsl};
+
+use crate::models::{
+    UpgradeCapState, UpgradeCapTransfer, UpgradeCapVersion, UpgradeCompatibilityPolicyEnum,
+};
+use crate::schema::sql_types::UpgradeCompatibilityPolicy;
+use crate::schema::upgrade_cap_state;
+
+// Every pipeline upserts `upgrade_cap_state` in the transaction of its own
+// history rows. Pipelines commit independently, so a cap's row may be written
+// by any of them first and each column is merged so that the commit order
+// doesn't matter:
+// - the owner is replaced by transfers from a later `owner_checkpoint`,
+// - the package by a higher version,
+// - the policy by a more restrictive one, labels are declared from the least restrictive,
+// - `destroyed` and `last_activity_checkpoint` only move forward, a cap is destroyed once.
+// Columns a pipeline doesn't know are written with values that never win the merge.
+
+/// Upsert of a cap's state, runs on both async and blocking connections.
+pub type StateUpsert = BoxedSqlQuery<'static, Pg, SqlQuery>;
+
+/// State of a newly created cap, a transfer in the same checkpoint comes after the creation.
+pub fn created(state: &UpgradeCapState) -> StateUpsert {
+    upsert(state.clone(), false)
+}
+
+/// Owner from a transfer, transfers of a checkpoint are applied in transaction order.
+pub fn owner(transfer: &UpgradeCapTransfer) -> StateUpsert {
+    upsert(
+        UpgradeCapState {
+            owner_address: transfer.new_owner_address.clone(),
+            owner_tx_digest: transfer.tx_digest.clone(),
+            owner_checkpoint: transfer.seq_checkpoint,
+            ..unknown(&transfer.object_id, transfer.seq_checkpoint)
+        },
+        true,
+    )
+}
+
+/// Package of an upgrade, older versions are ignored.
+pub fn package(upgrade: &UpgradeCapVersion) -> StateUpsert {
+    upsert(
+        UpgradeCapState {
+            package_id: upgrade.package_id.clone(),
+            version: upgrade.version,
+            last_upgrade_at: Some(upgrade.timestamp),
+            ..unknown(&upgrade.object_id, upgrade.seq_checkpoint)
+        },
+        false,
+    )
+}
+
+/// Restricted policy, a less restrictive one is ignored.
+pub fn policy(
+    cap_id: &str,
+    new_policy: UpgradeCompatibilityPolicyEnum,
+    checkpoint: i64,
+) -> StateUpsert {
+    upsert(
+        UpgradeCapState {
+            policy: new_policy,
+            ..unknown(cap_id, checkpoint)
+        },
+        false,
+    )
+}
+
+/// Destroyed caps can't authorize upgrades anymore, like frozen ones.
+pub fn destroyed(
+    cap_id: &str,
+    checkpoint: i64,
+    tx_digest: &str,
+    timestamp: DateTime<Utc>,
+) -> StateUpsert {
+    upsert(
+        UpgradeCapState {
+            policy: UpgradeCompatibilityPolicyEnum::Immutable,
+            destroyed: true,
+            destroyed_seq_checkpoint: Some(checkpoint),
+            destroyed_tx_digest: Some(tx_digest.to_string()),
+            destroyed_at: Some(timestamp),
+            ..unknown(cap_id, checkpoint)
+        },
+        false,
+    )
+}
+
+/// Caps among `ids` that any pipeline indexed, runs on both async and blocking connections.
+/// Objects without a type in the checkpoint, such as wrapped ones, are only caps if listed.
+pub fn indexed(ids: &[String]) -> upgrade_cap_state::BoxedQuery<'_, Pg, Text> {
+    upgrade_cap_state::table
+        .select(upgrade_cap_state::object_id)
+        .filter(upgrade_cap_state::object_id.eq_any(ids))
+        .into_boxed()
+}
+
+/// Values losing the merge against anything another pipeline writes.
+fn unknown(cap_id: &str, checkpoint: i64) -> UpgradeCapState {
+    UpgradeCapState {
+        object_id: cap_id.to_string(),
+        owner_address: String::new(),
+        owner_tx_digest: String::new(),
+        package_id: String::new(),
+        version: 0,
+        last_upgrade_at: None,
+        policy: UpgradeCompatibilityPolicyEnum::Compatible,
+        destroyed: false,
+        last_activity_checkpoint: checkpoint,
+        owner_checkpoint: -1,
+        destroyed_seq_checkpoint: None,
+        destroyed_tx_digest: None,
+        destroyed_at: None,
+    }
+}
+
+fn upsert(state: UpgradeCapState, owner_wins_ties: bool) -> StateUpsert {
+    let owner_wins = if owner_wins_ties {
+        "s.owner_checkpoint <= EXCLUDED.owner_checkpoint"
+    } else {
+        "s.owner_checkpoint < EXCLUDED.owner_checkpoint"
+    };
+
+    diesel::sql_query(format!(
+        "INSERT INTO upgrade_cap_state AS s (
+            object_id, owner_address, owner_tx_digest, owner_checkpoint, package_id,
+            version, last_upgrade_at, policy, destroyed, last_activity_checkpoint,
+            destroyed_seq_checkpoint, destroyed_tx_digest, destroyed_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+        ON CONFLICT (object_id) DO UPDATE SET
+            owner_address = CASE WHEN {owner_wins}
+                THEN EXCLUDED.owner_address ELSE s.owner_address END,
+            owner_tx_digest = CASE WHEN {owner_wins}
+                THEN EXCLUDED.owner_tx_digest ELSE s.owner_tx_digest END,
+            owner_checkpoint = GREATEST(s.owner_checkpoint, EXCLUDED.owner_checkpoint),
+            package_id = CASE WHEN s.version < EXCLUDED.version
+                THEN EXCLUDED.package_id ELSE s.package_id END,
+            last_upgrade_at = CASE WHEN s.version < EXCLUDED.version
+                THEN EXCLUDED.last_upgrade_at ELSE s.last_upgrade_at END,
+            version = GREATEST(s.version, EXCLUDED.version),
+            policy = GREATEST(s.policy, EXCLUDED.policy),
+            destroyed = s.destroyed OR EXCLUDED.destroyed,
+            destroyed_seq_checkpoint =
+                COALESCE(s.destroyed_seq_checkpoint, EXCLUDED.destroyed_seq_checkpoint),
+            destroyed_tx_digest = COALESCE(s.destroyed_tx_digest, EXCLUDED.destroyed_tx_digest),
+            destroyed_at = COALESCE(s.destroyed_at, EXCLUDED.destroyed_at),
+            last_activity_checkpoint =
+                GREATEST(s.last_activity_checkpoint, EXCLUDED.last_activity_checkpoint)"
+    ))
+    .into_boxed()
+    .bind::<Text, _>(state.object_id)
+    .bind::<Text, _>(state.owner_address)
+    .bind::<Text, _>(state.owner_tx_digest)
+    .bind::<BigInt, _>(state.owner_checkpoint)
+    .bind::<Text, _>(state.package_id)
+    .bind::<BigInt, _>(state.version)
+    .bind::<Nullable<Timestamptz>, _>(state.last_upgrade_at)
+    .bind::<UpgradeCompatibilityPolicy, _>(state.policy)
+    .bind::<Bool, _>(state.destroyed)
+    .bind::<BigInt, _>(state.last_activity_checkpoint)
+    .bind::<Nullable<BigInt>, _>(state.destroyed_seq_checkpoint)
+    .bind::<Nullable<Text>, _>(state.destroyed_tx_digest)
+    .bind::<Nullable<Timestamptz>, _>(state.destroyed_at)
+}
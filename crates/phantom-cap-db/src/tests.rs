@@ -0,0 +1,537 @@
+use std::collections::BTreeMap;
+
+use diesel::dsl::sql;
+use diesel::sql_types::{Array, Bool, Integer, Nullable, Text};
+use diesel::{
+    Connection, ExpressionMethods, IntoSql, PgConnection, QueryDsl, QueryableByName, RunQueryDsl,
+    SelectableHelper,
+};
+use diesel_migrations::MigrationHarness;
+
+use crate::MIGRATIONS;
+use crate::events::{CapEvent, EventFilter, PackageFilter};
+use crate::models::{
+    OwnerTypeEnum, UpgradeCapPolicyChange, UpgradeCapState, UpgradeCapTransfer, UpgradeCapVersion,
+    UpgradeClassEnum, UpgradeCompatibilityPolicyEnum, WebhookDeliveryStatusEnum,
+};
+use crate::schema::sql_types::{
+    OwnerType, UpgradeClass, UpgradeCompatibilityPolicy, WebhookDeliveryStatus,
+};
+use crate::schema::upgrade_cap_state::dsl as upgrade_cap_state_dsl;
+use crate::state::{self, StateUpsert};
+use crate::webhooks;
+
+/// Migrated connection inside a test transaction. Database tests are ignored
+/// by default, run them with `DATABASE_URL` set and `cargo test -- --ignored`.
+fn connection() -> PgConnection {
+    let database_url = std::env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+
+    let mut conn = PgConnection::establish(&database_url).expect("Failed to connect to database");
+    conn.begin_test_transaction()
+        .expect("Failed to begin test transaction");
+    conn.run_pending_migrations(MIGRATIONS)
+        .expect("Could not run migrations");
+    conn
+}
+
+/// Writes every variant of an enum and reads it back.
+macro_rules! assert_round_trips {
+    ($conn:expr, $enum:ty, $sql_type:ty) => {
+        for variant in <$enum>::ALL {
+            let loaded = diesel::select(variant.clone().into_sql::<$sql_type>())
+                .get_result::<$enum>($conn)
+                .unwrap_or_else(|e| panic!("{:?} failed to round-trip: {}", variant, e));
+            assert_eq!(loaded, variant);
+        }
+    };
+}
+
+/// Labels of a Postgres enum, in declaration order.
+fn enum_labels(conn: &mut PgConnection, type_name: &str) -> Vec<String> {
+    diesel::select(sql::<Array<Text>>(&format!(
+        "enum_range(NULL::{})::text[]",
+        type_name
+    )))
+    .get_result::<Vec<String>>(conn)
+    .expect("Failed to load enum labels")
+}
+
+#[test]
+#[ignore = "needs DATABASE_URL"]
+fn enums_round_trip() {
+    let mut conn = connection();
+
+    assert_round_trips!(
+        &mut conn,
+        UpgradeCompatibilityPolicyEnum,
+        UpgradeCompatibilityPolicy
+    );
+    assert_round_trips!(&mut conn, OwnerTypeEnum, OwnerType);
+    assert_round_trips!(&mut conn, UpgradeClassEnum, UpgradeClass);
+    assert_round_trips!(&mut conn, WebhookDeliveryStatusEnum, WebhookDeliveryStatus);
+}
+
+#[test]
+#[ignore = "needs DATABASE_URL"]
+fn enums_match_database() {
+    let mut conn = connection();
+
+    let labels = |labels: &[&str]| labels.iter().map(|l| l.to_string()).collect::<Vec<_>>();
+
+    assert_eq!(
+        enum_labels(&mut conn, "upgrade_compatibility_policy"),
+        labels(&UpgradeCompatibilityPolicyEnum::ALL.map(|p| p.sql_label()))
+    );
+    assert_eq!(
+        enum_labels(&mut conn, "owner_type"),
+        labels(&OwnerTypeEnum::ALL.map(|o| o.sql_label()))
+    );
+    assert_eq!(
+        enum_labels(&mut conn, "upgrade_class"),
+        labels(&UpgradeClassEnum::ALL.map(|c| c.sql_label()))
+    );
+    assert_eq!(
+        enum_labels(&mut conn, "webhook_delivery_status"),
+        labels(&WebhookDeliveryStatusEnum::ALL.map(|s| s.sql_label()))
+    );
+}
+
+#[derive(QueryableByName)]
+struct DbColumn {
+    #[diesel(sql_type = Text)]
+    table_name: String,
+    #[diesel(sql_type = Text)]
+    column_name: String,
+    #[diesel(sql_type = Text)]
+    data_type: String,
+    #[diesel(sql_type = Text)]
+    udt_name: String,
+    #[diesel(sql_type = Bool)]
+    nullable: bool,
+    /// Position in the primary key, `None` when not part of it.
+    #[diesel(sql_type = Nullable<Integer>)]
+    key_position: Option<i32>,
+}
+
+/// Diesel type `print-schema` generates for a column.
+fn diesel_type(column: &DbColumn) -> String {
+    let name = |udt_name: &str| match udt_name {
+        "text" => "Text".to_string(),
+        "int2" => "Int2".to_string(),
+        "int4" => "Int4".to_string(),
+        "int8" => "Int8".to_string(),
+        "bool" => "Bool".to_string(),
+        "bytea" => "Bytea".to_string(),
+        "timestamptz" => "Timestamptz".to_string(),
+        // enums, e.g. `owner_type` is `OwnerType`.
+        other => other
+            .split('_')
+            .map(|word| word[..1].to_uppercase() + &word[1..])
+            .collect(),
+    };
+
+    let ty = match column.data_type.as_str() {
+        "ARRAY" => format!("Array<{}>", name(&column.udt_name[1..])),
+        _ => name(&column.udt_name),
+    };
+
+    match column.nullable {
+        true => format!("Nullable<{}>", ty),
+        false => ty,
+    }
+}
+
+/// Table definitions as `name (primary key) { column -> Type, ... }` lines.
+type Tables = BTreeMap<String, Vec<String>>;
+
+fn database_tables(conn: &mut PgConnection) -> Tables {
+    let columns = diesel::sql_query(
+        r#"
+        SELECT
+            c.table_name::text AS table_name,
+            c.column_name::text AS column_name,
+            c.data_type::text AS data_type,
+            c.udt_name::text AS udt_name,
+            c.is_nullable = 'YES' AS nullable,
+            (
+                SELECT k.ordinal_position::int
+                FROM information_schema.table_constraints tc
+                JOIN information_schema.key_column_usage k
+                    ON k.constraint_name = tc.constraint_name
+                    AND k.table_schema = tc.table_schema
+                WHERE tc.constraint_type = 'PRIMARY KEY'
+                    AND tc.table_schema = c.table_schema
+                    AND tc.table_name = c.table_name
+                    AND k.column_name = c.column_name
+            ) AS key_position
+        FROM information_schema.columns c
+        WHERE c.table_schema = current_schema()
+            AND c.table_name NOT LIKE '\_\_diesel%'
+        ORDER BY c.table_name, c.ordinal_position
+        "#,
+    )
+    .load::<DbColumn>(conn)
+    .expect("Failed to load database columns");
+
+    let mut tables = Tables::new();
+    let mut keys = BTreeMap::<String, BTreeMap<i32, String>>::new();
+    for column in &columns {
+        if let Some(position) = column.key_position {
+            keys.entry(column.table_name.clone())
+                .or_default()
+                .insert(position, column.column_name.clone());
+        }
+        tables
+            .entry(column.table_name.clone())
+            .or_default()
+            .push(format!("{} -> {}", column.column_name, diesel_type(column)));
+    }
+
+    for (table, lines) in tables.iter_mut() {
+        let key = keys
+            .get(table)
+            .map(|k| k.values().cloned().collect::<Vec<_>>().join(", "))
+            .unwrap_or_default();
+        lines.insert(0, format!("{} ({})", table, key));
+    }
+    tables
+}
+
+fn schema_tables() -> Tables {
+    let mut tables = Tables::new();
+    let mut current = None;
+
+    for line in include_str!("schema.rs").lines().map(str::trim) {
+        if let Some(header) = line.strip_suffix(" {").filter(|l| l.contains(" (")) {
+            let name = header.split(' ').next().unwrap_or_default().to_string();
+            tables.insert(name.clone(), vec![header.to_string()]);
+            current = Some(name);
+        } else if let Some(column) = line.strip_suffix(',').filter(|l| l.contains(" -> ")) {
+            if let Some(table) = current.as_ref().and_then(|t| tables.get_mut(t)) {
+                table.push(column.to_string());
+            }
+        } else if line == "}" {
+            current = None;
+        }
+    }
+    tables
+}
+
+#[test]
+#[ignore = "needs DATABASE_URL"]
+fn schema_matches_migrations() {
+    let mut conn = connection();
+
+    assert_eq!(
+        schema_tables(),
+        database_tables(&mut conn),
+        "schema.rs is out of date, regenerate it with `diesel print-schema`"
+    );
+}
+
+#[test]
+fn events_round_trip() {
+    let timestamp = chrono::DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+    let events = [
+        CapEvent::Version(UpgradeCapVersion {
+            object_id: "0x1".to_string(),
+            package_id: "0x2".to_string(),
+            version: 2,
+            seq_checkpoint: 10,
+            tx_digest: "digest".to_string(),
+            publisher: "0x3".to_string(),
+            timestamp,
+        }),
+        CapEvent::Transfer(UpgradeCapTransfer {
+            object_id: "0x1".to_string(),
+            old_owner_address: "0x3".to_string(),
+            new_owner_address: "0x4".to_string(),
+            seq_checkpoint: 11,
+            tx_digest: "digest".to_string(),
+            timestamp,
+        }),
+        CapEvent::PolicyChange(UpgradeCapPolicyChange {
+            object_id: "0x1".to_string(),
+            old_policy: UpgradeCompatibilityPolicyEnum::Compatible,
+            new_policy: UpgradeCompatibilityPolicyEnum::DepOnly,
+            seq_checkpoint: 12,
+            tx_digest: "digest".to_string(),
+            timestamp,
+        }),
+    ];
+
+    for event in events {
+        let payload = serde_json::to_value(&event).expect("Failed to serialize event");
+        assert_eq!(payload["kind"], event.kind());
+
+        let loaded = serde_json::from_value::<CapEvent>(payload).expect("Failed to load event");
+        assert_eq!(loaded.kind(), event.kind());
+        assert_eq!(loaded.cap_id(), event.cap_id());
+    }
+}
+
+#[test]
+fn event_filters_match() {
+    let timestamp = chrono::DateTime::from_timestamp_millis(1_700_000_000_000).unwrap();
+    let version = CapEvent::Version(UpgradeCapVersion {
+        object_id: "0x1".to_string(),
+        package_id: "0x2".to_string(),
+        version: 1,
+        seq_checkpoint: 10,
+        tx_digest: "digest".to_string(),
+        publisher: "0x3".to_string(),
+        timestamp,
+    });
+    let transfer = CapEvent::Transfer(UpgradeCapTransfer {
+        object_id: "0x1".to_string(),
+        old_owner_address: "0x3".to_string(),
+        new_owner_address: "0x4".to_string(),
+        seq_checkpoint: 11,
+        tx_digest: "digest".to_string(),
+        timestamp,
+    });
+    let owners = |event: &CapEvent| match event {
+        CapEvent::Version(_) => vec!["0x3".to_string()],
+        _ => vec!["0x3".to_string(), "0x4".to_string()],
+    };
+    let matches = |filter: &EventFilter, event: &CapEvent| filter.matches(event, &owners(event));
+
+    let every = EventFilter::default();
+    assert!(matches(&every, &version) && matches(&every, &transfer));
+
+    // a package not indexed yet only matches its publish.
+    let mut package = EventFilter {
+        package: Some(PackageFilter {
+            package_id: "0x2".to_string(),
+            cap_id: None,
+        }),
+        ..Default::default()
+    };
+    assert!(matches(&package, &version) && !matches(&package, &transfer));
+    package.package = Some(PackageFilter {
+        package_id: "0x2".to_string(),
+        cap_id: Some("0x1".to_string()),
+    });
+    assert!(matches(&package, &transfer));
+
+    let owner = EventFilter {
+        owner: Some("0x4".to_string()),
+        ..Default::default()
+    };
+    assert!(!matches(&owner, &version) && matches(&owner, &transfer));
+
+    let versions = EventFilter {
+        cap_id: Some("0x1".to_string()),
+        kinds: vec!["version".to_string()],
+        ..Default::default()
+    };
+    assert!(matches(&versions, &version) && !matches(&versions, &transfer));
+}
+
+/// Upserts each pipeline would commit for a cap, in checkpoint order within a pipeline.
+fn pipeline_commits(cap_id: &str) -> Vec<Vec<StateUpsert>> {
+    let at = |checkpoint: i64| {
+        chrono::DateTime::from_timestamp_millis(1_700_000_000_000 + checkpoint * 1000).unwrap()
+    };
+    let transfer = |old: &str, new: &str, checkpoint: i64, digest: &str| UpgradeCapTransfer {
+        object_id: cap_id.to_string(),
+        old_owner_address: old.to_string(),
+        new_owner_address: new.to_string(),
+        seq_checkpoint: checkpoint,
+        tx_digest: digest.to_string(),
+        timestamp: at(checkpoint),
+    };
+    let upgrade = |package_id: &str, version: i64, checkpoint: i64| UpgradeCapVersion {
+        object_id: cap_id.to_string(),
+        package_id: package_id.to_string(),
+        version,
+        seq_checkpoint: checkpoint,
+        tx_digest: format!("upgrade-{}", version),
+        publisher: "0xa".to_string(),
+        timestamp: at(checkpoint),
+    };
+
+    vec![
+        vec![state::created(&UpgradeCapState {
+            object_id: cap_id.to_string(),
+            owner_address: "0xa".to_string(),
+            owner_tx_digest: "create".to_string(),
+            package_id: "0x1".to_string(),
+            version: 1,
+            last_upgrade_at: None,
+            policy: UpgradeCompatibilityPolicyEnum::Compatible,
+            destroyed: false,
+            last_activity_checkpoint: 10,
+            owner_checkpoint: 10,
+            destroyed_seq_checkpoint: None,
+            destroyed_tx_digest: None,
+            destroyed_at: None,
+        })],
+        // two transfers in one checkpoint, the second one is the owner.
+        vec![
+            state::owner(&transfer("0xa", "0xb", 20, "transfer-1")),
+            state::owner(&transfer("0xb", "0xc", 20, "transfer-2")),
+        ],
+        vec![
+            state::package(&upgrade("0x2", 2, 15)),
+            state::package(&upgrade("0x3", 3, 30)),
+        ],
+        vec![
+            state::policy(cap_id, UpgradeCompatibilityPolicyEnum::Additive, 25),
+            state::policy(cap_id, UpgradeCompatibilityPolicyEnum::DepOnly, 35),
+        ],
+    ]
+}
+
+/// Every order of `n` items.
+fn permutations(n: usize) -> Vec<Vec<usize>> {
+    if n == 0 {
+        return vec![vec![]];
+    }
+
+    permutations(n - 1)
+        .into_iter()
+        .flat_map(|order| {
+            (0..=order.len()).map(move |i| {
+                let mut order = order.clone();
+                order.insert(i, n - 1);
+                order
+            })
+        })
+        .collect()
+}
+
+fn load_state(conn: &mut PgConnection, cap_id: &str) -> UpgradeCapState {
+    upgrade_cap_state_dsl::upgrade_cap_state
+        .filter(upgrade_cap_state_dsl::object_id.eq(cap_id))
+        .select(UpgradeCapState::as_select())
+        .first::<UpgradeCapState>(conn)
+        .expect("Failed to load cap state")
+}
+
+#[test]
+#[ignore = "needs DATABASE_URL"]
+fn state_is_independent_of_commit_order() {
+    let mut conn = connection();
+
+    let expected = UpgradeCapState {
+        object_id: String::new(),
+        owner_address: "0xc".to_string(),
+        owner_tx_digest: "transfer-2".to_string(),
+        package_id: "0x3".to_string(),
+        version: 3,
+        last_upgrade_at: chrono::DateTime::from_timestamp_millis(1_700_000_030_000),
+        policy: UpgradeCompatibilityPolicyEnum::DepOnly,
+        destroyed: false,
+        last_activity_checkpoint: 35,
+        owner_checkpoint: 20,
+        destroyed_seq_checkpoint: None,
+        destroyed_tx_digest: None,
+        destroyed_at: None,
+    };
+
+    for (i, order) in permutations(pipeline_commits("").len()).iter().enumerate() {
+        let cap_id = format!("0x{:x}", 0x100 + i);
+        let mut pipelines = pipeline_commits(&cap_id);
+
+        for pipeline in order {
+            for upsert in std::mem::take(&mut pipelines[*pipeline]) {
+                upsert
+                    .execute(&mut conn)
+                    .expect("Failed to upsert cap state");
+            }
+        }
+
+        assert_eq!(
+            load_state(&mut conn, &cap_id),
+            UpgradeCapState {
+                object_id: cap_id,
+                ..expected.clone()
+            },
+            "pipelines committed in order {:?}",
+            order
+        );
+    }
+}
+
+#[test]
+#[ignore = "needs DATABASE_URL"]
+fn destroyed_state_survives_later_commits() {
+    let mut conn = connection();
+
+    // the cap is destroyed before the other pipelines catch up.
+    let destroyed_at = chrono::DateTime::from_timestamp_millis(1_700_000_040_000).unwrap();
+    state::destroyed("0x1", 40, "destroy", destroyed_at)
+        .execute(&mut conn)
+        .expect("Failed to upsert cap state");
+    for pipeline in pipeline_commits("0x1") {
+        for upsert in pipeline {
+            upsert
+                .execute(&mut conn)
+                .expect("Failed to upsert cap state");
+        }
+    }
+
+    let loaded = load_state(&mut conn, "0x1");
+    assert!(loaded.destroyed);
+    assert_eq!(loaded.policy, UpgradeCompatibilityPolicyEnum::Immutable);
+    assert_eq!(loaded.destroyed_tx_digest.as_deref(), Some("destroy"));
+    assert_eq!(loaded.destroyed_at, Some(destroyed_at));
+    assert_eq!(loaded.owner_address, "0xc");
+    assert_eq!(loaded.last_activity_checkpoint, 40);
+}
+
+#[test]
+#[ignore = "needs DATABASE_URL"]
+fn indexed_caps_include_caps_never_seen_wrapped() {
+    let mut conn = connection();
+
+    // wrapped before the destroy pipeline started, only the other pipelines indexed it.
+    for upsert in pipeline_commits("0x1").into_iter().flatten() {
+        upsert
+            .execute(&mut conn)
+            .expect("Failed to upsert cap state");
+    }
+
+    let unwrapped_then_deleted = ["0x1".to_string(), "0x2".to_string()];
+    let caps = state::indexed(&unwrapped_then_deleted)
+        .load::<String>(&mut conn)
+        .expect("Failed to load indexed caps");
+    assert_eq!(caps, ["0x1"]);
+}
+
+#[test]
+fn webhook_urls_must_be_public() {
+    let check = |url: &str| webhooks::check_url(&url::Url::parse(url).unwrap());
+
+    for url in [
+        "https://hooks.example.com/phantom",
+        "http://93.184.216.34:8080/",
+        "https://[2606:4700::1111]/",
+    ] {
+        assert_eq!(check(url), Ok(()), "{}", url);
+    }
+
+    for url in [
+        "ftp://hooks.example.com/",
+        "file:///etc/passwd",
+        "http://localhost:5432/",
+        "http://api.LOCALHOST./",
+        "http://metadata.google.internal/",
+        "http://127.0.0.1/",
+        "http://127.1/",
+        "http://0x7f000001/",
+        "http://10.0.0.1/",
+        "http://172.16.0.1/",
+        "http://192.168.1.1/",
+        "http://169.254.169.254/latest/meta-data/",
+        "http://100.64.0.1/",
+        "http://0.0.0.0/",
+        "http://[::1]/",
+        "http://[fd00::1]/",
+        "http://[fe80::1]/",
+        "http://[::ffff:127.0.0.1]/",
+    ] {
+        assert!(check(url).is_err(), "{}", url);
+    }
+}
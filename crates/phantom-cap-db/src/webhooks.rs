@@ -0,0 +1,205 @@
+use std::collections::{BTreeSet, HashMap};
+use std::net::IpAddr;
+
+use diesel::result::Error;
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, NullableExpressionMethods, QueryDsl, SelectableHelper,
+};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use url::{Host, Url};
+
+use crate::events::{self, CapEvent, EventFilter, PackageFilter};
+use crate::models::{NewWebhookDelivery, WebhookSubscription};
+use crate::query;
+use crate::schema::upgrade_cap_versions::dsl as upgrade_cap_versions_dsl;
+use crate::schema::webhook_deliveries::dsl::webhook_deliveries;
+use crate::schema::webhook_subscriptions::dsl as webhook_subscriptions_dsl;
+
+/// Checks that a webhook url is http or https to a public host, so that deliveries can't
+/// reach the services next to the dispatcher. Domains are checked again once resolved.
+pub fn check_url(url: &Url) -> Result<(), String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err("Webhook urls must be http or https".to_string());
+    }
+
+    let public = match url.host() {
+        None => return Err("Webhook urls must have a host".to_string()),
+        Some(Host::Domain(domain)) => !is_local_domain(domain),
+        Some(Host::Ipv4(ip)) => is_public_ip(ip.into()),
+        Some(Host::Ipv6(ip)) => is_public_ip(ip.into()),
+    };
+    if !public {
+        return Err("Webhook urls must point to a public host".to_string());
+    }
+
+    Ok(())
+}
+
+fn is_local_domain(domain: &str) -> bool {
+    let domain = domain.trim_end_matches('.').to_ascii_lowercase();
+    ["localhost", "local", "internal"]
+        .iter()
+        .any(|local| domain == *local || domain.ends_with(&format!(".{}", local)))
+}
+
+/// Whether an address is routed on the public internet.
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                // "this network", carrier-grade NAT and reserved ranges.
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+                || a >= 240)
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(ip.into()),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local())
+            }
+        },
+    }
+}
+
+/// Filter of a subscription, given the cap of every indexed package by id.
+pub fn subscription_filter(
+    subscription: &WebhookSubscription,
+    package_caps: &HashMap<String, String>,
+) -> EventFilter {
+    let package = subscription
+        .package_id
+        .as_ref()
+        .map(|package_id| PackageFilter {
+            package_id: package_id.clone(),
+            cap_id: package_caps.get(package_id).cloned(),
+        });
+
+    EventFilter {
+        cap_id: subscription.cap_id.clone(),
+        package,
+        owner: subscription.owner_address.clone(),
+        kinds: subscription.event_kinds.clone(),
+    }
+}
+
+/// Subscriptions that may match one of the events, narrowed down by the database on the
+/// cap, package and owner they follow.
+async fn candidate_subscriptions(
+    conn: &mut AsyncPgConnection,
+    events: &[CapEvent],
+    owners: &[Vec<String>],
+) -> Result<Vec<WebhookSubscription>, Error> {
+    let cap_ids = events
+        .iter()
+        .map(|event| event.cap_id().to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    let published = events
+        .iter()
+        .filter_map(|event| match event {
+            CapEvent::Version(v) => Some(v.package_id.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let owners = owners.concat();
+
+    // a package filter matches the package's publish, and every event of its cap.
+    let cap_packages = upgrade_cap_versions_dsl::upgrade_cap_versions
+        .select(upgrade_cap_versions_dsl::package_id.nullable())
+        .filter(upgrade_cap_versions_dsl::object_id.eq_any(&cap_ids));
+
+    webhook_subscriptions_dsl::webhook_subscriptions
+        .filter(
+            webhook_subscriptions_dsl::cap_id
+                .is_null()
+                .or(webhook_subscriptions_dsl::cap_id.eq_any(&cap_ids)),
+        )
+        .filter(
+            webhook_subscriptions_dsl::package_id
+                .is_null()
+                .or(webhook_subscriptions_dsl::package_id.eq_any(&published))
+                .or(webhook_subscriptions_dsl::package_id.eq_any(cap_packages)),
+        )
+        .filter(
+            webhook_subscriptions_dsl::owner_address
+                .is_null()
+                .or(webhook_subscriptions_dsl::owner_address.eq_any(&owners)),
+        )
+        .select(WebhookSubscription::as_select())
+        .load::<WebhookSubscription>(conn)
+        .await
+}
+
+/// Queues a delivery of the events to every subscription they match. Called in the
+/// transaction committing them, so that a delivery exists for every committed event.
+pub async fn enqueue(conn: &mut AsyncPgConnection, events: &[CapEvent]) -> Result<usize, Error> {
+    if events.is_empty() {
+        return Ok(0);
+    }
+
+    let owners = events::all_owners(conn, events).await?;
+    let subscriptions = candidate_subscriptions(conn, events, &owners).await?;
+    if subscriptions.is_empty() {
+        return Ok(0);
+    }
+
+    let package_ids = subscriptions
+        .iter()
+        .filter_map(|subscription| subscription.package_id.clone())
+        .collect::<Vec<_>>();
+    let package_caps = if package_ids.is_empty() {
+        HashMap::new()
+    } else {
+        query::get_packages_cap(conn, &package_ids)
+            .await?
+            .into_iter()
+            .collect::<HashMap<_, _>>()
+    };
+
+    let filters = subscriptions
+        .iter()
+        .map(|subscription| {
+            (
+                subscription.id,
+                subscription_filter(subscription, &package_caps),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut deliveries = Vec::new();
+    for (event, owners) in events.iter().zip(&owners) {
+        let payload =
+            serde_json::to_string(event).map_err(|e| Error::SerializationError(Box::new(e)))?;
+
+        for (subscription_id, filter) in &filters {
+            if filter.matches(event, owners) {
+                deliveries.push(NewWebhookDelivery {
+                    subscription_id: *subscription_id,
+                    event_kind: event.kind().to_string(),
+                    payload: payload.clone(),
+                });
+            }
+        }
+    }
+
+    if deliveries.is_empty() {
+        return Ok(0);
+    }
+
+    diesel::insert_into(webhook_deliveries)
+        .values(&deliveries)
+        .execute(conn)
+        .await
+}
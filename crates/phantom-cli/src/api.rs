@@ -0,0 +1,119 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Risk levels of the PhantomCap risk matrix, ordered from the safest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Best,
+    Low,
+    Medium,
+    High,
+}
+
+impl std::fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RiskLevel::Best => write!(f, "Best"),
+            RiskLevel::Low => write!(f, "Low Risk"),
+            RiskLevel::Medium => write!(f, "Medium Risk"),
+            RiskLevel::High => write!(f, "High Risk"),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct Risk {
+    pub level: RiskLevel,
+    pub reasons: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct OwnerClass {
+    #[serde(rename = "type")]
+    pub kind: String,
+}
+
+#[derive(Deserialize)]
+pub struct Package {
+    pub upgrade_cap_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct Cap {
+    pub policy: String,
+    pub owner: String,
+    pub owner_class: OwnerClass,
+    pub risk: Risk,
+}
+
+#[derive(Deserialize)]
+pub struct CapVersion {
+    pub version: i64,
+    pub timestamp: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct Page<T> {
+    items: Vec<T>,
+}
+
+/// Client of the PhantomCap JSON API.
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+}
+
+impl Client {
+    /// Client of the API the instance serves for `network`.
+    pub fn new(base_url: &str, network: &str) -> Self {
+        Client {
+            http: reqwest::Client::new(),
+            base_url: format!("{}/{}/api/v1", base_url.trim_end_matches('/'), network),
+        }
+    }
+
+    /// Fetches `path`, `None` when PhantomCap doesn't know the object.
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<Option<T>> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .with_context(|| format!("Failed to request {}", url))?;
+
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+
+        let body = response
+            .error_for_status()
+            .with_context(|| format!("Request to {} failed", url))?
+            .json::<T>()
+            .await
+            .with_context(|| format!("Invalid response from {}", url))?;
+
+        Ok(Some(body))
+    }
+
+    pub async fn package(&self, package_id: &str) -> Result<Option<Package>> {
+        self.get(&format!("/packages/{}", package_id)).await
+    }
+
+    pub async fn cap(&self, cap_id: &str) -> Result<Option<Cap>> {
+        self.get(&format!("/caps/{}", cap_id)).await
+    }
+
+    pub async fn latest_version(&self, cap_id: &str) -> Result<Option<CapVersion>> {
+        let page = self
+            .get::<Page<CapVersion>>(&format!("/caps/{}/versions?limit=1", cap_id))
+            .await?;
+
+        Ok(page.and_then(|p| p.items.into_iter().next()))
+    }
+}
@@ -0,0 +1,116 @@
+mod api;
+mod manifest;
+mod report;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anyhow::Result;
+use clap::Parser;
+
+use api::{Client, RiskLevel};
+use manifest::Dependency;
+use report::{DependencyReport, Report, Status};
+
+/// Audits the UpgradeCaps of a Move project's on-chain dependencies.
+///
+/// Exits with 1 when a dependency is at or above the risk threshold, 2 on errors.
+#[derive(Parser)]
+#[command(name = "phantom-cli", version)]
+struct Args {
+    /// Directory holding the project's Move.toml / Move.lock.
+    #[arg(default_value = ".")]
+    path: PathBuf,
+
+    /// PhantomCap instance to query.
+    #[arg(long, env = "PHANTOM_API_URL", default_value = "http://localhost:8080")]
+    api_url: String,
+
+    /// Environment whose published addresses are used, and the network they're looked up on.
+    #[arg(long, default_value = "mainnet")]
+    env: String,
+
+    /// Lowest risk level failing the audit.
+    #[arg(long, value_enum, default_value_t = RiskLevel::High)]
+    fail_on: RiskLevel,
+
+    /// Also fail on dependencies that can't be resolved or aren't indexed.
+    #[arg(long)]
+    strict: bool,
+
+    /// Print the report as JSON.
+    #[arg(long)]
+    json: bool,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
+
+    match run(&args).await {
+        Ok(report) if report.failed => ExitCode::from(1),
+        Ok(_) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {:#}", e);
+            ExitCode::from(2)
+        }
+    }
+}
+
+async fn run(args: &Args) -> Result<Report> {
+    let dependencies = manifest::resolve_dependencies(&args.path, &args.env)?;
+    let client = Client::new(&args.api_url, &args.env);
+
+    let mut reports = Vec::with_capacity(dependencies.len());
+    for dependency in dependencies {
+        reports.push(assess(&client, dependency).await?);
+    }
+
+    let report = Report::new(args.fail_on, args.strict, reports);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        report.print_text();
+    }
+
+    Ok(report)
+}
+
+async fn assess(client: &Client, dependency: Dependency) -> Result<DependencyReport> {
+    let Dependency {
+        name,
+        source,
+        package_id,
+    } = dependency;
+
+    let Some(id) = package_id.as_deref() else {
+        return Ok(DependencyReport::unassessed(name, source, package_id));
+    };
+    let Some(package) = client.package(id).await? else {
+        return Ok(DependencyReport::unassessed(name, source, package_id));
+    };
+    let Some(cap) = client.cap(&package.upgrade_cap_id).await? else {
+        return Ok(DependencyReport::unassessed(name, source, package_id));
+    };
+
+    // the first version is the publish itself.
+    let last_upgrade = client
+        .latest_version(&package.upgrade_cap_id)
+        .await?
+        .filter(|v| v.version > 1)
+        .map(|v| v.timestamp);
+
+    Ok(DependencyReport {
+        name,
+        source,
+        package_id,
+        status: Status::Assessed,
+        upgrade_cap_id: Some(package.upgrade_cap_id),
+        policy: Some(cap.policy),
+        owner: Some(cap.owner),
+        owner_type: Some(cap.owner_class.kind),
+        last_upgrade,
+        risk: Some(cap.risk.level),
+        reasons: cap.risk.reasons,
+    })
+}
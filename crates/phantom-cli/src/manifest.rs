@@ -0,0 +1,486 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use toml::{Table, Value};
+
+/// Packages shipped with Sui, upgraded by the validators rather than through an UpgradeCap.
+const FRAMEWORK_PACKAGES: [&str; 5] = ["MoveStdlib", "Sui", "SuiSystem", "Bridge", "DeepBook"];
+const FRAMEWORK_ADDRESSES: [&str; 3] = ["0x1", "0x2", "0x3"];
+
+/// Dependency of the audited project.
+pub struct Dependency {
+    pub name: String,
+    /// Where the dependency comes from, as written in the lock file.
+    pub source: String,
+    /// Published address, `None` when it couldn't be resolved.
+    pub package_id: Option<String>,
+}
+
+/// Where a dependency's sources live.
+enum Source {
+    Local(PathBuf),
+    Git {
+        url: String,
+        rev: String,
+        subdir: String,
+    },
+}
+
+impl Source {
+    fn parse(project: &Path, value: &Value) -> Option<Self> {
+        let table = value.as_table()?;
+
+        if let Some(local) = table.get("local").and_then(Value::as_str) {
+            return Some(Source::Local(project.join(local)));
+        }
+
+        Some(Source::Git {
+            url: table.get("git")?.as_str()?.to_string(),
+            rev: table.get("rev")?.as_str()?.to_string(),
+            subdir: table
+                .get("subdir")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        })
+    }
+
+    /// Directory holding the dependency's manifest, git dependencies are
+    /// looked up in the cache the Sui CLI fills on build.
+    fn directory(&self) -> Option<PathBuf> {
+        match self {
+            Source::Local(path) => Some(path.clone()),
+            Source::Git { url, rev, subdir } => {
+                let name = format!("{}_{}", url_to_file_name(url), rev.replace('/', "__"));
+                Some(move_home()?.join(name).join(subdir))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Local(path) => write!(f, "{}", path.display()),
+            Source::Git { url, rev, .. } => write!(f, "{}@{}", url, rev),
+        }
+    }
+}
+
+fn move_home() -> Option<PathBuf> {
+    match std::env::var("MOVE_HOME") {
+        Ok(home) => Some(PathBuf::from(home)),
+        Err(_) => Some(PathBuf::from(std::env::var("HOME").ok()?).join(".move")),
+    }
+}
+
+fn url_to_file_name(url: &str) -> String {
+    url.replace(['/', ':', '.', '@'], "_")
+}
+
+fn read_toml(path: &Path) -> Result<Option<Table>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let table = content
+        .parse::<Table>()
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+    Ok(Some(table))
+}
+
+fn get<'a>(table: &'a Table, path: &[&str]) -> Option<&'a Value> {
+    let (last, parents) = path.split_last()?;
+    let mut table = table;
+    for key in parents {
+        table = table.get(*key)?.as_table()?;
+    }
+    table.get(*last)
+}
+
+/// Every dependency of the project at `project`, transitive ones included
+/// when a `Move.lock` is present, direct ones from `Move.toml` otherwise.
+pub fn resolve_dependencies(project: &Path, env: &str) -> Result<Vec<Dependency>> {
+    let sources = match read_toml(&project.join("Move.lock"))? {
+        Some(lock) => lock_sources(project, &lock, env),
+        None => {
+            let manifest = read_toml(&project.join("Move.toml"))?
+                .ok_or_else(|| anyhow!("No Move.lock or Move.toml in {}", project.display()))?;
+            manifest_sources(project, &manifest)
+        }
+    };
+
+    let mut dependencies = vec![];
+    for (name, source) in sources {
+        if FRAMEWORK_PACKAGES.contains(&name.as_str()) {
+            continue;
+        }
+
+        let package_id = match source.as_ref().and_then(Source::directory) {
+            Some(dir) => published_id(&dir, &name, env)?,
+            None => None,
+        };
+        if package_id
+            .as_deref()
+            .is_some_and(|id| FRAMEWORK_ADDRESSES.contains(&short_address(id).as_str()))
+        {
+            continue;
+        }
+
+        dependencies.push(Dependency {
+            name,
+            source: source.map_or("unknown".to_string(), |s| s.to_string()),
+            package_id,
+        });
+    }
+
+    Ok(dependencies)
+}
+
+/// Dependencies pinned in a lock file, `[[move.package]]` entries of the
+/// older format or `[pinned.<env>]` entries of the newer one.
+fn lock_sources(project: &Path, lock: &Table, env: &str) -> Vec<(String, Option<Source>)> {
+    if let Some(packages) = get(lock, &["move", "package"]).and_then(Value::as_array) {
+        return packages
+            .iter()
+            .filter_map(|package| {
+                let package = package.as_table()?;
+                let name = package
+                    .get("id")
+                    .or_else(|| package.get("name"))?
+                    .as_str()?
+                    .to_string();
+                let source = package
+                    .get("source")
+                    .and_then(|s| Source::parse(project, s));
+                Some((name, source))
+            })
+            .collect();
+    }
+
+    get(lock, &["pinned", env])
+        .and_then(Value::as_table)
+        .map(|pinned| {
+            pinned
+                .iter()
+                .map(|(name, package)| {
+                    let source = package
+                        .get("source")
+                        .and_then(|s| Source::parse(project, s));
+                    (name.clone(), source)
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn manifest_sources(project: &Path, manifest: &Table) -> Vec<(String, Option<Source>)> {
+    manifest
+        .get("dependencies")
+        .and_then(Value::as_table)
+        .map(|dependencies| {
+            dependencies
+                .iter()
+                .map(|(name, source)| (name.clone(), Source::parse(project, source)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Latest published address of the package in `dir`, from its lock file for
+/// `env`, its `Published.toml`, the `published-at` field or its named address.
+fn published_id(dir: &Path, name: &str, env: &str) -> Result<Option<String>> {
+    let lock = read_toml(&dir.join("Move.lock"))?;
+    let published = read_toml(&dir.join("Published.toml"))?;
+    let manifest = read_toml(&dir.join("Move.toml"))?;
+
+    let candidates = [
+        lock.as_ref()
+            .and_then(|l| get(l, &["env", env, "latest-published-id"])),
+        published
+            .as_ref()
+            .and_then(|p| get(p, &["published", env, "published-at"])),
+        manifest
+            .as_ref()
+            .and_then(|m| get(m, &["package", "published-at"])),
+        manifest
+            .as_ref()
+            .and_then(|m| get(m, &["addresses", &name.to_lowercase()]))
+            .or_else(|| manifest.as_ref().and_then(|m| get(m, &["addresses", name]))),
+    ];
+
+    Ok(candidates
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .find(|id| short_address(id) != "0x0")
+        .map(str::to_string))
+}
+
+/// Address without leading zeros, `0x0` for unpublished `_` addresses.
+fn short_address(id: &str) -> String {
+    let hex = id.trim_start_matches("0x").trim_start_matches('0');
+    if hex.is_empty() || hex == "_" {
+        "0x0".to_string()
+    } else {
+        format!("0x{}", hex)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Project directory removed once the test is over.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("phantom-cli-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn write(&self, path: &str, content: &str) {
+            let path = self.0.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn sources(sources: Vec<(String, Option<Source>)>) -> Vec<(String, Option<String>)> {
+        let mut sources = sources
+            .into_iter()
+            .map(|(name, source)| (name, source.map(|s| s.to_string())))
+            .collect::<Vec<_>>();
+        sources.sort();
+        sources
+    }
+
+    #[test]
+    fn lock_sources_of_the_older_format() {
+        let project = Path::new("/project");
+        let lock = r#"
+            [move]
+            version = 2
+
+            [[move.package]]
+            id = "Sui"
+            source = { git = "https://github.com/MystenLabs/sui.git", rev = "framework/mainnet", subdir = "crates/sui-framework/packages/sui-framework" }
+
+            [[move.package]]
+            id = "Oracle"
+            source = { local = "../oracle" }
+
+            [[move.package]]
+            name = "Legacy"
+        "#
+        .parse::<Table>()
+        .unwrap();
+
+        assert_eq!(
+            sources(lock_sources(project, &lock, "mainnet")),
+            [
+                ("Legacy".to_string(), None),
+                (
+                    "Oracle".to_string(),
+                    Some(project.join("../oracle").display().to_string())
+                ),
+                (
+                    "Sui".to_string(),
+                    Some("https://github.com/MystenLabs/sui.git@framework/mainnet".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn lock_sources_of_the_newer_format() {
+        let project = Path::new("/project");
+        let lock = r#"
+            [pinned.mainnet.Oracle]
+            source = { local = "../oracle" }
+
+            [pinned.mainnet.Token]
+            source = { git = "https://github.com/org/token.git", rev = "abc123", subdir = "move" }
+
+            [pinned.testnet.Faucet]
+            source = { local = "../faucet" }
+        "#
+        .parse::<Table>()
+        .unwrap();
+
+        assert_eq!(
+            sources(lock_sources(project, &lock, "mainnet")),
+            [
+                (
+                    "Oracle".to_string(),
+                    Some(project.join("../oracle").display().to_string())
+                ),
+                (
+                    "Token".to_string(),
+                    Some("https://github.com/org/token.git@abc123".to_string())
+                ),
+            ]
+        );
+        assert!(lock_sources(project, &lock, "devnet").is_empty());
+    }
+
+    #[test]
+    fn manifest_sources_of_direct_dependencies() {
+        let project = Path::new("/project");
+        let manifest = r#"
+            [package]
+            name = "app"
+
+            [dependencies]
+            Oracle = { local = "../oracle" }
+            Token = { git = "https://github.com/org/token.git", rev = "main" }
+            Broken = { git = "https://github.com/org/broken.git" }
+        "#
+        .parse::<Table>()
+        .unwrap();
+
+        assert_eq!(
+            sources(manifest_sources(project, &manifest)),
+            [
+                ("Broken".to_string(), None),
+                (
+                    "Oracle".to_string(),
+                    Some(project.join("../oracle").display().to_string())
+                ),
+                (
+                    "Token".to_string(),
+                    Some("https://github.com/org/token.git@main".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn published_id_sources_by_priority() {
+        let dir = TempDir::new("published-id");
+        let id = |dir: &TempDir| published_id(&dir.0, "Oracle", "mainnet").unwrap();
+
+        assert_eq!(id(&dir), None);
+
+        dir.write(
+            "Move.toml",
+            r#"
+            [package]
+            name = "Oracle"
+
+            [addresses]
+            oracle = "0x4"
+            "#,
+        );
+        assert_eq!(id(&dir).as_deref(), Some("0x4"));
+
+        dir.write(
+            "Move.toml",
+            r#"
+            [package]
+            name = "Oracle"
+            published-at = "0x3"
+
+            [addresses]
+            oracle = "0x4"
+            "#,
+        );
+        assert_eq!(id(&dir).as_deref(), Some("0x3"));
+
+        dir.write(
+            "Published.toml",
+            r#"
+            [published.mainnet]
+            published-at = "0x2"
+
+            [published.testnet]
+            published-at = "0x22"
+            "#,
+        );
+        assert_eq!(id(&dir).as_deref(), Some("0x2"));
+
+        dir.write(
+            "Move.lock",
+            r#"
+            [env.mainnet]
+            latest-published-id = "0x1"
+            "#,
+        );
+        assert_eq!(id(&dir).as_deref(), Some("0x1"));
+        assert_eq!(
+            published_id(&dir.0, "Oracle", "testnet")
+                .unwrap()
+                .as_deref(),
+            Some("0x22")
+        );
+    }
+
+    #[test]
+    fn published_id_skips_unpublished_addresses() {
+        let dir = TempDir::new("unpublished");
+        dir.write(
+            "Move.toml",
+            r#"
+            [package]
+            name = "Oracle"
+            published-at = "0x0"
+
+            [addresses]
+            Oracle = "_"
+            "#,
+        );
+
+        assert_eq!(published_id(&dir.0, "Oracle", "mainnet").unwrap(), None);
+    }
+
+    #[test]
+    fn resolve_dependencies_leaves_out_the_framework() {
+        let dir = TempDir::new("resolve");
+        dir.write(
+            "app/Move.lock",
+            r#"
+            [pinned.mainnet.Sui]
+            source = { local = "../sui" }
+
+            [pinned.mainnet.Oracle]
+            source = { local = "../oracle" }
+
+            [pinned.mainnet.Wrapper]
+            source = { local = "../wrapper" }
+            "#,
+        );
+        dir.write("sui/Move.toml", "[addresses]\nsui = \"0x2\"\n");
+        dir.write("oracle/Move.toml", "[addresses]\noracle = \"0xabc\"\n");
+        // a renamed framework package is still left out by its address.
+        dir.write("wrapper/Move.toml", "[addresses]\nwrapper = \"0x0002\"\n");
+
+        let dependencies = resolve_dependencies(&dir.0.join("app"), "mainnet").unwrap();
+
+        assert_eq!(dependencies.len(), 1);
+        assert_eq!(dependencies[0].name, "Oracle");
+        assert_eq!(dependencies[0].package_id.as_deref(), Some("0xabc"));
+    }
+
+    #[test]
+    fn short_addresses() {
+        assert_eq!(short_address("0x0000abc"), "0xabc");
+        assert_eq!(short_address("0x2"), "0x2");
+        assert_eq!(short_address("0x0"), "0x0");
+        assert_eq!(short_address("0x"), "0x0");
+        assert_eq!(short_address("_"), "0x0");
+    }
+}
@@ -0,0 +1,214 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::api::RiskLevel;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Assessed,
+    /// The published address is known but PhantomCap hasn't indexed it.
+    NotIndexed,
+    /// No published address was found for the dependency.
+    Unresolved,
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Assessed => write!(f, "Assessed"),
+            Status::NotIndexed => write!(f, "Not indexed"),
+            Status::Unresolved => write!(f, "Unresolved"),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct DependencyReport {
+    pub name: String,
+    pub source: String,
+    pub package_id: Option<String>,
+    pub status: Status,
+    pub upgrade_cap_id: Option<String>,
+    pub policy: Option<String>,
+    pub owner: Option<String>,
+    pub owner_type: Option<String>,
+    /// `None` when never upgraded since publish.
+    pub last_upgrade: Option<DateTime<Utc>>,
+    pub risk: Option<RiskLevel>,
+    pub reasons: Vec<String>,
+}
+
+impl DependencyReport {
+    pub fn unassessed(name: String, source: String, package_id: Option<String>) -> Self {
+        let status = match package_id {
+            Some(_) => Status::NotIndexed,
+            None => Status::Unresolved,
+        };
+
+        DependencyReport {
+            name,
+            source,
+            package_id,
+            status,
+            upgrade_cap_id: None,
+            policy: None,
+            owner: None,
+            owner_type: None,
+            last_upgrade: None,
+            risk: None,
+            reasons: vec![],
+        }
+    }
+
+    /// Whether the dependency fails the build for the given threshold.
+    pub fn fails(&self, threshold: RiskLevel, strict: bool) -> bool {
+        match self.risk {
+            Some(risk) => risk >= threshold,
+            None => strict,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Report {
+    pub threshold: RiskLevel,
+    pub strict: bool,
+    pub failed: bool,
+    pub dependencies: Vec<DependencyReport>,
+}
+
+impl Report {
+    pub fn new(threshold: RiskLevel, strict: bool, dependencies: Vec<DependencyReport>) -> Self {
+        Report {
+            threshold,
+            strict,
+            failed: dependencies.iter().any(|d| d.fails(threshold, strict)),
+            dependencies,
+        }
+    }
+
+    pub fn print_text(&self) {
+        let now = Utc::now();
+
+        print_row([
+            "DEPENDENCY",
+            "PACKAGE",
+            "POLICY",
+            "OWNER",
+            "OWNER TYPE",
+            "LAST UPGRADE",
+            "RISK",
+        ]);
+
+        for d in &self.dependencies {
+            let risk = match d.risk {
+                Some(risk) => risk.to_string(),
+                None => d.status.to_string(),
+            };
+            let last_upgrade = match (d.status, d.last_upgrade) {
+                (Status::Assessed, Some(t)) => {
+                    format!("{}d ago", now.signed_duration_since(t).num_days())
+                }
+                (Status::Assessed, None) => "never".to_string(),
+                _ => "-".to_string(),
+            };
+            let marker = if d.fails(self.threshold, self.strict) {
+                " !"
+            } else {
+                ""
+            };
+
+            print_row([
+                &d.name,
+                &short_id(d.package_id.as_deref()),
+                d.policy.as_deref().unwrap_or("-"),
+                &short_id(d.owner.as_deref()),
+                d.owner_type.as_deref().unwrap_or("-"),
+                &last_upgrade,
+                &format!("{}{}", risk, marker),
+            ]);
+        }
+
+        println!();
+        if self.failed {
+            println!(
+                "FAILED: dependencies marked with ! are at or above {}",
+                self.threshold
+            );
+        } else {
+            println!("OK: no dependency is at or above {}", self.threshold);
+        }
+    }
+}
+
+fn print_row(columns: [&str; 7]) {
+    let [name, package, policy, owner, owner_type, last_upgrade, risk] = columns;
+    println!(
+        "{:<24} {:<14} {:<12} {:<14} {:<12} {:<14} {}",
+        name, package, policy, owner, owner_type, last_upgrade, risk
+    );
+}
+
+fn short_id(id: Option<&str>) -> String {
+    match id {
+        Some(id) if id.len() > 12 => format!("{}…{}", &id[..6], &id[id.len() - 4..]),
+        Some(id) => id.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependency(risk: Option<RiskLevel>) -> DependencyReport {
+        DependencyReport {
+            risk,
+            ..DependencyReport::unassessed("Oracle".to_string(), "../oracle".to_string(), None)
+        }
+    }
+
+    #[test]
+    fn fails_at_or_above_the_threshold() {
+        for (risk, threshold, fails) in [
+            (RiskLevel::Best, RiskLevel::Low, false),
+            (RiskLevel::Low, RiskLevel::Low, true),
+            (RiskLevel::Medium, RiskLevel::High, false),
+            (RiskLevel::High, RiskLevel::High, true),
+            (RiskLevel::High, RiskLevel::Best, true),
+        ] {
+            for strict in [false, true] {
+                assert_eq!(
+                    dependency(Some(risk)).fails(threshold, strict),
+                    fails,
+                    "{} at {} threshold",
+                    risk,
+                    threshold
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unassessed_fail_only_when_strict() {
+        let unassessed = dependency(None);
+
+        assert!(!unassessed.fails(RiskLevel::Best, false));
+        assert!(unassessed.fails(RiskLevel::High, true));
+
+        assert!(!Report::new(RiskLevel::High, false, vec![dependency(None)]).failed);
+        assert!(Report::new(RiskLevel::High, true, vec![dependency(None)]).failed);
+        assert!(
+            Report::new(
+                RiskLevel::Medium,
+                false,
+                vec![
+                    dependency(Some(RiskLevel::Low)),
+                    dependency(Some(RiskLevel::Medium))
+                ]
+            )
+            .failed
+        );
+    }
+}